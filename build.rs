@@ -11,6 +11,9 @@ fn main() {
         .file(schemas_directory.join("filesystem.capnp"))
         .file(schemas_directory.join("file.capnp"))
         .file(schemas_directory.join("tag.capnp"))
+        .file(schemas_directory.join("content.capnp"))
+        .file(schemas_directory.join("trash.capnp"))
+        .file(schemas_directory.join("inode.capnp"))
         .run()
         .unwrap();
 }
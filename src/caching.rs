@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::inodes::{get_is_inode_root, FileInode, NamespaceInode, TagInode};
+
+/// For entries that can go stale without the filesystem noticing, e.g., files under
+/// namespaces that compare against the current time.
+pub const NO_TTL: Duration = Duration::ZERO;
+
+/// How long the kernel can trust the entries and attributes it was replied, per
+/// kind of entry.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct EntryTtls {
+    #[serde(with = "humantime_serde")]
+    pub root: Duration,
+    /// Kept short, as a file's size and times come from its delegate.
+    #[serde(with = "humantime_serde")]
+    pub file: Duration,
+    #[serde(with = "humantime_serde")]
+    pub tag: Duration,
+    #[serde(with = "humantime_serde")]
+    pub namespace: Duration
+}
+
+impl EntryTtls {
+    pub fn get(&self, inode_id: u64) -> Duration {
+        if get_is_inode_root(inode_id) {
+            self.root
+        } else if FileInode::get_is_file(inode_id) {
+            self.file
+        } else if TagInode::get_is_tag(inode_id) {
+            self.tag
+        } else if NamespaceInode::get_is_namespace(inode_id) {
+            self.namespace
+        } else {
+            NO_TTL
+        }
+    }
+}
+
+impl Default for EntryTtls {
+    fn default() -> Self {
+        Self {
+            root: Duration::from_secs(60),
+            file: Duration::from_secs(1),
+            tag: Duration::from_secs(10),
+            namespace: Duration::from_secs(10)
+        }
+    }
+}
+
+/// What the kernel is allowed to cache for a mount.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct CachingPolicy {
+    pub ttls: EntryTtls,
+    /// Lets the kernel buffer writes and send them later, in bigger chunks.
+    pub writeback_cache: bool,
+    /// Keeps a file's cached content when it's opened again, i.e., `FOPEN_KEEP_CACHE`,
+    /// as its content only changes through the mount.
+    pub keep_cache: bool,
+    /// Lets the kernel look up entries while listing them, in one request.
+    pub readdir_plus: bool
+}
+
+impl Default for CachingPolicy {
+    fn default() -> Self {
+        Self {
+            ttls: EntryTtls::default(),
+            writeback_cache: false,
+            keep_cache: true,
+            readdir_plus: false
+        }
+    }
+}
@@ -0,0 +1,25 @@
+pub mod show;
+
+use clap::{Parser, Subcommand};
+
+use crate::{cli::{config::show::ShowParameters, ProgramParameters}, errors::ResultBtAny};
+
+/// Inspects `config.toml`, or whichever file `--config` points at.
+#[derive(Parser, Debug)]
+pub struct ConfigParameters {
+    #[command(subcommand)]
+    pub subcommand: ConfigSubcommand
+}
+
+impl ConfigParameters {
+    pub fn run(&self, program_arguments: &ProgramParameters) -> ResultBtAny<()> {
+        match &self.subcommand {
+            ConfigSubcommand::Show(show_arguments) => show_arguments.run(program_arguments)
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigSubcommand {
+    Show(ShowParameters)
+}
@@ -0,0 +1,31 @@
+use std::{fs::canonicalize, path::PathBuf};
+
+use clap::Args;
+
+use crate::{cli::ProgramParameters, configuration::MountConfiguration, errors::ResultBtAny};
+
+/// Prints the settings in effect, as TOML.
+#[derive(Args, Debug)]
+pub struct ShowParameters {
+    /// Mount to show the settings of, rather than the ones every mount starts from.
+    pub mount_path: Option<PathBuf>
+}
+
+impl ShowParameters {
+    pub fn run(&self, program_arguments: &ProgramParameters) -> ResultBtAny<()> {
+        print!("{}", self.get_effective_configuration(program_arguments)?);
+        Ok(())
+    }
+
+    pub fn get_effective_configuration(&self, program_arguments: &ProgramParameters)
+    -> ResultBtAny<String> {
+        let configuration = &program_arguments.configuration;
+        let mount_configuration: MountConfiguration = match &self.mount_path {
+            // Mounts are configured by their canonical path, as long as it exists.
+            Some(mount_path) => configuration.get_mount(&canonicalize(mount_path)
+                .unwrap_or(mount_path.clone())),
+            None => configuration.global.clone()
+        };
+        Ok(toml::to_string(&mount_configuration)?)
+    }
+}
@@ -0,0 +1,68 @@
+use std::{collections::BTreeSet, iter, path::{Path, PathBuf}};
+
+use clap::Parser;
+
+use crate::{cli::{store::StoreParameters, tags::setup::DEFAULT_SCRIPT_NAME,
+    ProgramParameters}, doctor::{check_allow_other, check_change_script, check_directory,
+    check_fuse_device, check_fusermount, check_store, Diagnosis, Severity,
+    FUSE_CONFIGURATION_PATH, FUSE_DEVICE_PATH}, errors::ResultBtAny,
+    path::get_configuration_directory, stores::StoreRegistry};
+
+/// Checks what mounting needs, and the health of every store, suggesting fixes.
+#[derive(Parser, Debug)]
+pub struct DoctorParameters {
+    /// Where `tfs tags setup` wrote the `ct` script.
+    #[arg(long, default_value = get_configuration_directory()
+        .join(DEFAULT_SCRIPT_NAME)
+        .into_os_string())]
+    pub script_path: PathBuf
+}
+
+impl DoctorParameters {
+    pub fn run(&self, program_arguments: &ProgramParameters) -> ResultBtAny<()> {
+        let configuration = &program_arguments.configuration;
+        let mount_configurations = iter::once(&configuration.global)
+            .chain(configuration.mounts.values());
+        let is_allow_other_requested = mount_configurations.clone()
+            .any(|mount_configuration| mount_configuration.mount_options.allow_root
+                || mount_configuration.mount_options.allow_other);
+        let mut diagnoses = vec![
+            check_fuse_device(Path::new(FUSE_DEVICE_PATH)),
+            check_fusermount(),
+            check_allow_other(Path::new(FUSE_CONFIGURATION_PATH), is_allow_other_requested),
+            check_directory("Configuration directory", &get_configuration_directory())
+        ];
+
+        let data_directories: BTreeSet<_> = mount_configurations.clone()
+            .map(|mount_configuration| mount_configuration.paths.get_data_directory())
+            .collect();
+        let log_directories: BTreeSet<_> = mount_configurations
+            .map(|mount_configuration| mount_configuration.paths.get_log_directory())
+            .collect();
+        diagnoses.extend(data_directories.iter()
+            .map(|data_directory| check_directory("Data directory", data_directory)));
+        diagnoses.extend(log_directories.iter()
+            .map(|log_directory| check_directory("Log directory", log_directory)));
+
+        let data_directory = StoreParameters::get_data_directory(program_arguments);
+        match StoreRegistry::load(&data_directory) {
+            Ok(store_registry) => diagnoses.extend(store_registry.get_all()
+                .flat_map(|(store_name, _)| check_store(store_name, &data_directory))),
+            Err(e) => diagnoses.push(Diagnosis::problem(format!("The store registry in \
+                `{}` can't be read, `{}`.", data_directory.to_string_lossy(), e.error),
+                "Restore `stores.json` from a backup."))
+        }
+        diagnoses.push(check_change_script(&self.script_path));
+
+        for diagnosis in &diagnoses {
+            println!("{diagnosis}");
+        }
+        let problem_count = diagnoses.iter()
+            .filter(|diagnosis| diagnosis.severity == Severity::Problem)
+            .count();
+        if problem_count > 0 {
+            Err(format!("Found `{problem_count}` problems."))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use std::{fs, path::PathBuf};
+
+use clap::Parser;
+
+use crate::{cli::ProgramParameters, errors::ResultBtAny, failures::{LAST_ERROR_NAME,
+    TFS_DIRECTORY_NAME}, mounts::{get_active_mounts, get_is_stale}};
+
+/// Shows the most recent failures of each process that's had any, as the kernel only
+/// passes on their errno.
+#[derive(Parser, Debug)]
+pub struct ErrorsParameters {
+    /// Only this mount's, rather than every active mount's.
+    pub mount_path: Option<PathBuf>
+}
+
+impl ErrorsParameters {
+    pub fn run(&self, _program_arguments: &ProgramParameters) -> ResultBtAny<()> {
+        let mount_paths = match &self.mount_path {
+            Some(mount_path) => vec![fs::canonicalize(mount_path)
+                .unwrap_or_else(|_| mount_path.clone())],
+            None => get_active_mounts()?.into_iter()
+                .map(|(mount_path, _)| mount_path)
+                .collect()
+        };
+        let is_listing_all = self.mount_path.is_none();
+        for mount_path in mount_paths {
+            let _mount_path = mount_path.to_string_lossy();
+            if is_listing_all {
+                println!("Mount `{_mount_path}`:");
+            }
+            if get_is_stale(&mount_path) {
+                println!("Stale, its process is gone.");
+                continue;
+            }
+            let failures = fs::read_to_string(mount_path.join(TFS_DIRECTORY_NAME)
+                .join(LAST_ERROR_NAME))
+                .map_err(|e| format!("Couldn't read failures of \
+                    `{_mount_path}`, is it a TFS mount? {e}"))?;
+            if failures.is_empty() {
+                println!("No failures.");
+            } else {
+                print!("{failures}");
+            }
+        }
+        Ok(())
+    }
+}
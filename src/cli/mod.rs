@@ -1,32 +1,65 @@
+pub mod config;
+pub mod doctor;
+pub mod errors;
 pub mod mount;
+pub mod status;
+pub mod store;
 pub mod tags;
+pub mod trash;
+pub mod unmount;
 
-use std::fs::{self, create_dir_all};
+use std::{fs::{self, create_dir_all}, path::PathBuf};
 
 use clap::{Parser, Subcommand};
-use crate::{cli::{mount::MountParameters, tags::TagsParameters}, errors::ResultBtAny,
+use crate::{cli::{config::ConfigParameters, doctor::DoctorParameters,
+    errors::ErrorsParameters, mount::MountParameters, status::StatusParameters,
+    store::StoreParameters, tags::TagsParameters, trash::TrashParameters,
+    unmount::UnmountParameters},
+    configuration::TfsConfiguration, errors::ResultBtAny,
     path::get_configuration_directory};
 
 #[derive(Parser, Debug)]
 pub struct ProgramParameters {
     #[arg(short, long)]
     pub dry: bool,
+    /// Prints backtraces along with errors.
+    #[arg(short, long)]
+    pub verbose: bool,
+    /// Configuration file to use instead of `~/.tag_filesystem/config.toml`.
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Loaded from `config`, once running.
+    #[arg(skip)]
+    pub configuration: TfsConfiguration,
     #[command(subcommand)]
-    pub subcommand: ProgramSubcommands 
+    pub subcommand: ProgramSubcommands
 }
 
 impl ProgramParameters {
     pub fn run(&mut self) -> ResultBtAny<()> {
         let configuration_directory = get_configuration_directory();
         create_dir_all(configuration_directory)?;
+        self.configuration = TfsConfiguration::load(&self.config.clone()
+            .unwrap_or_else(TfsConfiguration::get_default_path))?;
 
         if let ProgramSubcommands::Mount(ref mut mount_arguments) = self.subcommand {
+            mount_arguments.check_if_stale(self.dry)?;
             mount_arguments.mount_path = fs::canonicalize(&mount_arguments.mount_path)?;
         }
+        if let ProgramSubcommands::Trash(ref mut trash_arguments) = self.subcommand {
+            trash_arguments.mount_path = fs::canonicalize(&trash_arguments.mount_path)?;
+        }
 
         match &self.subcommand {
             ProgramSubcommands::Mount(mount_arguments) => mount_arguments.run(self),
-            ProgramSubcommands::Tags(tag_arguments) => tag_arguments.run(self)
+            ProgramSubcommands::Tags(tag_arguments) => tag_arguments.run(self),
+            ProgramSubcommands::Trash(trash_arguments) => trash_arguments.run(self),
+            ProgramSubcommands::Config(config_arguments) => config_arguments.run(self),
+            ProgramSubcommands::Store(store_arguments) => store_arguments.run(self),
+            ProgramSubcommands::Status(status_arguments) => status_arguments.run(self),
+            ProgramSubcommands::Unmount(unmount_arguments) => unmount_arguments.run(self),
+            ProgramSubcommands::Doctor(doctor_arguments) => doctor_arguments.run(self),
+            ProgramSubcommands::Errors(errors_arguments) => errors_arguments.run(self)
         }
     }
 }
@@ -34,5 +67,12 @@ impl ProgramParameters {
 #[derive(Subcommand, Debug)]
 pub enum ProgramSubcommands {
     Mount(MountParameters),
-    Tags(TagsParameters)
+    Tags(TagsParameters),
+    Trash(TrashParameters),
+    Config(ConfigParameters),
+    Store(StoreParameters),
+    Status(StatusParameters),
+    Unmount(UnmountParameters),
+    Doctor(DoctorParameters),
+    Errors(ErrorsParameters)
 }
@@ -1,23 +1,36 @@
 pub mod plain;
 pub mod systemd;
 
-use std::path::PathBuf;
+use std::{path::{self, PathBuf}, time::Duration};
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use tracing::info;
 
 use crate::{cli::{mount::{plain::PlainParameters, systemd::SystemdParamereters},
-    ProgramParameters}, errors::ResultBtAny, tracing::setup_normal_tracing, wrappers::PathExt};
+    ProgramParameters}, configuration::{MountConfiguration, StorageBackend},
+    errors::ResultBtAny, mounts::{detach_stale_mount, get_is_stale},
+    stores::{StoreLocation, StoreRegistry},
+    tracing::setup_normal_tracing};
 
+/// Mounts a tag filesystem at `mount_path`.
 #[derive(Parser, Debug)]
 pub struct MountParameters {
     pub mount_path: PathBuf,
+    /// Lazily unmounts what a killed mount left at `mount_path` before mounting.
+    #[arg(long)]
+    pub recover_stale: bool,
+    #[command(flatten)]
+    pub overrides: MountArguments,
     #[command(subcommand)]
     pub subcommand: MountSubcommand
 }
 
 impl MountParameters {
     pub fn run(&self, program_arguments: &ProgramParameters) -> ResultBtAny<()> {
-        setup_normal_tracing(self.mount_path.__strip_prefix("/"));
+        let configuration = self.get_configuration(program_arguments);
+        let store_location = self.get_store_location(&configuration)?;
+        setup_normal_tracing(&configuration.paths.get_log_directory(),
+            store_location.get_log_scoping(), &configuration.logging);
 
         match &self.subcommand {
             MountSubcommand::Systemd(systemd_argument) =>
@@ -26,6 +39,42 @@ impl MountParameters {
                 plain_arguments.run(program_arguments, &self)
         }
     }
+
+    /// What a killed mount leaves behind can't even be canonicalized, so it's dealt
+    /// with before anything else.
+    pub fn check_if_stale(&self, is_dry: bool) -> ResultBtAny<()> {
+        let mount_path = path::absolute(&self.mount_path)?;
+        if !get_is_stale(&mount_path) {
+            return Ok(());
+        }
+        let _mount_path = mount_path.to_string_lossy();
+        if !self.recover_stale {
+            Err(format!("`{_mount_path}` is a stale mount, left by a process that's gone. \
+                Pass `--recover-stale` to unmount it first, or run `tfs unmount \
+                {_mount_path}`."))?;
+        }
+        if is_dry {
+            info!("Would have detached stale mount `{_mount_path}`.");
+            return Ok(());
+        }
+        detach_stale_mount(&mount_path)
+    }
+
+    /// The mount's settings from the configuration file, with the flags on top.
+    pub fn get_configuration(&self, program_arguments: &ProgramParameters)
+    -> MountConfiguration {
+        self.overrides.apply(program_arguments.configuration.get_mount(&self.mount_path))
+    }
+
+    /// The named store if there's one, otherwise the data kept for the mount's path.
+    pub fn get_store_location(&self, configuration: &MountConfiguration)
+    -> ResultBtAny<StoreLocation> {
+        match &configuration.storage.store {
+            Some(store_name) => StoreRegistry::load(&configuration.paths.get_data_directory())?
+                .get_location(store_name),
+            None => Ok(StoreLocation::MountPath(self.mount_path.clone()))
+        }
+    }
 }
 
 #[derive(Subcommand, Debug)]
@@ -33,3 +82,82 @@ pub enum MountSubcommand {
     Plain(PlainParameters),
     Systemd(SystemdParamereters)
 }
+
+/// Overrides the mount's settings from the configuration file, apart from caching,
+/// which `plain` takes.
+#[derive(Args, Debug, Default)]
+pub struct MountArguments {
+    /// Where file content is kept.
+    #[arg(long, value_enum)]
+    pub storage_backend: Option<StorageBackend>,
+    /// Named store to mount, as created with `tfs store create`.
+    #[arg(long, value_name = "NAME")]
+    pub store: Option<String>,
+    /// Under which snapshots and delegate storage are kept.
+    #[arg(long, value_name = "PATH")]
+    pub data_directory: Option<PathBuf>,
+    /// Under which rotated logs are kept.
+    #[arg(long, value_name = "PATH")]
+    pub log_directory: Option<PathBuf>,
+    /// How often the mount checks for signals and whether to persist, e.g., `1s`.
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub loop_interval: Option<Duration>,
+    /// How long the mount waits between writing snapshots, e.g., `5s`.
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub persist_interval: Option<Duration>,
+    /// How long trashed items are kept before being purged, e.g., `30days`.
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub trash_retention: Option<Duration>,
+    /// Whether to unmount once the process exits.
+    #[arg(long, value_name = "BOOL")]
+    pub auto_unmount: Option<bool>,
+    /// Whether root can use the mount too.
+    #[arg(long, value_name = "BOOL")]
+    pub allow_root: Option<bool>,
+    /// Whether every user can use the mount.
+    #[arg(long, value_name = "BOOL")]
+    pub allow_other: Option<bool>,
+    /// Size, in bytes, at which the log is rotated.
+    #[arg(long, value_name = "BYTES")]
+    pub log_max_size: Option<usize>,
+    /// How many rotated logs are kept around.
+    #[arg(long, value_name = "COUNT")]
+    pub log_kept_files: Option<usize>,
+    /// Added to the default tracing directives, e.g., `tag_filesystem::fuse=debug`.
+    #[arg(long, value_name = "DIRECTIVES")]
+    pub log_directives: Option<String>
+}
+
+impl MountArguments {
+    pub fn apply(&self, mut configuration: MountConfiguration) -> MountConfiguration {
+        let storage = &mut configuration.storage;
+        storage.backend = self.storage_backend.unwrap_or(storage.backend);
+        if let Some(store_name) = &self.store {
+            storage.store = Some(store_name.clone());
+        }
+        let paths = &mut configuration.paths;
+        if let Some(data_directory) = &self.data_directory {
+            paths.data_directory = data_directory.clone();
+        }
+        if let Some(log_directory) = &self.log_directory {
+            paths.log_directory = log_directory.clone();
+        }
+        let persistence = &mut configuration.persistence;
+        persistence.loop_interval = self.loop_interval.unwrap_or(persistence.loop_interval);
+        persistence.persist_interval = self.persist_interval
+            .unwrap_or(persistence.persist_interval);
+        persistence.trash_retention = self.trash_retention
+            .unwrap_or(persistence.trash_retention);
+        let mount_options = &mut configuration.mount_options;
+        mount_options.auto_unmount = self.auto_unmount.unwrap_or(mount_options.auto_unmount);
+        mount_options.allow_root = self.allow_root.unwrap_or(mount_options.allow_root);
+        mount_options.allow_other = self.allow_other.unwrap_or(mount_options.allow_other);
+        let logging = &mut configuration.logging;
+        logging.max_size = self.log_max_size.unwrap_or(logging.max_size);
+        logging.kept_files = self.log_kept_files.unwrap_or(logging.kept_files);
+        if let Some(log_directives) = &self.log_directives {
+            logging.directives = log_directives.clone();
+        }
+        configuration
+    }
+}
@@ -1,28 +1,94 @@
-use std::fs::create_dir_all;
+use std::{fs::create_dir_all, time::Duration};
 
 use clap::Args;
-use tracing::info;
+use tracing::{error, info};
 
-use crate::{cli::{mount::MountParameters, ProgramParameters}, errors::ResultBtAny,
-    filesystem::TagFilesystem};
+use crate::{caching::CachingPolicy, cli::{mount::MountParameters, ProgramParameters},
+    errors::ResultBtAny, filesystem::TagFilesystem, mounts::{daemonize, wait_for_daemon}};
 
+/// How long `--daemon` waits for the mount before giving up on it.
+const DAEMON_MOUNT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Runs the mount in the foreground, or as a daemon, until it's sent `SIGTERM`.
 #[derive(Args, Debug)]
-pub struct PlainParameters;
+pub struct PlainParameters {
+    /// Forks off and detaches from the terminal, returning once mounted. Stop it with
+    /// `tfs unmount`.
+    #[arg(long)]
+    pub daemon: bool,
+    /// Refuses every change, and can be mounted while the store is mounted elsewhere.
+    #[arg(long)]
+    pub read_only: bool,
+    #[command(flatten)]
+    pub caching: CachingArguments
+}
 
 impl PlainParameters {
     pub fn run(&self, program_arguments: &ProgramParameters,
         mount_arguments: &MountParameters) -> ResultBtAny<()>
     {
         let _mount_path = mount_arguments.mount_path.to_string_lossy();
+        let mut configuration = mount_arguments.get_configuration(program_arguments);
+        configuration.caching = self.caching.apply(configuration.caching);
+        configuration.mount_options.read_only |= self.read_only;
+        let store_location = mount_arguments.get_store_location(&configuration)?;
         if program_arguments.dry {
             info!("Would have created directories on the way to, and mounted TFS \
-                at `{}`.", _mount_path);
+                at `{}` from `{store_location:?}` with `{configuration:?}`.", _mount_path);
         } else {
             create_dir_all(&mount_arguments.mount_path)?;
             info!("Creating all directories to `{}`.", _mount_path);
-            TagFilesystem::run_filesystem(&mount_arguments.mount_path)?;
+            if self.daemon && let Some(daemon_pid) = daemonize()? {
+                wait_for_daemon(&mount_arguments.mount_path, daemon_pid,
+                    DAEMON_MOUNT_TIMEOUT)?;
+                println!("Mounted TFS at `{_mount_path}`, as process `{daemon_pid}`.");
+                return Ok(());
+            }
+            TagFilesystem::run_filesystem(&mount_arguments.mount_path, &store_location,
+                &configuration)
+                // Nowhere else to go once detached.
+                .inspect_err(|e| error!("Mount failed. {}", e.to_string_wbt()))?;
         }
         Ok(())
     }
 }
 
+/// Overrides the mount's caching policy from the configuration file.
+#[derive(Args, Debug, Default)]
+pub struct CachingArguments {
+    /// How long the kernel caches the root's attributes, e.g., `1m`.
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub root_ttl: Option<Duration>,
+    /// How long the kernel caches files' entries and attributes.
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub file_ttl: Option<Duration>,
+    /// How long the kernel caches tags' entries and attributes.
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub tag_ttl: Option<Duration>,
+    /// How long the kernel caches namespaces' entries and attributes.
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub namespace_ttl: Option<Duration>,
+    /// Whether the kernel buffers writes before sending them on.
+    #[arg(long, value_name = "BOOL")]
+    pub writeback_cache: Option<bool>,
+    /// Whether files keep their cached content when opened again.
+    #[arg(long, value_name = "BOOL")]
+    pub keep_cache: Option<bool>,
+    /// Whether the kernel looks up entries while listing directories.
+    #[arg(long, value_name = "BOOL")]
+    pub readdir_plus: Option<bool>
+}
+
+impl CachingArguments {
+    pub fn apply(&self, mut caching: CachingPolicy) -> CachingPolicy {
+        let ttls = &mut caching.ttls;
+        ttls.root = self.root_ttl.unwrap_or(ttls.root);
+        ttls.file = self.file_ttl.unwrap_or(ttls.file);
+        ttls.tag = self.tag_ttl.unwrap_or(ttls.tag);
+        ttls.namespace = self.namespace_ttl.unwrap_or(ttls.namespace);
+        caching.writeback_cache = self.writeback_cache.unwrap_or(caching.writeback_cache);
+        caching.keep_cache = self.keep_cache.unwrap_or(caching.keep_cache);
+        caching.readdir_plus = self.readdir_plus.unwrap_or(caching.readdir_plus);
+        caching
+    }
+}
@@ -0,0 +1,36 @@
+use clap::Parser;
+use humantime::format_rfc3339_seconds;
+
+use crate::{cli::ProgramParameters, errors::ResultBtAny, mounts::get_active_mounts,
+    snapshots::PersistentSnapshots};
+
+/// Lists the active TFS mounts, with their store, pid and when they last saved.
+#[derive(Parser, Debug)]
+pub struct StatusParameters;
+
+impl StatusParameters {
+    pub fn run(&self, _program_arguments: &ProgramParameters) -> ResultBtAny<()> {
+        println!("MOUNT\tSTORE\tPID\tLAST PERSISTED");
+        for (mount_path, mount_record) in get_active_mounts()? {
+            let mount_path = mount_path.to_string_lossy();
+            // Mounted by an older `tfs`, or its record went missing.
+            let Some(mount_record) = mount_record else {
+                println!("{mount_path}\t-\t-\t-");
+                continue;
+            };
+            let store_name = mount_record.store.as_deref()
+                .unwrap_or("-");
+            let when_promoted = PersistentSnapshots::get_when_promoted(
+                &mount_record.snapshot_directory)?;
+            // Read-only mounts never persist, so the time would be the writer's.
+            let last_persisted = match (mount_record.is_read_only, when_promoted) {
+                (true, _) => "read-only".to_string(),
+                (false, Some(when_promoted)) => format_rfc3339_seconds(when_promoted)
+                    .to_string(),
+                (false, None) => "never".to_string()
+            };
+            println!("{mount_path}\t{store_name}\t{}\t{last_persisted}", mount_record.pid);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+use clap::Args;
+use tracing::info;
+
+use crate::{cli::{store::StoreParameters, ProgramParameters}, errors::ResultBtAny,
+    stores::StoreRegistry};
+
+#[derive(Args, Debug)]
+pub struct CreateParameters {
+    pub store_name: String
+}
+
+impl CreateParameters {
+    pub fn run(&self, program_arguments: &ProgramParameters) -> ResultBtAny<()> {
+        let data_directory = StoreParameters::get_data_directory(program_arguments);
+        if program_arguments.dry {
+            info!("Would have created store `{}` under `{}`.", self.store_name,
+                data_directory.to_string_lossy());
+            return Ok(());
+        }
+        StoreRegistry::load(&data_directory)?
+            .create(&data_directory, &self.store_name)?;
+        println!("Created store `{}`.", self.store_name);
+        Ok(())
+    }
+}
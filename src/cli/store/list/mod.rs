@@ -0,0 +1,17 @@
+use clap::Args;
+
+use crate::{cli::{store::StoreParameters, ProgramParameters}, errors::ResultBtAny,
+    stores::StoreRegistry};
+
+#[derive(Args, Debug)]
+pub struct ListParameters;
+
+impl ListParameters {
+    pub fn run(&self, program_arguments: &ProgramParameters) -> ResultBtAny<()> {
+        let data_directory = StoreParameters::get_data_directory(program_arguments);
+        for (store_name, _) in StoreRegistry::load(&data_directory)?.get_all() {
+            println!("{store_name}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use std::{fs::canonicalize, path::PathBuf};
+
+use clap::Args;
+use tracing::info;
+
+use crate::{cli::{store::StoreParameters, ProgramParameters}, errors::ResultBtAny,
+    stores::StoreRegistry};
+
+/// Moves the data kept for a mount path, from before stores, into a new store.
+#[derive(Args, Debug)]
+pub struct MigrateParameters {
+    pub store_name: String,
+    /// Where the data used to be mounted. It can't be mounted while migrating.
+    #[arg(long)]
+    pub from: PathBuf
+}
+
+impl MigrateParameters {
+    pub fn run(&self, program_arguments: &ProgramParameters) -> ResultBtAny<()> {
+        let data_directory = StoreParameters::get_data_directory(program_arguments);
+        // Data was keyed by the canonical mount path, if it's still around.
+        let mount_path = canonicalize(&self.from)
+            .unwrap_or(self.from.clone());
+        if program_arguments.dry {
+            info!("Would have migrated the data for `{}` into store `{}`.",
+                mount_path.to_string_lossy(), self.store_name);
+            return Ok(());
+        }
+        StoreRegistry::load(&data_directory)?
+            .migrate(&data_directory, &self.store_name, &mount_path)?;
+        println!("Migrated `{}` into store `{}`, mount it with `tfs mount --store {} <path> \
+            plain`.", mount_path.to_string_lossy(), self.store_name, self.store_name);
+        Ok(())
+    }
+}
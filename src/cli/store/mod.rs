@@ -0,0 +1,40 @@
+pub mod create;
+pub mod list;
+pub mod migrate;
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use crate::{cli::{store::{create::CreateParameters, list::ListParameters,
+    migrate::MigrateParameters}, ProgramParameters}, errors::ResultBtAny};
+
+/// Manages named stores, which can be mounted at any path with `mount --store`.
+#[derive(Parser, Debug)]
+pub struct StoreParameters {
+    #[command(subcommand)]
+    pub subcommand: StoreSubcommand
+}
+
+impl StoreParameters {
+    pub fn run(&self, program_arguments: &ProgramParameters) -> ResultBtAny<()> {
+        match &self.subcommand {
+            StoreSubcommand::Create(create_arguments) => create_arguments.run(program_arguments),
+            StoreSubcommand::List(list_arguments) => list_arguments.run(program_arguments),
+            StoreSubcommand::Migrate(migrate_arguments) =>
+                migrate_arguments.run(program_arguments)
+        }
+    }
+
+    /// Stores live under the data directory that every mount starts from.
+    pub fn get_data_directory(program_arguments: &ProgramParameters) -> PathBuf {
+        program_arguments.configuration.global.paths.get_data_directory()
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StoreSubcommand {
+    Create(CreateParameters),
+    List(ListParameters),
+    Migrate(MigrateParameters)
+}
@@ -0,0 +1,38 @@
+use std::{fs::{self, remove_dir, rename}, path::PathBuf};
+
+use clap::Parser;
+use tracing::info;
+
+use crate::{cli::ProgramParameters, errors::ResultBtAny, path::format_tags,
+    trash::TRASH_NAME};
+
+#[derive(Parser, Debug)]
+pub struct DeleteParameters {
+    pub mount_path: PathBuf,
+    pub tag_names: Vec<String>,
+    /// Deletes tags even if files still carry them.
+    #[arg(short, long, default_value_t = false)]
+    pub force: bool
+}
+
+impl DeleteParameters {
+    pub fn run(&self, program_arguments: &ProgramParameters) -> ResultBtAny<()> {
+        let mount_path = fs::canonicalize(&self.mount_path)?;
+        let trash_path = mount_path.join(format_tags([TRASH_NAME].into_iter()));
+        for tag_name in &self.tag_names {
+            let tag_path = mount_path.join(tag_name);
+            if program_arguments.dry {
+                println!("Would have deleted `{}`.", tag_path.to_string_lossy());
+            } else if self.force {
+                // `rmdir` refuses tags that are still on files, moving them into
+                // the trash doesn't.
+                rename(&tag_path, trash_path.join(tag_name))?;
+                info!("Forcibly deleted `{}`.", tag_path.to_string_lossy());
+            } else {
+                remove_dir(&tag_path)?;
+                info!("Deleted `{}`.", tag_path.to_string_lossy());
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,9 +1,11 @@
 pub mod change;
+pub mod delete;
 pub mod setup;
 
 use clap::{Parser, Subcommand};
 
-use crate::{cli::{tags::{change::ChangeParameters, setup::SetupParameters}, ProgramParameters},
+use crate::{cli::{tags::{change::ChangeParameters, delete::DeleteParameters,
+    setup::SetupParameters}, ProgramParameters},
     errors::ResultBtAny, tracing::setup_syslog_tracing};
 
 #[derive(Parser, Debug)]
@@ -14,11 +16,12 @@ pub struct TagsParameters {
 
 impl TagsParameters {
     pub fn run(&self, program_arguments: &ProgramParameters) -> ResultBtAny<()> {
-        setup_syslog_tracing()?;
+        setup_syslog_tracing(&program_arguments.configuration.global.logging)?;
 
         match &self.subcommand {
             TagsSubcommand::Setup(setup_arguments) =>setup_arguments.run(program_arguments),
-            TagsSubcommand::Change(change_arguments) => change_arguments.run()
+            TagsSubcommand::Change(change_arguments) => change_arguments.run(),
+            TagsSubcommand::Delete(delete_arguments) => delete_arguments.run(program_arguments)
         }
     }
 }
@@ -26,5 +29,6 @@ impl TagsParameters {
 #[derive(Subcommand, Debug)]
 pub enum TagsSubcommand {
     Change(ChangeParameters),
+    Delete(DeleteParameters),
     Setup(SetupParameters)
 }
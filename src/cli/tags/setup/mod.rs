@@ -17,6 +17,8 @@ pub struct SetupParameters {
 }
 
 pub const DEFAULT_SCRIPT_NAME : &str = "change_tags.sh";
+/// Starts the script's first line, followed by the version of `tfs` that wrote it.
+pub const SCRIPT_VERSION_MARKER: &str = "# Written by `tfs tags setup`, version ";
 
 impl SetupParameters {
     pub fn run(&self, program_arguments: &ProgramParameters) -> ResultBtAny<()> {
@@ -43,7 +45,8 @@ impl SetupParameters {
 #[template(path = "change_tags.sh.j2")] 
 pub struct ChangeTemplate {
     wrapper_name: String,
-    to_binary: String
+    to_binary: String,
+    version: &'static str
 }
 
 impl TryFrom<&SetupParameters> for ChangeTemplate {
@@ -54,7 +57,8 @@ impl TryFrom<&SetupParameters> for ChangeTemplate {
             wrapper_name: value.wrapper_name.clone(),
             to_binary: current_exe()?
                 .to_string_lossy()
-                .into_owned()
+                .into_owned(),
+            version: env!("CARGO_PKG_VERSION")
         })
     }
 }
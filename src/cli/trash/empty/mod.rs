@@ -0,0 +1,30 @@
+use std::fs::{read_dir, remove_dir, remove_file};
+
+use clap::Args;
+use tracing::info;
+
+use crate::{cli::{trash::TrashParameters, ProgramParameters}, errors::ResultBtAny};
+
+#[derive(Args, Debug)]
+pub struct EmptyParameters;
+
+impl EmptyParameters {
+    pub fn run(&self, program_arguments: &ProgramParameters,
+        trash_arguments: &TrashParameters) -> ResultBtAny<()>
+    {
+        for trash_entry in read_dir(trash_arguments.get_trash_path())? {
+            let trash_entry = trash_entry?;
+            let trashed_path = trash_entry.path();
+            if program_arguments.dry {
+                println!("Would have purged `{}`.", trashed_path.to_string_lossy());
+            } else if trash_entry.file_type()?.is_dir() {
+                remove_dir(&trashed_path)?;
+                info!("Purged tag `{}`.", trashed_path.to_string_lossy());
+            } else {
+                remove_file(&trashed_path)?;
+                info!("Purged file `{}`.", trashed_path.to_string_lossy());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+use std::fs::read_dir;
+
+use clap::Args;
+
+use crate::{cli::trash::TrashParameters, errors::ResultBtAny};
+
+#[derive(Args, Debug)]
+pub struct ListParameters;
+
+impl ListParameters {
+    pub fn run(&self, trash_arguments: &TrashParameters) -> ResultBtAny<()> {
+        let mut trash_entries = read_dir(trash_arguments.get_trash_path())?
+            .collect::<Result<Vec<_>, _>>()?;
+        trash_entries.sort_by_key(|trash_entry| trash_entry.file_name());
+        for trash_entry in trash_entries {
+            let suffix = if trash_entry.file_type()?.is_dir() { "/" } else { "" };
+            println!("{}{suffix}", trash_entry.file_name().to_string_lossy());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+pub mod empty;
+pub mod list;
+pub mod restore;
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use crate::{cli::{trash::{empty::EmptyParameters, list::ListParameters,
+    restore::RestoreParameters}, ProgramParameters}, errors::ResultBtAny, path::format_tags,
+    tracing::setup_syslog_tracing, trash::TRASH_NAME};
+
+#[derive(Parser, Debug)]
+pub struct TrashParameters {
+    pub mount_path: PathBuf,
+    #[command(subcommand)]
+    pub subcommand: TrashSubcommand
+}
+
+impl TrashParameters {
+    pub fn run(&self, program_arguments: &ProgramParameters) -> ResultBtAny<()> {
+        setup_syslog_tracing(&program_arguments.configuration.get_mount(&self.mount_path)
+            .logging)?;
+
+        match &self.subcommand {
+            TrashSubcommand::List(list_arguments) => list_arguments.run(self),
+            TrashSubcommand::Restore(restore_arguments) =>
+                restore_arguments.run(program_arguments, self),
+            TrashSubcommand::Empty(empty_arguments) =>
+                empty_arguments.run(program_arguments, self)
+        }
+    }
+
+    /// The trash is only reachable through the mount, as its `{ .trash }` namespace.
+    pub fn get_trash_path(&self) -> PathBuf {
+        self.mount_path.join(format_tags([TRASH_NAME].into_iter()))
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TrashSubcommand {
+    List(ListParameters),
+    Restore(RestoreParameters),
+    Empty(EmptyParameters)
+}
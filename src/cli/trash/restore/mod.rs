@@ -0,0 +1,42 @@
+use std::fs::rename;
+
+use clap::Args;
+use tracing::info;
+
+use crate::{cli::{trash::TrashParameters, ProgramParameters}, errors::ResultBtAny,
+    path::format_tags};
+
+#[derive(Args, Debug)]
+pub struct RestoreParameters {
+    /// Name of the item as listed in the trash.
+    pub trash_name: String,
+    /// Restores a file under these tags instead of the ones it had.
+    #[arg(short, long)]
+    pub tags: Vec<String>,
+    /// Restores under a different name.
+    #[arg(short, long)]
+    pub name: Option<String>
+}
+
+impl RestoreParameters {
+    pub fn run(&self, program_arguments: &ProgramParameters,
+        trash_arguments: &TrashParameters) -> ResultBtAny<()>
+    {
+        let trashed_path = trash_arguments.get_trash_path().join(&self.trash_name);
+        let mut restored_path = trash_arguments.mount_path.clone();
+        if !self.tags.is_empty() {
+            restored_path.push(format_tags(self.tags.iter().map(String::as_str)));
+        }
+        restored_path.push(self.name.as_ref().unwrap_or(&self.trash_name));
+
+        if program_arguments.dry {
+            println!("Would have restored `{}` to `{}`.", trashed_path.to_string_lossy(),
+                restored_path.to_string_lossy());
+        } else {
+            rename(&trashed_path, &restored_path)?;
+            info!("Restored `{}` to `{}`.", trashed_path.to_string_lossy(),
+                restored_path.to_string_lossy());
+        }
+        Ok(())
+    }
+}
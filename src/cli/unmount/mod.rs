@@ -0,0 +1,28 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use clap::Parser;
+use tracing::info;
+
+use crate::{cli::ProgramParameters, errors::ResultBtAny, mounts::unmount};
+
+/// Has the mount at `mount_path` save and unmount, falling back to `fusermount -u`.
+#[derive(Parser, Debug)]
+pub struct UnmountParameters {
+    pub mount_path: PathBuf,
+    /// How long to wait for the mount to save before falling back, e.g., `10s`.
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "10s")]
+    pub timeout: Duration
+}
+
+impl UnmountParameters {
+    pub fn run(&self, program_arguments: &ProgramParameters) -> ResultBtAny<()> {
+        // Can't be canonicalized once its process is gone, so is taken as is then.
+        let mount_path = fs::canonicalize(&self.mount_path)
+            .unwrap_or_else(|_| self.mount_path.clone());
+        if program_arguments.dry {
+            info!("Would have unmounted `{}`.", mount_path.to_string_lossy());
+            return Ok(());
+        }
+        unmount(&mount_path, self.timeout)
+    }
+}
@@ -0,0 +1,254 @@
+use std::{collections::HashMap, fs, io::ErrorKind, path::{Path, PathBuf}, time::Duration};
+
+use clap::ValueEnum;
+use fuser::MountOption;
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+use tracing::info;
+
+use crate::{caching::CachingPolicy, errors::ResultBtAny, path::get_configuration_directory,
+    trash::IndexedTrash, ResultExt};
+
+/// Settings for a single mount, where anything left out takes its default.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct MountConfiguration {
+    pub storage: StorageConfiguration,
+    pub paths: PathsConfiguration,
+    pub persistence: PersistenceConfiguration,
+    pub mount_options: MountOptionsConfiguration,
+    pub caching: CachingPolicy,
+    pub logging: LoggingConfiguration
+}
+
+/// Where file content is kept, apart from the snapshots of names and tags.
+#[derive(Deserialize, Serialize, ValueEnum, PartialEq, Eq, Default, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    /// Each file's content is a file of its own, named by its inode, in a directory
+    /// for the mount.
+    #[default]
+    Delegate
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfiguration {
+    pub backend: StorageBackend,
+    /// Named store to mount, rather than the data kept for the mount's path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store: Option<String>
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathsConfiguration {
+    /// Under which each mount keeps its snapshots and delegate storage. Can start
+    /// with `~`.
+    pub data_directory: PathBuf,
+    /// Under which each mount keeps its rotated logs. Can start with `~`.
+    pub log_directory: PathBuf
+}
+
+impl PathsConfiguration {
+    pub fn get_data_directory(&self) -> PathBuf {
+        expand_tilde(&self.data_directory)
+    }
+
+    pub fn get_log_directory(&self) -> PathBuf {
+        expand_tilde(&self.log_directory)
+    }
+}
+
+impl Default for PathsConfiguration {
+    fn default() -> Self {
+        Self {
+            data_directory: get_configuration_directory(),
+            log_directory: get_configuration_directory().join("logs")
+        }
+    }
+}
+
+fn expand_tilde(path: &Path) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(&path.to_string_lossy()).as_ref())
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct PersistenceConfiguration {
+    /// How often the mount wakes up to check for signals and whether to persist.
+    #[serde(with = "humantime_serde")]
+    pub loop_interval: Duration,
+    /// How long the mount waits between asking for snapshots to be written.
+    #[serde(with = "humantime_serde")]
+    pub persist_interval: Duration,
+    /// How long trashed items are kept before being purged for good.
+    #[serde(with = "humantime_serde")]
+    pub trash_retention: Duration
+}
+
+impl Default for PersistenceConfiguration {
+    fn default() -> Self {
+        Self {
+            loop_interval: Duration::from_secs(1),
+            persist_interval: Duration::from_secs(5),
+            trash_retention: IndexedTrash::DEFAULT_RETENTION
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct MountOptionsConfiguration {
+    /// Unmounts once the process exits, even if it crashed.
+    pub auto_unmount: bool,
+    /// Lets root, as well as the mounting user, in.
+    pub allow_root: bool,
+    /// Lets every user in. Can't be combined with `allow_root`.
+    pub allow_other: bool,
+    /// Refuses every change, and doesn't take the store's lock, so it can be mounted
+    /// alongside a writable mount, e.g., for a backup.
+    pub read_only: bool
+}
+
+impl MountOptionsConfiguration {
+    pub fn get_mount_options(&self) -> ResultBtAny<Vec<MountOption>> {
+        if self.allow_root && self.allow_other {
+            Err("Mount options `allow_root` and `allow_other` can't both be set.")?;
+        }
+        let mount_options = [
+            (self.auto_unmount, MountOption::AutoUnmount),
+            (self.allow_root, MountOption::AllowRoot),
+            (self.allow_other, MountOption::AllowOther),
+            (self.read_only, MountOption::RO)
+        ];
+        Ok(mount_options.into_iter()
+            .filter_map(|(is_set, mount_option)| is_set.then_some(mount_option))
+            .collect())
+    }
+}
+
+impl Default for MountOptionsConfiguration {
+    fn default() -> Self {
+        Self {
+            auto_unmount: true,
+            allow_root: true,
+            allow_other: false,
+            read_only: false
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfiguration {
+    /// Size, in bytes, at which the log is rotated.
+    pub max_size: usize,
+    /// How many rotated logs are kept around.
+    pub kept_files: usize,
+    /// Added to the default tracing directives, e.g., `tag_filesystem::fuse=debug`.
+    /// The `ADDITIONAL_LOG_DIRECTIVES` environment variable still goes last.
+    pub directives: String
+}
+
+impl Default for LoggingConfiguration {
+    fn default() -> Self {
+        Self {
+            max_size: 10_000_000,
+            kept_files: 1,
+            directives: String::new()
+        }
+    }
+}
+
+/// What's in `config.toml`. Top-level settings apply to every mount, and ones under
+/// `mounts` to only that mount, taking precedence, e.g.,
+///
+/// ```toml
+/// [persistence]
+/// persist_interval = "30s"
+///
+/// [mounts."/home/user/tags".caching]
+/// keep_cache = false
+///
+/// [mounts."/home/user/tags".caching.ttls]
+/// file = "0s"
+/// namespace = "1m"
+/// ```
+#[derive(PartialEq, Eq, Default, Debug)]
+pub struct TfsConfiguration {
+    pub global: MountConfiguration,
+    /// Keyed by the canonical mount path, having the global settings merged in.
+    pub mounts: HashMap<String, MountConfiguration>
+}
+
+impl TfsConfiguration {
+    const CONFIGURATION_FILENAME: &str = "config.toml";
+    const MOUNTS_KEY: &str = "mounts";
+
+    pub fn get_default_path() -> PathBuf {
+        get_configuration_directory().join(Self::CONFIGURATION_FILENAME)
+    }
+
+    pub fn parse(configuration: &str) -> ResultBtAny<Self> {
+        let mut global_table: Table = toml::from_str(configuration)?;
+        let mount_tables = match global_table.remove(Self::MOUNTS_KEY) {
+            Some(Value::Table(mount_tables)) => mount_tables,
+            Some(_) => Err(format!("`{}` has to be a table of mount paths.",
+                Self::MOUNTS_KEY))?,
+            None => Table::new()
+        };
+
+        let mut mounts = HashMap::new();
+        for (mount_path, mount_table) in mount_tables {
+            let Value::Table(mount_table) = mount_table else {
+                Err(format!("Mount `{mount_path}` has to be a table."))?
+            };
+            let mut merged_table = global_table.clone();
+            merge_tables(&mut merged_table, mount_table);
+            let mount_configuration: MountConfiguration = merged_table.try_into()
+                .map_err(|e| format!("Mount `{mount_path}` is invalid. {e}"))?;
+            mounts.insert(mount_path, mount_configuration);
+        }
+        Ok(Self {
+            global: global_table.try_into()?,
+            mounts
+        })
+    }
+
+    /// Falls back to the defaults when there's no configuration file.
+    pub fn load(configuration_path: &Path) -> ResultBtAny<Self> {
+        let configuration = match fs::read_to_string(configuration_path) {
+            Ok(configuration) => configuration,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                info!("No configuration at `{}`, using defaults.",
+                    configuration_path.to_string_lossy());
+                return Ok(Self::default());
+            },
+            Err(e) => Err(e)?
+        };
+        Self::parse(&configuration)
+            .map_err_inner(|e| format!("Configuration `{}` is invalid. {e}",
+                configuration_path.to_string_lossy()).into())
+    }
+
+    pub fn get_mount(&self, mount_path: &Path) -> MountConfiguration {
+        self.mounts.get(mount_path.to_string_lossy().as_ref())
+            .unwrap_or(&self.global)
+            .clone()
+    }
+}
+
+/// Overlays one table onto another, key by key, so nested tables only replace the
+/// settings they have.
+fn merge_tables(base_table: &mut Table, overlay_table: Table) {
+    for (key, overlay_value) in overlay_table {
+        match (base_table.get_mut(&key), overlay_value) {
+            (Some(Value::Table(base_value)), Value::Table(overlay_value)) =>
+                merge_tables(base_value, overlay_value),
+            (_, overlay_value) => {
+                base_table.insert(key, overlay_value);
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::{collections::{BTreeSet, HashMap, HashSet}, fmt::Display};
+
+use crate::{errors::ResultBtAny, inodes::FileInode, wrappers::write_iter};
+
+/// The searchable terms of a text file.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct TfsContent {
+    pub inode: FileInode,
+    pub terms: BTreeSet<String>
+}
+
+impl TfsContent {
+    pub fn from_text(inode: FileInode, text: &str) -> Self {
+        Self {
+            inode,
+            terms: get_terms(text).collect()
+        }
+    }
+}
+
+impl Display for TfsContent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}(terms={})", self.inode, self.terms.len())
+    }
+}
+
+/// Words are runs of alphanumeric characters, compared case insensitively.
+pub fn get_terms(text: &str) -> impl Iterator<Item = String> {
+    text.split(|character: char| !character.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+}
+
+type ByInode = HashMap<FileInode, TfsContent>;
+type ByTerm = HashMap<String, HashSet<FileInode>>;
+
+/// Inverted index from terms to the text files containing them. Files are
+/// marked stale on write and only re-read once the writer is done with them.
+#[derive(Debug, Clone)]
+pub struct IndexedContents {
+    contents: ByInode,
+    by_term: ByTerm,
+    stale: HashSet<FileInode>
+}
+
+impl IndexedContents {
+    /// Larger files are left out of the index rather than read in full.
+    pub const MAX_INDEXED_SIZE: u64 = 8 << 20;
+
+    pub fn new() -> Self {
+        Self {
+            contents: ByInode::new(),
+            by_term: ByTerm::new(),
+            stale: HashSet::new()
+        }
+    }
+
+    pub fn get_by_inode(&self, file_inode: &FileInode) -> Option<&TfsContent> {
+        self.contents.get(file_inode)
+    }
+
+    pub fn get_all(&self) -> impl Iterator<Item = &TfsContent> {
+        self.contents.values()
+    }
+
+    /// Files containing every one of `terms`.
+    pub fn get_by_terms(&self, terms: &[String]) -> HashSet<FileInode> {
+        let mut term_inodes = terms.iter()
+            .map(|term| self.by_term.get(term));
+        let Some(Some(first_inodes)) = term_inodes.next() else {
+            return HashSet::new();
+        };
+        let mut matching_inodes = first_inodes.clone();
+        for inodes in term_inodes {
+            let Some(inodes) = inodes else {
+                return HashSet::new();
+            };
+            matching_inodes.retain(|inode| inodes.contains(inode));
+        }
+        matching_inodes
+    }
+
+    pub fn get_is_match(&self, file_inode: &FileInode, terms: &[String]) -> bool {
+        self.contents.get(file_inode)
+            .is_some_and(|content| terms.iter()
+                .all(|term| content.terms.contains(term)))
+    }
+
+    pub fn add(&mut self, to_add: TfsContent) -> ResultBtAny<&TfsContent> {
+        let file_inode = to_add.inode;
+        if self.contents.contains_key(&file_inode) {
+            Err(format!("Content for file inode `{file_inode}` already exists."))?;
+        }
+
+        for term in &to_add.terms {
+            self.by_term.entry(term.clone())
+                .or_default()
+                .insert(file_inode);
+        }
+        Ok(self.contents.entry(file_inode)
+            .or_insert(to_add))
+    }
+
+    pub fn remove_by_inode(&mut self, file_inode: &FileInode) -> Option<TfsContent> {
+        self.stale.remove(file_inode);
+        let removed_content = self.contents.remove(file_inode)?;
+        for term in &removed_content.terms {
+            let Some(inodes) = self.by_term.get_mut(term) else {
+                continue;
+            };
+            inodes.remove(file_inode);
+            if inodes.is_empty() {
+                self.by_term.remove(term);
+            }
+        }
+        Some(removed_content)
+    }
+
+    pub fn mark_stale(&mut self, file_inode: FileInode) {
+        self.stale.insert(file_inode);
+    }
+
+    pub fn take_stale(&mut self) -> HashSet<FileInode> {
+        std::mem::take(&mut self.stale)
+    }
+}
+
+impl Default for IndexedContents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for IndexedContents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_iter(f, ('[', ']'), self.contents.values())
+    }
+}
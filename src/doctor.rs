@@ -0,0 +1,212 @@
+use std::{fmt::{self, Display, Formatter}, fs::{self, create_dir_all},
+    os::unix::fs::{FileTypeExt, PermissionsExt}, path::Path};
+
+use nix::{sys::statvfs::statvfs, unistd::{access, geteuid, AccessFlags}};
+
+use crate::{cli::tags::setup::SCRIPT_VERSION_MARKER, errors::ResultBt,
+    mounts::find_fusermount, snapshots::{OpenError, PersistentSnapshots, TfsSnapshots},
+    stores::StoreLocation};
+
+pub const FUSE_DEVICE_PATH: &str = "/dev/fuse";
+pub const FUSE_CONFIGURATION_PATH: &str = "/etc/fuse.conf";
+/// Below which snapshots and logs might fail to be written.
+pub const MINIMUM_FREE_SPACE: u64 = 64_000_000;
+const PROBE_FILENAME: &str = ".tfs_doctor";
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Severity {
+    Healthy,
+    /// Works for now, but might not for long, or not for everything.
+    Warning,
+    /// Stops mounting, or loses data.
+    Problem
+}
+
+/// The outcome of a single check, with what to do about it, if anything.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Diagnosis {
+    pub severity: Severity,
+    pub summary: String,
+    pub fix: Option<String>
+}
+
+impl Diagnosis {
+    pub fn healthy(summary: impl Into<String>) -> Self {
+        Self { severity: Severity::Healthy, summary: summary.into(), fix: None }
+    }
+
+    pub fn warning(summary: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { severity: Severity::Warning, summary: summary.into(), fix: Some(fix.into()) }
+    }
+
+    pub fn problem(summary: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { severity: Severity::Problem, summary: summary.into(), fix: Some(fix.into()) }
+    }
+}
+
+impl Display for Diagnosis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Healthy => "ok",
+            Severity::Warning => "warning",
+            Severity::Problem => "problem"
+        };
+        write!(f, "[{label}] {}", self.summary)?;
+        if let Some(fix) = &self.fix {
+            write!(f, "\n    Fix: {fix}")?;
+        }
+        Ok(())
+    }
+}
+
+pub fn check_fuse_device(device_path: &Path) -> Diagnosis {
+    let _device_path = device_path.to_string_lossy();
+    match fs::metadata(device_path) {
+        Ok(metadata) if metadata.file_type().is_char_device() => {},
+        Ok(_) => return Diagnosis::problem(format!("`{_device_path}` isn't a device."),
+            "Reboot, or remove it and load FUSE with `modprobe fuse`."),
+        Err(_) => return Diagnosis::problem(format!("`{_device_path}` is missing."),
+            "Load FUSE with `modprobe fuse`. In a container, pass the device through, \
+                e.g., `--device /dev/fuse`.")
+    }
+    match access(device_path, AccessFlags::R_OK | AccessFlags::W_OK) {
+        Ok(()) => Diagnosis::healthy(format!("`{_device_path}` can be opened.")),
+        Err(e) => Diagnosis::problem(format!("`{_device_path}` can't be opened, `{e}`."),
+            format!("Let the user read and write it, e.g., `chmod 666 {_device_path}`."))
+    }
+}
+
+/// Mounting as anyone but root, and unmounting automatically, go through it.
+pub fn check_fusermount() -> Diagnosis {
+    let Some(fusermount_path) = find_fusermount() else {
+        return Diagnosis::problem("Neither `fusermount3` nor `fusermount` is installed.",
+            "Install FUSE 3, e.g., `apt install fuse3` or `dnf install fuse3`.");
+    };
+    let _fusermount_path = fusermount_path.to_string_lossy();
+    let is_setuid = fs::metadata(&fusermount_path)
+        .is_ok_and(|metadata| metadata.permissions().mode() & 0o4000 != 0);
+    if !is_setuid && !geteuid().is_root() {
+        return Diagnosis::problem(format!("`{_fusermount_path}` isn't setuid, so only root \
+            can mount with it."), format!("`chmod u+s {_fusermount_path}`, as root."));
+    }
+    Diagnosis::healthy(format!("`{_fusermount_path}` is installed."))
+}
+
+/// `allow_root` and `allow_other` are refused for anyone but root, unless FUSE is
+/// configured to allow them.
+pub fn check_allow_other(fuse_configuration_path: &Path, is_requested: bool) -> Diagnosis {
+    if !is_requested {
+        return Diagnosis::healthy("Neither `allow_root` nor `allow_other` is set.");
+    }
+    if geteuid().is_root() {
+        return Diagnosis::healthy("Root can set `allow_root` and `allow_other`.");
+    }
+    let _fuse_configuration_path = fuse_configuration_path.to_string_lossy();
+    let is_allowed = fs::read_to_string(fuse_configuration_path)
+        .is_ok_and(|fuse_configuration| fuse_configuration.lines()
+            .any(|line| line.trim() == "user_allow_other"));
+    if is_allowed {
+        Diagnosis::healthy(format!("`{_fuse_configuration_path}` has `user_allow_other`."))
+    } else {
+        Diagnosis::problem(format!("`allow_root` or `allow_other` is set, but \
+            `{_fuse_configuration_path}` doesn't have `user_allow_other`."),
+            format!("Add `user_allow_other` to `{_fuse_configuration_path}`, as root, or \
+                set `allow_root = false` under `[mount_options]` in `config.toml`."))
+    }
+}
+
+/// That `directory` can be written to, and has room to.
+pub fn check_directory(purpose: &str, directory: &Path) -> Diagnosis {
+    let _directory = directory.to_string_lossy();
+    let probe_path = directory.join(PROBE_FILENAME);
+    let was_written = create_dir_all(directory)
+        .and_then(|_| fs::write(&probe_path, "probe"))
+        .and_then(|_| fs::remove_file(&probe_path));
+    if let Err(e) = was_written {
+        return Diagnosis::problem(format!("{purpose} `{_directory}` can't be written to, \
+            `{e}`."), "Make it writable, or point `config.toml` elsewhere.");
+    }
+    let available_space = match statvfs(directory) {
+        Ok(statistics) => statistics.blocks_available() * statistics.fragment_size(),
+        Err(e) => return Diagnosis::warning(format!("Couldn't tell how much room \
+            {purpose} `{_directory}` has, `{e}`."), "Check with `df`.")
+    };
+    if available_space < MINIMUM_FREE_SPACE {
+        return Diagnosis::problem(format!("{purpose} `{_directory}` only has \
+            `{available_space}` bytes free."), "Free up space on its disk, or point \
+            `config.toml` elsewhere.");
+    }
+    Diagnosis::healthy(format!("{purpose} `{_directory}` is writable, with \
+        `{available_space}` bytes free."))
+}
+
+/// That the store's snapshot matches its checksum, and its delegate storage is there.
+/// Doesn't create anything that's missing.
+pub fn check_store(store_name: &str, data_directory: &Path) -> Vec<Diagnosis> {
+    let store_location = StoreLocation::Named(store_name.to_string());
+    let snapshot_directory = store_location.get_snapshot_directory(data_directory);
+    let _snapshot_directory = snapshot_directory.to_string_lossy();
+    let snapshot_diagnosis = if !snapshot_directory.is_dir() {
+        Diagnosis::problem(format!("Store `{store_name}` has no snapshot directory, \
+            `{_snapshot_directory}`."), format!("Restore it from a backup, or \
+            `mkdir -p {_snapshot_directory}` to start the store over."))
+    } else {
+        match open_snapshot(&snapshot_directory) {
+            Ok(false) => Diagnosis::healthy(format!("Store `{store_name}` hasn't saved a \
+                snapshot yet.")),
+            Ok(true) => Diagnosis::healthy(format!("Store `{store_name}`'s snapshot \
+                matches its checksum.")),
+            Err(e) if matches!(e.error, OpenError::Checksum(_)) => Diagnosis::problem(
+                format!("Store `{store_name}`'s snapshot doesn't match its checksum."),
+                format!("Restore `{_snapshot_directory}` from a backup.")),
+            Err(e) => Diagnosis::problem(format!("Store `{store_name}`'s snapshot can't \
+                be read, `{}`.", e.error), format!("Check `{_snapshot_directory}`'s files \
+                and permissions."))
+        }
+    };
+
+    let delegate_directory = store_location.get_delegate_directory(data_directory);
+    let _delegate_directory = delegate_directory.to_string_lossy();
+    let delegate_diagnosis = if delegate_directory.is_dir() {
+        Diagnosis::healthy(format!("Store `{store_name}` has its delegate directory."))
+    } else {
+        Diagnosis::problem(format!("Store `{store_name}` has no delegate directory, \
+            `{_delegate_directory}`."), "Restore it from a backup, as files' content is \
+            kept there.")
+    };
+    vec![snapshot_diagnosis, delegate_diagnosis]
+}
+
+/// Whether there's a snapshot, having checked it against its checksum if so.
+fn open_snapshot(snapshot_directory: &Path) -> ResultBt<bool, OpenError> {
+    if PersistentSnapshots::get_when_promoted(snapshot_directory)?.is_none() {
+        return Ok(false);
+    }
+    PersistentSnapshots::try_new(snapshot_directory.to_path_buf())?
+        .open_safe()?;
+    Ok(true)
+}
+
+/// That `ct` was written by this version of `tfs`, as older scripts might call it
+/// differently.
+pub fn check_change_script(script_path: &Path) -> Diagnosis {
+    let _script_path = script_path.to_string_lossy();
+    let Ok(script) = fs::read_to_string(script_path) else {
+        return Diagnosis::warning(format!("There's no `ct` script at `{_script_path}`."),
+            "Write one with `tfs tags setup`, to change tags with `ct`.");
+    };
+    let current_version = env!("CARGO_PKG_VERSION");
+    let script_version = script.lines()
+        .next()
+        .and_then(|first_line| first_line.strip_prefix(SCRIPT_VERSION_MARKER))
+        .map(|version| version.trim_end_matches('.'));
+    match script_version {
+        Some(version) if version == current_version => Diagnosis::healthy(format!(
+            "`ct` script `{_script_path}` is from version `{version}`.")),
+        Some(version) => Diagnosis::warning(format!("`ct` script `{_script_path}` is \
+            from version `{version}`, rather than `{current_version}`."),
+            "Rewrite it with `tfs tags setup`."),
+        None => Diagnosis::warning(format!("`ct` script `{_script_path}` is from before \
+            versions were recorded."), "Rewrite it with `tfs tags setup`.")
+    }
+}
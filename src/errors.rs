@@ -1,10 +1,16 @@
 use std::{error::Error, fmt::Display};
 
-use crate::WithBacktrace;
+use derive_more::{Display, Error};
+use drums::Backtrace;
+use libc::{c_int, EEXIST, EINVAL, EIO, ENOENT, ENOTEMPTY, EPERM, EROFS, EXDEV};
+use tracing::Level;
+
+use crate::{names::NameError, WithBacktrace};
 
 // TODO: Consistent file, lineno.
 pub type ResultBt<T, E> = Result<T, WithBacktrace<E>>;
 pub type ResultBtAny<T> = Result<T, WithBacktrace<AnyError>>;
+pub type ResultBtTfs<T> = Result<T, WithBacktrace<TfsError>>;
 
 define_to_dyn!(&str);
 define_to_dyn!(String);
@@ -14,6 +20,10 @@ define_to_dyn!(std::ffi::NulError);
 define_to_dyn!(capnp::Error);
 define_to_dyn!(serde_json::Error);
 define_to_dyn!(askama::Error);
+define_to_dyn!(toml::de::Error);
+define_to_dyn!(toml::ser::Error);
+define_to_dyn!(nix::errno::Errno);
+define_to_dyn!(mount_watcher::mount::ReadError);
 
 pub trait StringExt {
     fn append_if_error<T>(&mut self, r: ResultBtAny<T>);
@@ -40,6 +50,70 @@ pub fn collect_errors<T, E: Display>(errors: impl Iterator<Item = ResultBt<T, E>
     Ok(())
 }
 
+/// Why a `TagFilesystem` operation failed, which decides what it replies to FUSE
+/// with.
+#[derive(Debug, Display, Error, Backtrace)]
+#[bt_from(AnyError, std::io::Error, NameError)]
+pub enum TfsError {
+    NotFound(#[error(not(source))] String),
+    AlreadyExists(#[error(not(source))] String),
+    NotEmpty(#[error(not(source))] String),
+    InvalidName(NameError),
+    /// The move can't be done in place, e.g., a tag into a namespace.
+    CrossDevice(#[error(not(source))] String),
+    PermissionDenied(#[error(not(source))] String),
+    /// What the kernel passed on can't be acted on, e.g., an offset out of range.
+    InvalidArgument(#[error(not(source))] String),
+    /// A change was asked of a read-only mount.
+    ReadOnly(#[error(not(source))] String),
+    /// Anything else, e.g., the delegate storage failing.
+    Io(AnyError)
+}
+
+impl TfsError {
+    pub fn get_errno(&self) -> c_int {
+        match self {
+            TfsError::NotFound(_) => ENOENT,
+            TfsError::AlreadyExists(_) => EEXIST,
+            TfsError::NotEmpty(_) => ENOTEMPTY,
+            TfsError::InvalidName(name_error) => name_error.get_errno(),
+            TfsError::CrossDevice(_) => EXDEV,
+            TfsError::PermissionDenied(_) => EPERM,
+            TfsError::InvalidArgument(_) => EINVAL,
+            TfsError::ReadOnly(_) => EROFS,
+            TfsError::Io(_) => EIO
+        }
+    }
+
+    /// Only failures of TFS itself are errors, the rest are down to what was asked.
+    pub fn get_level(&self) -> Level {
+        match self {
+            TfsError::Io(_) => Level::ERROR,
+            // Expected of every write to a read-only mount.
+            TfsError::ReadOnly(_) => Level::DEBUG,
+            _ => Level::INFO
+        }
+    }
+}
+
+impl From<AnyError> for TfsError {
+    fn from(value: AnyError) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<std::io::Error> for TfsError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value.into())
+    }
+}
+
+impl From<NameError> for TfsError {
+    fn from(value: NameError) -> Self {
+        Self::InvalidName(value)
+    }
+}
+
 // TODO: Read up on reasoning behind things that are not Send and Sync. And why does
 // `into`/conversions not work for some cases when not including Sync
 pub type AnyError = Box<dyn Error + Send + Sync>;
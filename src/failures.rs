@@ -0,0 +1,116 @@
+use std::{collections::{HashMap, VecDeque}, fmt::Write, fs, time::SystemTime};
+
+use humantime::format_rfc3339_seconds;
+use libc::c_int;
+
+/// Hidden directory under the root where TFS reports on itself.
+pub const TFS_DIRECTORY_NAME: &str = ".tfs";
+/// Under `.tfs`, the most recent failures of each process that's had any.
+pub const LAST_ERROR_NAME: &str = "last_error";
+
+/// How many failures are kept for each process.
+const FAILURES_PER_PROCESS: usize = 4;
+/// How many processes have their failures kept, dropping whichever failed longest ago.
+const MAX_PROCESSES: usize = 16;
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Failure {
+    pub when_failed: SystemTime,
+    pub operation: &'static str,
+    pub errno: c_int,
+    pub message: String
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+struct ProcessFailures {
+    /// Taken from `/proc` when it first failed, as it might be gone by the time
+    /// anyone looks.
+    command: String,
+    /// Oldest first.
+    failures: VecDeque<Failure>
+}
+
+/// Failures replied to the kernel, which only ever passes on their errno, kept so
+/// the message can be read back through `.tfs/last_error` or `tfs errors`.
+#[derive(PartialEq, Eq, Default, Clone, Debug)]
+pub struct FailureLog {
+    by_pid: HashMap<u32, ProcessFailures>
+}
+
+impl FailureLog {
+    /// `pid` is the caller's thread, as the kernel passes it on, whose failures are
+    /// kept with the rest of its process's.
+    pub fn record(&mut self, pid: u32, failure: Failure) {
+        let pid = get_process_id(pid);
+        if !self.by_pid.contains_key(&pid) && self.by_pid.len() >= MAX_PROCESSES {
+            let longest_ago = self.by_pid.iter()
+                .min_by_key(|(_, process_failures)| process_failures.get_when_last_failed())
+                .map(|(pid, _)| *pid);
+            if let Some(longest_ago) = longest_ago {
+                self.by_pid.remove(&longest_ago);
+            }
+        }
+        let process_failures = self.by_pid.entry(pid)
+            .or_insert_with(|| ProcessFailures {
+                command: get_command(pid),
+                failures: VecDeque::new()
+            });
+        if process_failures.failures.len() >= FAILURES_PER_PROCESS {
+            process_failures.failures.pop_front();
+        }
+        process_failures.failures.push_back(failure);
+    }
+
+    pub fn get_by_pid(&self, pid: u32) -> impl Iterator<Item = &Failure> {
+        self.by_pid.get(&pid)
+            .into_iter()
+            .flat_map(|process_failures| process_failures.failures.iter())
+    }
+
+    /// Processes that failed most recently go first, each with their failures, most
+    /// recent last, e.g.,
+    ///
+    /// ```text
+    /// Process `1234` (`mv`):
+    ///   2026-01-01T12:00:00Z rename failed with errno `18`. Renaming a tag, ...
+    /// ```
+    pub fn render(&self) -> String {
+        let mut processes: Vec<_> = self.by_pid.iter().collect();
+        processes.sort_by_key(|(_, process_failures)| process_failures.get_when_last_failed());
+        let mut rendered = String::new();
+        for (pid, process_failures) in processes.into_iter().rev() {
+            // Writing to a `String` doesn't fail.
+            let _ = writeln!(rendered, "Process `{pid}` (`{}`):", process_failures.command);
+            for failure in &process_failures.failures {
+                let _ = writeln!(rendered, "  {} {} failed with errno `{}`. {}",
+                    format_rfc3339_seconds(failure.when_failed), failure.operation,
+                    failure.errno, failure.message);
+            }
+        }
+        rendered
+    }
+}
+
+impl ProcessFailures {
+    fn get_when_last_failed(&self) -> Option<SystemTime> {
+        self.failures.back()
+            .map(|failure| failure.when_failed)
+    }
+}
+
+/// The thread group, i.e., the process, that thread `pid` is in, or `pid` itself if
+/// it's gone.
+fn get_process_id(pid: u32) -> u32 {
+    fs::read_to_string(format!("/proc/{pid}/status"))
+        .ok()
+        .and_then(|status| status.lines()
+            .find_map(|line| line.strip_prefix("Tgid:"))
+            .and_then(|process_id| process_id.trim().parse().ok()))
+        .unwrap_or(pid)
+}
+
+fn get_command(pid: u32) -> String {
+    fs::read_to_string(format!("/proc/{pid}/comm"))
+        .map(|command| command.trim_end().to_string())
+        .unwrap_or_else(|_| String::from("unknown"))
+}
@@ -3,13 +3,14 @@ use std::{collections::{HashMap, HashSet}, fmt::Display, time::SystemTime};
 use bon::Builder;
 use fuser::FileType;
 
-use crate::{entries::TfsEntry, errors::ResultBtAny, inodes::{FileInode, TagInodes}, unwrap_or,
+use crate::{entries::TfsEntry, errors::{ResultBt, ResultBtAny},
+    inodes::{FileInode, TagInodes}, names::{check_file_name, NameError}, unwrap_or,
     wrappers::{write_btreeset, write_iter, VecWrapper}};
 
 // TODO: Figure out eval steps. File inheriting perms
 // from directory etc., maybe rename - same with Tag builder. 
 #[derive(Builder, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Clone)]
-#[builder(on(String, into))]
+#[builder(on(String, into), finish_fn(name = build_unchecked, vis = ""))]
 pub struct TfsFile {
     pub name: String,
     pub inode: FileInode,
@@ -29,6 +30,15 @@ pub struct TfsFile {
     pub tags: TagInodes,
 }
 
+impl<S: tfs_file_builder::IsComplete> TfsFileBuilder<S> {
+    /// Builds the file, as long as it has a valid name.
+    pub fn build(self) -> ResultBt<TfsFile, NameError> {
+        let tfs_file = self.build_unchecked();
+        check_file_name(&tfs_file.name)?;
+        Ok(tfs_file)
+    }
+}
+
 impl TfsEntry for TfsFile {
     fn get_name(&self) -> &str {
         &self.name
@@ -190,11 +200,6 @@ impl IndexedFiles {
         self.files.keys()
     }
 
-    pub fn get_free_inode(&self) -> ResultBtAny<FileInode> {
-        let inodes_inuse = self.get_inuse_inodes_();
-        FileInode::try_from_free_inodes(inodes_inuse)
-    }
-
     fn will_collide(&self, check_for: &TfsFile) -> ResultBtAny<()> {
         Self::_will_collide(&self.files, &self.by_tags, &self.by_name_and_tags,
             &check_for.name, &check_for.inode, &check_for.tags)
@@ -1,21 +1,45 @@
-use std::{fmt::Display, fs::File, io::BufReader, path::PathBuf, thread::sleep,
-    time::{Duration, Instant}};
+use std::{collections::{HashMap, HashSet}, fmt::Display, fs::File, io::BufReader,
+    path::{Path, PathBuf}, sync::mpsc, thread::sleep,
+    time::{Duration, Instant, SystemTime}};
 
 use bon::bon;
-use fuser::{spawn_mount2, FileAttr, MountOption};
+use fuser::{spawn_mount2, FileAttr, MountOption, FUSE_ROOT_ID};
 use libc::SIGTERM;
 use signal_hook::iterator::Signals;
 use tracing::{info, instrument, warn};
 
 #[cfg(test)]
 use crate::{snapshots::StubSnapshots, storage::StubStorage};
-use crate::{entries::TfsEntry, errors::{collect_errors, AnyError, ResultBtAny}, files::{IndexedFiles, TfsFile},
-    inodes::{FileInode, NamespaceInode, TagInode, TagInodes}, journal::TfsJournal,
-    namespaces::{self, IndexedNamepsaces, TfsNamespace}, os::{COMMON_BLOCK_SIZE, NO_RDEV},
+use crate::{caching::CachingPolicy, configuration::{MountConfiguration, StorageBackend},
+    contents::{IndexedContents, TfsContent}, entries::TfsEntry,
+    errors::{collect_errors, AnyError, ResultBtAny, ResultBtTfs, TfsError},
+    failures::FailureLog,
+    files::{IndexedFiles, TfsFile},
+    inodes::{FileInode, InodeAllocator, NamespaceInode, TagInode, TagInodes},
+    invalidations::{spawn_invalidator, Invalidation, Invalidations}, journal::TfsJournal,
+    mounts::{MountRecord, FILESYSTEM_NAME},
+    names::{check_file_name, check_tag_name, NameError},
+    namespaces::{self, IndexedNamepsaces, NamespaceQuery, TfsNamespace},
+    os::{get_directory_link_count, COMMON_BLOCK_SIZE, NO_RDEV, UNCOUNTED_DIRECTORY_LINKS},
     path::{format_tags, parse_tags}, persistence::{deserialize_tag_filesystem,
-    serialize_tag_filesystem}, snapshots::{PersistentSnapshots, TfsSnapshots},
-    storage::{DelegateStorage, TfsStorage}, tags::{IndexedTags, TfsTag},
-    wrappers::VecWrapper, WithBacktrace};
+    serialize_tag_filesystem}, queries::{parse_comparison, parse_file_predicate,
+    FilePredicate, TagPredicate},
+    snapshots::{PersistentSnapshots, TfsSnapshots},
+    storage::{DelegateStorage, TfsStorage}, stores::{StoreLocation, StoreLock},
+    tags::{IndexedTags, TfsTag},
+    trash::{IndexedTrash, TfsTrashed, TrashedItem, TRASH_NAME}, wrappers::VecWrapper,
+    ResultExt, WithBacktrace};
+
+/// What renaming onto an existing file does, as picked by `renameat2`'s flags.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RenameMode {
+    /// Replaces the existing file.
+    Replace,
+    /// Fails instead, i.e., `RENAME_NOREPLACE`.
+    NoReplace,
+    /// Swaps the two files' names and tags, i.e., `RENAME_EXCHANGE`.
+    Exchange
+}
 
 #[derive(Debug)]
 pub struct TagFilesystem<Storage = DelegateStorage, Snapshots = PersistentSnapshots>
@@ -23,56 +47,143 @@ where Storage: TfsStorage, Snapshots: TfsSnapshots {
     files: IndexedFiles,
     tags: IndexedTags,
     namespaces: IndexedNamepsaces,
+    contents: IndexedContents,
+    trash: IndexedTrash,
+    file_inodes: InodeAllocator<FileInode>,
+    tag_inodes: InodeAllocator<TagInode>,
+    namespace_inodes: InodeAllocator<NamespaceInode>,
+    /// How many times each inode was handed to the kernel without being forgotten.
+    lookups: HashMap<u64, u64>,
     storage: Storage,
     snapshots: Snapshots,
-    journal: TfsJournal
+    journal: TfsJournal,
+    /// Where to send what changes make stale in the kernel's caches, once mounted.
+    invalidations: Option<Invalidations>,
+    caching: CachingPolicy,
+    /// How long trashed items are kept before `purge_expired_trash` purges them.
+    trash_retention: Duration,
+    /// Refuses changes through the mount, and leaves the snapshots alone.
+    is_read_only: bool,
+    failures: FailureLog
 }
 
 impl TagFilesystem {
-    const LOOP_COOLDOWN_SECONDS: u64 = 1;
-    const PERSIST_COOLDOWN_SECONDS: u64 = 5;
+    pub fn try_new(store_location: &StoreLocation, data_directory: &Path) -> ResultBtAny<Self> {
+        let snapshot_directory = store_location.get_snapshot_directory(data_directory);
+        let delegate_directory = store_location.get_delegate_directory(data_directory);
+        Self::try_load(PersistentSnapshots::try_new(snapshot_directory)?,
+            DelegateStorage::try_new(delegate_directory)?)
+    }
 
-    pub fn try_new(mount_path: &PathBuf) -> ResultBtAny<Self> {
-        let filesystem_snapshots = PersistentSnapshots::try_new(mount_path)?;
+    /// Loads the snapshot into memory once, refusing stores that don't exist yet
+    /// rather than creating them.
+    pub fn try_open_read_only(store_location: &StoreLocation, data_directory: &Path)
+    -> ResultBtAny<Self> {
+        let snapshot_directory = store_location.get_snapshot_directory(data_directory);
+        let delegate_directory = store_location.get_delegate_directory(data_directory);
+        let mut tag_filesystem = Self::try_load(
+            PersistentSnapshots::try_open(snapshot_directory)?,
+            DelegateStorage::try_open(delegate_directory)?)?;
+        tag_filesystem.set_is_read_only(true);
+        Ok(tag_filesystem)
+    }
+
+    fn try_load(filesystem_snapshots: PersistentSnapshots, storage: DelegateStorage)
+    -> ResultBtAny<Self> {
         let mut indexed_files = IndexedFiles::new();
         let mut indexed_tags = IndexedTags::new();
+        let mut indexed_contents = IndexedContents::new();
+        let mut indexed_trash = IndexedTrash::new();
+        let mut file_inodes = InodeAllocator::new();
+        let mut tag_inodes = InodeAllocator::new();
         if let Ok(safe_snapshot) = filesystem_snapshots.open_safe() {
-            let (persisted_files, persisted_tags) = deserialize_tag_filesystem(
-                BufReader::new(&safe_snapshot))?;
+            let (persisted_files, persisted_tags, persisted_contents, persisted_trash,
+                persisted_file_inodes, persisted_tag_inodes) =
+                deserialize_tag_filesystem(BufReader::new(&safe_snapshot))?;
+            (file_inodes, tag_inodes) = (persisted_file_inodes, persisted_tag_inodes);
             for persisted_file in persisted_files {
+                file_inodes.mark_used(&persisted_file.inode);
                 indexed_files.add(persisted_file)?;
             }
             for persisted_tag in persisted_tags {
+                tag_inodes.mark_used(&persisted_tag.inode);
                 indexed_tags.add(persisted_tag)?;
             }
+            for persisted_content in persisted_contents {
+                indexed_contents.add(persisted_content)?;
+            }
+            for persisted_trashed in persisted_trash {
+                match &persisted_trashed.item {
+                    TrashedItem::File(tfs_file) => file_inodes.mark_used(&tfs_file.inode),
+                    TrashedItem::Tag(tfs_tag, _) => tag_inodes.mark_used(&tfs_tag.inode)
+                }
+                indexed_trash.add_existing(persisted_trashed)?;
+            }
         }
         Ok(Self {
             files: indexed_files,
             tags: indexed_tags,
             namespaces: IndexedNamepsaces::new(),
-            storage: DelegateStorage::try_new(mount_path)?,
+            contents: indexed_contents,
+            trash: indexed_trash,
+            file_inodes,
+            tag_inodes,
+            namespace_inodes: InodeAllocator::new(),
+            lookups: HashMap::new(),
+            storage,
             snapshots: filesystem_snapshots,
-            journal: TfsJournal::new()
+            journal: TfsJournal::new(),
+            invalidations: None,
+            caching: CachingPolicy::default(),
+            trash_retention: IndexedTrash::DEFAULT_RETENTION,
+            is_read_only: false,
+            failures: FailureLog::default()
         })
     }
 
     #[instrument]
-    pub fn run_filesystem(mount_path: &PathBuf) -> ResultBtAny<()> {
-        let mount_handle = spawn_mount2(Self::try_new(mount_path)?,
-            mount_path,
-            &[MountOption::AutoUnmount, MountOption::AllowRoot])?;
+    pub fn run_filesystem(mount_path: &PathBuf, store_location: &StoreLocation,
+        configuration: &MountConfiguration) -> ResultBtAny<()>
+    {
+        let mut mount_options = configuration.mount_options.get_mount_options()?;
+        mount_options.push(MountOption::FSName(FILESYSTEM_NAME.to_string()));
+        let data_directory = configuration.paths.get_data_directory();
+        let is_read_only = configuration.mount_options.read_only;
+        // Kept until unmounting, by living as long as this does. Read-only mounts
+        // load the snapshot into memory once and never write it back, so can run
+        // alongside the writer.
+        let _store_lock = if is_read_only {
+            None
+        } else {
+            Some(StoreLock::try_acquire(&store_location.get_lock_path(&data_directory))?)
+        };
+        let mut tag_filesystem = match configuration.storage.backend {
+            StorageBackend::Delegate if is_read_only =>
+                Self::try_open_read_only(store_location, &data_directory)?,
+            StorageBackend::Delegate => Self::try_new(store_location, &data_directory)?
+        };
+        tag_filesystem.set_trash_retention(configuration.persistence.trash_retention);
+        if !is_read_only {
+            tag_filesystem.purge_expired_trash()?;
+        }
+        tag_filesystem.set_caching(configuration.caching);
+        let (invalidations, to_invalidate) = mpsc::channel();
+        tag_filesystem.set_invalidations(invalidations);
+        let mount_handle = spawn_mount2(tag_filesystem, mount_path, &mount_options)?;
+        spawn_invalidator(mount_handle.notifier(), to_invalidate);
         info!("Mounted TFS at `{}`.", mount_path.to_string_lossy());
+        MountRecord::new(mount_path, store_location, &data_directory, is_read_only).save()?;
 
         let mut unix_signals = Signals::new(&[SIGTERM])?;
 
         let root_dir = File::open(mount_path)?;
+        let persistence = configuration.persistence;
         let mut last_sync = Instant::now();
         loop {
-            sleep(Duration::from_secs(Self::LOOP_COOLDOWN_SECONDS));
-            info!("Slept `{}` seconds.", Self::LOOP_COOLDOWN_SECONDS); 
+            sleep(persistence.loop_interval);
+            info!("Slept `{:?}`.", persistence.loop_interval);
 
-            let should_persist =
-                last_sync.elapsed() > Duration::from_secs(Self::PERSIST_COOLDOWN_SECONDS);
+            let should_persist = last_sync.elapsed() > persistence.persist_interval;
             if should_persist {
                 root_dir.sync_all()?;
                 info!("Directing `{}` to sync.", mount_path.to_string_lossy());
@@ -84,6 +195,7 @@ impl TagFilesystem {
                 if is_sigterm {
                     drop(mount_handle);
                     info!("Unmounting TFS.");
+                    MountRecord::remove(mount_path)?;
                     return Ok(());
                 }
             }
@@ -98,51 +210,212 @@ where Storage: TfsStorage, Snapshots: TfsSnapshots {
         &self.files
     }
 
-    pub fn get_free_file_inode(&self) -> ResultBtAny<FileInode> {
-        self.files.get_free_inode()
+    pub fn get_free_file_inode(&self) -> ResultBtTfs<FileInode> {
+        Ok(self.file_inodes.get_free()?)
     }
 
     pub fn get_tags(&self) -> &IndexedTags {
         &self.tags
     }
 
-    pub fn get_free_tag_inode(&self) -> ResultBtAny<TagInode> {
-        self.tags.get_free_inode()
+    pub fn get_free_tag_inode(&self) -> ResultBtTfs<TagInode> {
+        Ok(self.tag_inodes.get_free()?)
     }
 
     pub fn get_namespaces(&self) -> &IndexedNamepsaces {
         &self.namespaces
     }
 
-    pub fn get_free_namespace_inode(&self) -> ResultBtAny<NamespaceInode> {
-        self.namespaces.get_free_inode()
+    pub fn get_free_namespace_inode(&self) -> ResultBtTfs<NamespaceInode> {
+        Ok(self.namespace_inodes.get_free()?)
+    }
+
+    /// FUSE generation of the inode, which changes whenever its id is reused.
+    pub fn get_generation(&self, inode_id: u64) -> u64 {
+        if FileInode::get_is_file(inode_id) {
+            self.file_inodes.get_generation(inode_id)
+        } else if TagInode::get_is_tag(inode_id) {
+            self.tag_inodes.get_generation(inode_id)
+        } else {
+            self.namespace_inodes.get_generation(inode_id)
+        }
+    }
+
+    pub fn get_contents(&self) -> &IndexedContents {
+        &self.contents
+    }
+
+    pub fn get_trash(&self) -> &IndexedTrash {
+        &self.trash
     }
 
+    /// Predicate namespaces can take in files of the same name under different tags,
+    /// which can't be told apart by name, so are refused rather than one picked.
     pub fn get_file_by_name_and_namespace_inode(&self, file_name: &str,
-        namespace_inode: &NamespaceInode) -> ResultBtAny<&TfsFile>
+        namespace_inode: &NamespaceInode) -> ResultBtTfs<&TfsFile>
     {
-        let namespace_tags = &self.namespaces.get_by_inode(namespace_inode)?.tags;
-        self.files.get_by_name_and_tags(file_name, namespace_tags)
-            .ok_or(format!("File with name `{file_name}` and tags `{namespace_tags}` \
-                does not exist.").into())
+        let tfs_namespace = self.get_namespace(namespace_inode)?;
+        let now = SystemTime::now();
+        let mut matching_files = vec![];
+        for tag_set in self.get_namespace_tag_sets(tfs_namespace, true) {
+            if let Some(tfs_file) = self.files.get_by_name_and_tags(file_name, tag_set)
+                && self.get_is_file_matching(tfs_file, tfs_namespace, None, now)?
+            {
+                matching_files.push(tfs_file);
+            }
+        }
+        match matching_files.as_slice() {
+            [tfs_file] => Ok(tfs_file),
+            [] => Err(TfsError::NotFound(format!("File with name `{file_name}` under \
+                namespace `{}` does not exist.", tfs_namespace.name)))?,
+            _ => Err(TfsError::NotFound(format!("`{}` files named `{file_name}` are under \
+                namespace `{}`, with different tags, narrow it down to one.",
+                matching_files.len(), tfs_namespace.name)))?
+        }
     }
 
+    /// Files are checked against metadata predicates on every call, as time based
+    /// ones change their answer without the filesystem changing. Files sharing a name
+    /// are left out, as `get_file_by_name_and_namespace_inode` refuses them.
     pub fn get_files_by_namespace_inode<'a>(&'a self, namespace_inode: &NamespaceInode)
-    -> ResultBtAny<impl Iterator<Item = &'a TfsFile>> {
-        let namespace_tags = &self.namespaces.get_by_inode(namespace_inode)?.tags;
-        Ok(self.files.get_by_tags(namespace_tags))
+    -> ResultBtTfs<impl Iterator<Item = &'a TfsFile>> {
+        let tfs_namespace = self.get_namespace(namespace_inode)?;
+        let now = SystemTime::now();
+        let content_matches = self.get_content_matches(tfs_namespace);
+        let mut inscope_files = vec![];
+        let mut name_counts: HashMap<&str, usize> = HashMap::new();
+        for tag_set in self.get_namespace_tag_sets(tfs_namespace, true) {
+            for tfs_file in self.files.get_by_tags(tag_set) {
+                if self.get_is_file_matching(tfs_file, tfs_namespace,
+                    content_matches.as_ref(), now)?
+                {
+                    inscope_files.push(tfs_file);
+                    *name_counts.entry(&tfs_file.name).or_default() += 1;
+                }
+            }
+        }
+        inscope_files.retain(|tfs_file| {
+            let is_unique = name_counts[tfs_file.name.as_str()] == 1;
+            if !is_unique {
+                info!("Left out `{}` under namespace `{}`, as its name is shared.",
+                    tfs_file.name, tfs_namespace.name);
+            }
+            is_unique
+        });
+        Ok(inscope_files.into_iter())
+    }
+
+    fn get_namespace(&self, namespace_inode: &NamespaceInode) -> ResultBtTfs<&TfsNamespace> {
+        self.namespaces.get_by_inode(namespace_inode)
+            .map_err_inner(|e| TfsError::NotFound(e.to_string()))
+    }
+
+    /// Content predicates are answered by `content_matches` when given, as found by
+    /// `get_content_matches`, or else by the file's own terms, e.g., for a lookup.
+    fn get_is_file_matching(&self, tfs_file: &TfsFile, tfs_namespace: &TfsNamespace,
+        content_matches: Option<&HashSet<FileInode>>, now: SystemTime) -> ResultBtAny<bool>
+    {
+        if let Some(content_matches) = content_matches
+            && !content_matches.contains(&tfs_file.inode)
+        {
+            return Ok(false);
+        }
+        for file_predicate in &tfs_namespace.file_predicates {
+            let is_content_matched = content_matches.is_some()
+                && matches!(file_predicate, FilePredicate::Content(_));
+            if is_content_matched {
+                continue;
+            }
+            if !file_predicate.is_satisfied_by(tfs_file, &self.storage, &self.contents, now)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Files meeting all of the namespace's content predicates, from the inverted
+    /// index, or `None` if it has none.
+    fn get_content_matches(&self, tfs_namespace: &TfsNamespace)
+    -> Option<HashSet<FileInode>> {
+        let mut content_matches: Option<HashSet<FileInode>> = None;
+        for file_predicate in &tfs_namespace.file_predicates {
+            let FilePredicate::Content(terms) = file_predicate else {
+                continue;
+            };
+            let term_matches = self.contents.get_by_terms(terms);
+            content_matches = Some(match content_matches {
+                Some(content_matches) => &content_matches & &term_matches,
+                None => term_matches
+            });
+        }
+        content_matches
+    }
+
+    /// Tag sets of files that fall under the namespace. Besides having all of the
+    /// namespace's tags, every predicate has to be met by one of the other tags.
+    /// When `is_exact`, the other tags also all have to meet some predicate.
+    fn get_namespace_tag_sets<'a>(&'a self, tfs_namespace: &'a TfsNamespace,
+        is_exact: bool) -> Vec<&'a TagInodes>
+    {
+        if tfs_namespace.get_is_exact() && is_exact {
+            return vec![&tfs_namespace.tags];
+        }
+
+        let predicate_inodes: Vec<_> = tfs_namespace.predicates.iter()
+            .map(|predicate| self.tags.get_by_predicate(predicate))
+            .collect();
+        self.files.get_tag_sets()
+            .filter(|tag_set| {
+                if !tag_set.0.is_superset(&tfs_namespace.tags.0) {
+                    return false;
+                }
+                let other_tags = &tag_set.0 - &tfs_namespace.tags.0;
+                let are_predicates_met = predicate_inodes.iter()
+                    .all(|inodes| !inodes.0.is_disjoint(&other_tags));
+                let are_others_matched = other_tags.iter()
+                    .all(|inode| predicate_inodes.iter()
+                        .any(|inodes| inodes.0.contains(inode)));
+                are_predicates_met && (!is_exact || are_others_matched)
+            })
+            .collect()
+    }
+
+    pub fn get_namespace_inrange_tags(&self, tfs_namespace: &TfsNamespace)
+    -> ResultBtTfs<Vec<&TfsTag>> {
+        if tfs_namespace.get_is_exact() {
+            return self.get_inrange_tags(tfs_namespace);
+        }
+
+        let now = SystemTime::now();
+        let content_matches = self.get_content_matches(tfs_namespace);
+        let mut inrange_inodes = tfs_namespace.tags.clone();
+        for tag_set in self.get_namespace_tag_sets(tfs_namespace, false) {
+            // Only tags that lead to at least one matching file are neighbours.
+            let mut has_matching_file = tfs_namespace.file_predicates.is_empty();
+            for tfs_file in self.files.get_by_tags(tag_set) {
+                if has_matching_file {
+                    break;
+                }
+                has_matching_file = self.get_is_file_matching(tfs_file, tfs_namespace,
+                    content_matches.as_ref(), now)?;
+            }
+            if has_matching_file {
+                inrange_inodes.0.extend(&tag_set.0);
+            }
+        }
+        inrange_inodes.0.iter()
+            .map(|inode| self.get_tag(inode))
+            .collect()
     }
 
     pub fn get_inrange_tags<'a>(&self, tag_inodes: impl Into<&'a TagInodes>)
-    -> ResultBtAny<Vec<&TfsTag>> {
+    -> ResultBtTfs<Vec<&TfsTag>> {
         let tag_inodes = tag_inodes.into();
 
         let mut inrange_tags = vec![];
 
         for tag_inode in &tag_inodes.0 {
-            inrange_tags.push(self.get_tags()
-                .get_by_inode(tag_inode)
-                .ok_or(format!("Tag inode `{tag_inode}` does not exist."))?);
+            inrange_tags.push(self.get_tag(tag_inode)?);
         }
 
         inrange_tags.extend(self.get_neighbour_tags(tag_inodes)?);
@@ -151,20 +424,30 @@ where Storage: TfsStorage, Snapshots: TfsSnapshots {
     }
 
     pub fn get_neighbour_tags<'a>(&self, tag_inodes: impl Into<&'a TagInodes>)
-    -> ResultBtAny<Vec<&TfsTag>> {
+    -> ResultBtTfs<Vec<&TfsTag>> {
         let tag_inodes = tag_inodes.into();
 
         let neighbour_inodes = self.files.get_neighbour_tag_inodes(tag_inodes);
         neighbour_inodes.0.iter()  
-            .map(|inode| self.tags.get_by_inode(inode)
-                .ok_or(format!("Tag inode with id `{}` \
-                    does not exist.", inode.get_id()).into()))
+            .map(|inode| self.get_tag(inode))
             .collect()
     }
 
+    fn get_tag(&self, tag_inode: &TagInode) -> ResultBtTfs<&TfsTag> {
+        Ok(self.tags.get_by_inode(tag_inode)
+            .ok_or(TfsError::NotFound(format!("Tag with inode `{tag_inode}` does \
+                not exist.")))?)
+    }
+
+    fn get_tag_by_name(&self, tag_name: &str) -> ResultBtTfs<&TfsTag> {
+        Ok(self.tags.get_by_name(tag_name)
+            .ok_or(TfsError::NotFound(format!("Tag `{tag_name}` does not exist.")))?)
+    }
+
     #[instrument]
     fn get_namespace_string_from_tags(filesystem_tags: &IndexedTags,
-        tag_inodes: &TagInodes) -> ResultBtAny<String>
+        tag_inodes: &TagInodes, tag_predicates: &[TagPredicate],
+        file_predicates: &[FilePredicate]) -> ResultBtAny<String>
     {
         let mut existent_inodes = vec![];
         let mut nonexistent_inodes = vec![];
@@ -180,46 +463,75 @@ where Storage: TfsStorage, Snapshots: TfsSnapshots {
                 VecWrapper(nonexistent_inodes)))?;
         }
 
+        let tag_predicates: Vec<_> = tag_predicates.iter()
+            .map(TagPredicate::to_string)
+            .chain(file_predicates.iter().map(FilePredicate::to_string))
+            .collect();
         Ok(format_tags(existent_inodes.iter()
-            .map(|tag| tag.name.as_str())))
+            .map(|tag| tag.name.as_str())
+            .chain(tag_predicates.iter().map(String::as_str))))
     }
 
-    pub fn get_fuser_attributes(&self, inode_id: u64) -> ResultBtAny<FileAttr> {
-        FileInode::try_from(inode_id)
-            .and_then(|inode| self.get_file_fuser(&inode))
-            .or(TagInode::try_from(inode_id)
-                .and_then(|inode| self.get_tag_fuser(&inode)))
-                .or(NamespaceInode::try_from(inode_id)
-                    .and_then(|inode| self.get_namespace_fuser(&inode)))
-            .map_err(|_| format!("`{inode_id}` is not either of a file,
-                tag or namespace inode.").into())
+    pub fn get_fuser_attributes(&self, inode_id: u64) -> ResultBtTfs<FileAttr> {
+        if let Ok(file_inode) = FileInode::try_from(inode_id) {
+            self.get_file_fuser(&file_inode)
+        } else if let Ok(tag_inode) = TagInode::try_from(inode_id) {
+            self.get_tag_fuser(&tag_inode)
+        } else {
+            self.get_namespace_fuser(&NamespaceInode::try_from(inode_id)?)
+        }
     }
 
-    pub fn get_file_fuser(&self, file_inode: &FileInode) -> ResultBtAny<FileAttr> {
+    pub fn get_file_fuser(&self, file_inode: &FileInode) -> ResultBtTfs<FileAttr> {
         let target_file = self.files.get_by_inode(&file_inode)
-            .ok_or(format!("File with inode `{file_inode}` does not exist."))?;
+            .or(self.trash.get_file_by_inode(file_inode))
+            .ok_or(TfsError::NotFound(format!("File with inode `{file_inode}` does \
+                not exist.")))?;
         Ok(Self::to_fuser()
             .tfs_entry(target_file)
             .file_size(self.storage.get_file_size(&file_inode)?)
             .call())
     }
 
-    pub fn get_tag_fuser(&self, tag_inode: &TagInode) -> ResultBtAny<FileAttr> {
-        let target_tag = self.tags.get_by_inode(&tag_inode)
-            .ok_or(format!("Tag with inode `{tag_inode}` does not exist."))?;
+    pub fn get_tag_fuser(&self, tag_inode: &TagInode) -> ResultBtTfs<FileAttr> {
+        let target_tag = match self.trash.get_tag_by_inode(tag_inode) {
+            Some(trashed_tag) => trashed_tag,
+            None => self.get_tag(tag_inode)?
+        };
         Ok(Self::to_fuser()
             .tfs_entry(target_tag)
+            .link_count(UNCOUNTED_DIRECTORY_LINKS)
             .call())
     }
 
-    pub fn get_namespace_fuser(&self, namespace_inode: &NamespaceInode) -> ResultBtAny<FileAttr> {
-        Ok(namespaces::get_fuse_attributes(&namespace_inode))
+    pub fn get_trashed_fuser(&self, trash_name: &str) -> ResultBtTfs<FileAttr> {
+        let trashed = self.get_trashed(trash_name)?;
+        match &trashed.item {
+            TrashedItem::File(tfs_file) => self.get_file_fuser(&tfs_file.inode),
+            TrashedItem::Tag(tfs_tag, _) => self.get_tag_fuser(&tfs_tag.inode)
+        }
+    }
+
+    pub fn get_namespace_fuser(&self, namespace_inode: &NamespaceInode) -> ResultBtTfs<FileAttr> {
+        self.get_namespace(namespace_inode)?;
+        Ok(namespaces::get_fuse_attributes(namespace_inode, UNCOUNTED_DIRECTORY_LINKS))
+    }
+
+    /// Every tag is a subdirectory of the root.
+    pub fn get_root_link_count(&self) -> u32 {
+        get_directory_link_count(self.tags.get_all().count())
+    }
+
+    fn get_trashed(&self, trash_name: &str) -> ResultBtTfs<&TfsTrashed> {
+        Ok(self.trash.get_by_name(trash_name)
+            .ok_or(TfsError::NotFound(format!("`{trash_name}` is not in the trash.")))?)
     }
 
     #[builder]
-    fn to_fuser(tfs_entry: &dyn TfsEntry, file_size: Option<u64>) -> FileAttr {
+    fn to_fuser(tfs_entry: &dyn TfsEntry, file_size: Option<u64>, link_count: Option<u32>)
+    -> FileAttr {
         let file_size = file_size.unwrap_or(0);
-        // TODO: What to do with `blocks`, `nlink` and `flags`.
+        // TODO: What to do with `blocks` and `flags`.
         FileAttr {
             ino: tfs_entry.get_inode_id(),
             size: file_size,
@@ -230,7 +542,9 @@ where Storage: TfsStorage, Snapshots: TfsSnapshots {
             crtime: tfs_entry.get_when_created(),
             kind: tfs_entry.get_file_kind(),
             perm: tfs_entry.get_permissions(),
-            nlink: 0,
+            // The kernel won't link to entries without any links, and every file has
+            // the one name.
+            nlink: link_count.unwrap_or(1),
             uid: tfs_entry.get_owner(),
             gid: tfs_entry.get_group(),
             rdev: NO_RDEV,
@@ -239,55 +553,63 @@ where Storage: TfsStorage, Snapshots: TfsSnapshots {
         }
     }
 
-    fn check_tags_exist(&self, to_check: &TagInodes) -> ResultBtAny<()> {
+    fn check_tags_exist(&self, to_check: &TagInodes) -> ResultBtTfs<()> {
         let doesnt_exist: Vec<_> = to_check.0.iter()
             .filter(|inode| self.tags.get_by_inode(inode)
                 .is_none())
             .collect();
         if doesnt_exist.len() > 0 {
-            Err(format!(
+            Err(TfsError::NotFound(format!(
                 "These tag inodes don't exist `{}`.",
-                VecWrapper(doesnt_exist)))?;
+                VecWrapper(doesnt_exist))))?;
         }
         Ok(())
     }
 
-    fn check_if_file_is_valid(&self, to_check: &TfsFile) -> ResultBtAny<()> {
+    fn check_if_file_is_valid(&self, to_check: &TfsFile) -> ResultBtTfs<()> {
+        check_file_name(&to_check.name)?;
         if let Some(similar_file) = self.files.get_by_name_and_tags(&to_check.name,
             &to_check.tags)
         {
             let are_files_same = to_check.inode == similar_file.inode;
             if !are_files_same {
-                return Err(format!("File with name `{}` and tags `{}` already \
-                    exists.", to_check.inode, to_check.tags).into());
+                Err(TfsError::AlreadyExists(format!("File with name `{}` and tags \
+                    `{}` already exists.", to_check.name, to_check.tags)))?;
             }
         }
 
+        // Untagged files are listed under the root, along with every tag.
+        let is_untagged = to_check.tags.0.is_empty();
+        if is_untagged && self.tags.get_by_name(&to_check.name).is_some() {
+            Err(TfsError::AlreadyExists(format!("Untagged file can't have the same \
+                name as a tag, `{}`.", to_check.name)))?;
+        }
+
         for inrange_tag in self.get_inrange_tags(to_check)? {
-            let are_names_name = to_check.name == inrange_tag.name;
-            if are_names_name {
-                return Err(format!("File name is same as one of it's tags \
-                    or neighbouring tags, `{}`.",
-                    to_check.name).into());
+            let are_names_same = to_check.name == inrange_tag.name;
+            if are_names_same {
+                Err(TfsError::AlreadyExists(format!("File name is same as one of it's \
+                    tags or neighbouring tags, `{}`.", to_check.name)))?;
             }
         }
         Ok(())
     }
 
-    fn check_if_tag_is_valid<'a>(&self, tag_inode: impl Into<&'a TagInode>) -> ResultBtAny<()> {
+    fn check_if_tag_is_valid<'a>(&self, tag_inode: impl Into<&'a TagInode>)
+    -> ResultBtTfs<()> {
         let tag_inode = tag_inode.into();
-        let target_tag = self.tags.get_by_inode(tag_inode)
-            .ok_or(format!("Tag with inode `{tag_inode}` does not exist."))?;
-        self.check_if_tag_is_valid_(target_tag)
+        self.check_if_tag_is_valid_(self.get_tag(tag_inode)?)
     }
 
-    fn check_if_tag_is_valid_(&self, to_check: &TfsTag) -> ResultBtAny<()> {
+    fn check_if_tag_is_valid_(&self, to_check: &TfsTag) -> ResultBtTfs<()> {
+        check_tag_name(&to_check.name)?;
+
         for tfs_tag in self.tags.get_all() {
             let is_same = to_check.inode == tfs_tag.inode;
             let is_colliding = to_check.name == tfs_tag.name; 
             if !is_same && is_colliding {
-                return Err(format!("Tag already exists with name `{}`",
-                    to_check.name).into());
+                Err(TfsError::AlreadyExists(format!("Tag already exists with name `{}`.",
+                    to_check.name)))?;
             }
         }
 
@@ -299,8 +621,8 @@ where Storage: TfsStorage, Snapshots: TfsSnapshots {
             for file in self.files.get_by_tags(&tag_inodes) {
                 let is_colliding = to_check.name == file.name;
                 if is_colliding {
-                    return Err(format!("Tag has same name as file w/ this tag, `{}`.",
-                        to_check.name).into());
+                    Err(TfsError::AlreadyExists(format!("Tag has same name as file w/ \
+                        this tag, `{}`.", to_check.name)))?;
                 }
             }
 
@@ -309,8 +631,8 @@ where Storage: TfsStorage, Snapshots: TfsSnapshots {
             for file in self.files.get_by_tags(&tag_inodes) {
                 let is_colliding = to_check.name == file.name;
                 if is_colliding {
-                    return Err(format!("Tag has same name as neighbouring file, `{}`.",
-                        to_check.name).into());
+                    Err(TfsError::AlreadyExists(format!("Tag has same name as \
+                        neighbouring file, `{}`.", to_check.name)))?;
                 }
             }
         }
@@ -318,8 +640,8 @@ where Storage: TfsStorage, Snapshots: TfsSnapshots {
         for untagged_file in self.files.get_by_tags(&TagInodes::new()) {
             let is_colliding = to_check.name == untagged_file.name;
             if is_colliding {
-                return Err(format!("Tag has same name as untagged file, `{}`.",
-                    to_check.name).into());
+                Err(TfsError::AlreadyExists(format!("Tag has same name as untagged \
+                    file, `{}`.", to_check.name)))?;
             }
         }
         Ok(())
@@ -329,73 +651,319 @@ where Storage: TfsStorage, Snapshots: TfsSnapshots {
         &self.storage
     }
 
-    pub fn add_file(&mut self, to_add: TfsFile) -> ResultBtAny<&TfsFile> {
+    pub fn add_file(&mut self, to_add: TfsFile) -> ResultBtTfs<&TfsFile> {
         self.check_if_file_is_valid(&to_add)?;
         self.write_to_file(&to_add.inode, 0, &[])?;
-        self.files.add(to_add)
+        self.file_inodes.mark_used(&to_add.inode);
+        Ok(self.files.add(to_add)?)
     }
 
-    pub fn add_tag(&mut self, to_add: TfsTag) -> ResultBtAny<&TfsTag> {
+    pub fn add_tag(&mut self, to_add: TfsTag) -> ResultBtTfs<&TfsTag> {
         self.check_if_tag_is_valid_(&to_add)?;
-        self.tags.add(to_add)
+        self.tag_inodes.mark_used(&to_add.inode);
+        self.invalidate_root_link_count();
+        Ok(self.tags.add(to_add)?)
+    }
+
+    fn add_namespace(&mut self, to_add: TfsNamespace) -> ResultBtTfs<NamespaceInode> {
+        let namespace_inode = self.namespaces.add(to_add)?;
+        self.namespace_inodes.mark_used(&namespace_inode);
+        Ok(namespace_inode)
+    }
+
+    pub fn get_lookup_count(&self, inode_id: u64) -> u64 {
+        self.lookups.get(&inode_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// To be called for every entry replied to the kernel, as each adds to its
+    /// lookup count.
+    pub fn remember_lookup(&mut self, inode_id: u64) {
+        *self.lookups.entry(inode_id).or_default() += 1;
+    }
+
+    /// Namespaces only exist while the kernel holds onto them, so they're removed
+    /// once it's forgotten all of their lookups.
+    pub fn forget_lookups(&mut self, inode_id: u64, lookup_count: u64) {
+        let Some(remaining_count) = self.lookups.get_mut(&inode_id) else {
+            return;
+        };
+        *remaining_count = remaining_count.saturating_sub(lookup_count);
+        if *remaining_count > 0 {
+            return;
+        }
+
+        self.lookups.remove(&inode_id);
+        if let Ok(namespace_inode) = NamespaceInode::try_from(inode_id)
+            && self.namespaces.remove_by_inode(&namespace_inode).is_some()
+        {
+            self.namespace_inodes.release(&namespace_inode);
+        }
+    }
+
+    pub fn set_invalidations(&mut self, invalidations: Invalidations) {
+        self.invalidations = Some(invalidations);
+    }
+
+    pub fn get_caching(&self) -> &CachingPolicy {
+        &self.caching
+    }
+
+    pub fn set_caching(&mut self, caching: CachingPolicy) {
+        self.caching = caching;
+    }
+
+    pub fn set_trash_retention(&mut self, trash_retention: Duration) {
+        self.trash_retention = trash_retention;
+    }
+
+    pub fn get_is_read_only(&self) -> bool {
+        self.is_read_only
+    }
+
+    pub fn set_is_read_only(&mut self, is_read_only: bool) {
+        self.is_read_only = is_read_only;
+    }
+
+    pub fn get_failures(&self) -> &FailureLog {
+        &self.failures
+    }
+
+    pub fn get_failures_mut(&mut self) -> &mut FailureLog {
+        &mut self.failures
+    }
+
+    /// Directories the kernel could have cached names under, being the root and the
+    /// namespaces and tag directories it still holds onto.
+    fn get_cached_directories(&self) -> impl Iterator<Item = u64> {
+        [FUSE_ROOT_ID].into_iter()
+            .chain(self.lookups.keys()
+                .copied()
+                .filter(|inode_id| NamespaceInode::get_is_namespace(*inode_id)
+                    || TagInode::get_is_tag(*inode_id)))
     }
 
-    pub fn save_persistently(&self) -> ResultBtAny<()> {
+    fn invalidate(&self, invalidation: Invalidation) {
+        let Some(invalidations) = &self.invalidations else {
+            return;
+        };
+        if invalidations.send(invalidation).is_err() {
+            warn!("Invalidations are no longer being sent to the kernel.");
+        }
+    }
+
+    /// Names, e.g., of files that moved, can be cached under any directory.
+    fn invalidate_names<'a>(&self, names: impl IntoIterator<Item = &'a str>) {
+        if self.invalidations.is_none() {
+            return;
+        }
+        for name in names {
+            for parent_inode in self.get_cached_directories() {
+                self.invalidate(Invalidation::Entry {
+                    parent_inode,
+                    name: name.to_string()
+                });
+            }
+        }
+    }
+
+    /// The root's link count follows how many tags there are.
+    fn invalidate_root_link_count(&self) {
+        self.invalidate(Invalidation::Inode(FUSE_ROOT_ID));
+    }
+
+    fn get_tag_names<'a>(&'a self, tag_inodes: &'a TagInodes) -> impl Iterator<Item = &'a str> {
+        tag_inodes.0.iter()
+            .filter_map(|inode| self.tags.get_by_inode(inode))
+            .map(|tag| tag.name.as_str())
+    }
+
+    pub fn save_persistently(&self) -> ResultBtTfs<()> {
         serialize_tag_filesystem(
             &self.snapshots.create_staging()?,
             self.files.get_all().collect(),
-            self.tags.get_all().collect())?;
+            self.tags.get_all().collect(),
+            self.contents.get_all().collect(),
+            self.trash.get_all().collect(),
+            &self.file_inodes,
+            &self.tag_inodes)?;
         self.snapshots.promote_staging()?;
         Ok(())
     }
 
     pub fn write_to_file(&mut self, file_inode: &FileInode, start_position: u64, to_write: &[u8])
-    -> ResultBtAny<()> {
-        self.storage.write(file_inode, start_position, to_write)
+    -> ResultBtTfs<()> {
+        self.storage.write(file_inode, start_position, to_write)?;
+        self.contents.mark_stale(*file_inode);
+        Ok(())
     }
-    
+
+    /// Copies up to `length` bytes of one file into another, returning how many were
+    /// copied.
+    pub fn copy_between_files(&mut self, source_inode: &FileInode, source_position: u64,
+        target_inode: &FileInode, target_position: u64, length: u64) -> ResultBtTfs<u64> {
+        let copied_amount = self.storage.copy_range(source_inode, source_position,
+            target_inode, target_position, length)?;
+        self.contents.mark_stale(*target_inode);
+        Ok(copied_amount)
+    }
+
+    /// Re-reads files written since the last refresh into the content index. Ones
+    /// that aren't text, or are too large, are dropped from it. Ones that fail to be
+    /// read stay stale, to be tried again on the next refresh.
+    pub fn refresh_contents(&mut self) -> ResultBtTfs<()> {
+        let refreshes: Vec<_> = self.contents.take_stale()
+            .into_iter()
+            .map(|file_inode| {
+                let refresh = self.refresh_content(file_inode);
+                if refresh.is_err() {
+                    self.contents.mark_stale(file_inode);
+                }
+                refresh
+            })
+            .collect();
+        Ok(collect_errors(refreshes.into_iter())?)
+    }
+
+    fn refresh_content(&mut self, file_inode: FileInode) -> ResultBtTfs<()> {
+        self.contents.remove_by_inode(&file_inode);
+        let Some(tfs_file) = self.files.get_by_inode(&file_inode) else {
+            return Ok(());
+        };
+        // Content searching namespaces might now list it or not.
+        self.invalidate_names([tfs_file.name.as_str()]);
+
+        let file_size = self.storage.get_file_size(&file_inode)?;
+        if file_size > IndexedContents::MAX_INDEXED_SIZE {
+            info!("Not indexing file inode `{file_inode}` of size `{file_size}`.");
+            return Ok(());
+        }
+        let file_contents = self.storage.read(&file_inode, 0, file_size.try_into()
+            .map_err(AnyError::from)?)?;
+        let Ok(file_text) = str::from_utf8(&file_contents) else {
+            return Ok(());
+        };
+        self.contents.add(TfsContent::from_text(file_inode, file_text))?;
+        Ok(())
+    }
+
+    /// Moves a file to a new name and tags. A file already there is handled as
+    /// `rename_mode` says, being deleted along with its content when replaced.
     pub fn move_file<'a>(&mut self,
         old_tags: impl Into<&'a TagInodes>, old_name: &str,
-        new_tags: impl Into<TagInodes>, new_name: String)
-    -> ResultBtAny<()> {
+        new_tags: impl Into<TagInodes>, new_name: String, rename_mode: RenameMode)
+    -> ResultBtTfs<()> {
         let old_tags = old_tags.into();
         let new_tags = new_tags.into();
 
-        self.files.do_by_name_and_tags(old_name, old_tags, |mut file| {
-            file.try_set_name(new_name.clone())?;
-            file.try_set_tags(new_tags.clone())
-        })
-            .flatten()?;
-        let modified_file = self.files.get_by_name_and_tags(&new_name, &new_tags)
-            .expect("To have just set name and tags prior.");
-
-        let e = self.check_if_file_is_valid(modified_file)
-            .err();
-        let errors = new_tags.0.iter()
-            .filter_map(|inode| self.check_if_tag_is_valid(inode).err())
-            .collect::<Vec<_>>();
-        if e.is_some() || !errors.is_empty() {
-            self.files.do_by_name_and_tags(&new_name, &new_tags, |mut file| {
-                file.try_set_name(old_name.to_string())?;
-                file.try_set_tags(old_tags.clone())
-            })
-                .flatten()
-                .expect("To have nothing take up old name and tags in the \
-                    meanwhile.");
-            let mut _e = String::from("Name and tag(s) combination is invalid.");
-            if let Some(e) = e { _e += &format!(" {e:?}") }
-            for e in errors { _e += &format!(" {e:?}") }
-            return Err(_e.into());
+        let source_inode = self.files.get_by_name_and_tags(old_name, old_tags)
+            .ok_or(TfsError::NotFound(format!("No file matching name `{old_name}` and \
+                tag inodes `{old_tags}`.")))?
+            .inode;
+        let target_inode = self.files.get_by_name_and_tags(&new_name, &new_tags)
+            .map(|file| file.inode);
+        if target_inode == Some(source_inode) {
+            return Ok(());
+        }
+        match (rename_mode, target_inode) {
+            (RenameMode::NoReplace, Some(_)) => Err(TfsError::AlreadyExists(format!(
+                "File with name `{new_name}` and tags `{new_tags}` already exists.")))?,
+            (RenameMode::Exchange, None) => Err(TfsError::NotFound(format!("No file \
+                matching name `{new_name}` and tag inodes `{new_tags}` to exchange with.")))?,
+            _ => ()
+        }
+
+        let mut changed_tags = old_tags.clone();
+        changed_tags.0.extend(&new_tags.0);
+        let changed_names = [old_name.to_string(), new_name.clone()];
+
+        let source_file = self.files.remove_by_inode(&source_inode)
+            .expect("To have just found the file by its name and tags.");
+        let target_file = target_inode.map(|inode| self.files.remove_by_inode(&inode)
+            .expect("To have just found the file by its name and tags."));
+        let mut moved_files = vec![TfsFile {
+            name: new_name,
+            tags: new_tags,
+            ..source_file.clone()
+        }];
+        if let (RenameMode::Exchange, Some(target_file)) = (rename_mode, &target_file) {
+            moved_files.push(TfsFile {
+                name: old_name.to_string(),
+                tags: old_tags.clone(),
+                ..target_file.clone()
+            });
+        }
+
+        let mut added_inodes = vec![];
+        let mut e = Ok(());
+        for moved_file in moved_files {
+            e = self.check_if_file_is_valid(&moved_file)
+                .and_then(|_| moved_file.tags.0.iter()
+                    .try_for_each(|inode| self.check_if_tag_is_valid(inode)));
+            if e.is_err() {
+                break;
+            }
+            added_inodes.push(moved_file.inode);
+            self.files.add(moved_file)
+                .expect("To have just freed the name and tags, and checked the file.");
+        }
+        if e.is_err() {
+            for added_inode in added_inodes {
+                self.files.remove_by_inode(&added_inode);
+            }
+            for original_file in [Some(source_file), target_file].into_iter().flatten() {
+                self.files.add(original_file)
+                    .expect("To have nothing take up the original names and tags in \
+                        the meanwhile.");
+            }
+            return e;
+        }
+
+        // Tags might have stopped being neighbours of namespaces the file was under.
+        self.invalidate_names(changed_names.iter()
+            .map(String::as_str)
+            .chain(self.get_tag_names(&changed_tags)));
+        self.invalidate(Invalidation::Inode(source_inode.get_id()));
+        if let Some(target_inode) = target_inode {
+            self.invalidate(Invalidation::Inode(target_inode.get_id()));
         }
 
+        if let (RenameMode::Replace, Some(replaced_file)) = (rename_mode, target_file) {
+            self.storage.delete(&replaced_file.inode)?;
+            self.contents.remove_by_inode(&replaced_file.inode);
+            self.release_file_inode(&replaced_file.inode);
+        }
         Ok(())
     }
 
+    /// Adds tags to a file, keeping its inode and so its content. This is what
+    /// linking a file into another namespace does.
+    pub fn add_tags_to_file(&mut self, file_inode: &FileInode, to_add: &TagInodes)
+    -> ResultBtTfs<&TfsFile> {
+        let tfs_file = self.files.get_by_inode(file_inode)
+            .ok_or(TfsError::NotFound(format!("File with inode `{file_inode}` does not \
+                exist.")))?;
+        let mut new_tags = tfs_file.tags.clone();
+        new_tags.0.extend(&to_add.0);
+        if new_tags == tfs_file.tags {
+            Err(TfsError::AlreadyExists(format!("File `{}` already has tags `{to_add}`.",
+                tfs_file.name)))?;
+        }
+
+        let (old_tags, file_name) = (tfs_file.tags.clone(), tfs_file.name.clone());
+        self.move_file(&old_tags, &file_name, new_tags, file_name.clone(),
+            RenameMode::NoReplace)?;
+        Ok(self.files.get_by_inode(file_inode)
+            .expect("To have just moved the file."))
+    }
+
     // TODO: Make atomic, along with `delete_tag`.
-    pub fn rename_tag(&mut self, old_name: &str, new_name: String) -> ResultBtAny<()> {
-        let tag_inode = self.tags.get_by_name(old_name)
-            .ok_or(format!("Tag `{old_name}` does not exist"))?
-            .inode;
+    pub fn rename_tag(&mut self, old_name: &str, new_name: String) -> ResultBtTfs<()> {
+        let tag_inode = self.get_tag_by_name(old_name)?.inode;
+        if self.tags.get_by_name(&new_name).is_some_and(|tag| tag.inode != tag_inode) {
+            Err(TfsError::AlreadyExists(format!("Tag `{new_name}` already exists.")))?;
+        }
         self.tags.do_by_inode(&tag_inode, |mut tag| tag.try_set_name(new_name))
             .flatten()?;
         
@@ -409,67 +977,312 @@ where Storage: TfsStorage, Snapshots: TfsSnapshots {
             return e;
         }
 
+        let mut stale_names = vec![old_name.to_string()];
         let namespace_updates = self.namespaces.do_for_all(|namespace_update| {
             if namespace_update.tags.0.contains(&tag_inode) {
                 let namespace_string = Self::get_namespace_string_from_tags(
-                    &self.tags, namespace_update.tags)?;
-                *namespace_update.name = namespace_string;
+                    &self.tags, namespace_update.tags, namespace_update.predicates,
+                    namespace_update.file_predicates)?;
+                stale_names.push(std::mem::replace(namespace_update.name, namespace_string));
             }
             Ok::<_, WithBacktrace<AnyError>>(())
         });
-        collect_errors(namespace_updates)?;
+        collect_errors(namespace_updates.into_iter())?;
 
+        self.invalidate_names(stale_names.iter().map(String::as_str));
+        self.invalidate(Invalidation::Inode(tag_inode.get_id()));
         Ok(())
     }
 
-    pub fn insert_namespace(&mut self, namespace_string: String) -> ResultBtAny<NamespaceInode> {
-        let namespace_tags = parse_tags(&namespace_string);
+    /// Re-uses the namespace selecting the same things if there's one already, so
+    /// every spelling of a query resolves to the same inode. The namespace is named
+    /// by the query's normalized spelling.
+    pub fn insert_namespace(&mut self, namespace_string: String) -> ResultBtTfs<NamespaceInode> {
+        let namespace_query = self.parse_namespace_query(&namespace_string,
+            NamespaceQuery::new())?;
+        self.insert_namespace_query(namespace_query)
+    }
 
-        let mut _namespace_tags = TagInodes::new(); 
-        for namespace_tag in namespace_tags {
-            let namespace_tag = self.tags.get_by_name(namespace_tag)
-                .ok_or(format!("`{namespace_tag}` does not exist."))?;
+    /// Composes a query looked up under a namespace with that namespace's, so
+    /// `{ tag_1 }/{ tag_2 }` is `{ tag_1, tag_2 }`.
+    pub fn insert_relative_namespace(&mut self, parent_inode: &NamespaceInode,
+        namespace_string: String) -> ResultBtTfs<NamespaceInode>
+    {
+        let parent_namespace = self.get_namespace(parent_inode)?;
+        if parent_namespace.is_trash {
+            Err(TfsError::NotFound(String::from("Namespaces can't be nested in the \
+                trash.")))?;
+        }
+        let namespace_query = self.parse_namespace_query(&namespace_string,
+            parent_namespace.get_query())?;
+        self.insert_namespace_query(namespace_query)
+    }
 
-            _namespace_tags.0.insert(namespace_tag.inode);
+    /// Queries start from the one they're relative to, which is empty under the
+    /// root. Tokens prefixed with one of [`NamespaceQuery::REMOVAL_PREFIXES`] take
+    /// away from it instead of adding to it.
+    fn parse_namespace_query(&self, namespace_string: &str, relative_to: NamespaceQuery)
+    -> ResultBtTfs<NamespaceQuery> {
+        let query_tokens: Vec<_> = parse_tags(namespace_string).collect();
+        if query_tokens == [TRASH_NAME] {
+            let mut namespace_query = NamespaceQuery::new();
+            namespace_query.is_trash = true;
+            return Ok(namespace_query);
         }
 
-        self.namespaces.add(TfsNamespace::builder()
-            .name(namespace_string)
-            .inode(self.get_free_namespace_inode()?)
-            .tags(_namespace_tags)
-            .build())
+        let mut namespace_query = relative_to;
+        let mut removed_query = NamespaceQuery::new();
+        for query_token in query_tokens {
+            if query_token == NamespaceQuery::RELATIVE_TOKEN {
+                continue;
+            }
+            match query_token.strip_prefix(NamespaceQuery::REMOVAL_PREFIXES) {
+                Some(removed_token) => self.parse_query_token(namespace_string,
+                    removed_token, &mut removed_query)?,
+                None => self.parse_query_token(namespace_string, query_token,
+                    &mut namespace_query)?
+            }
+        }
+        Ok(namespace_query.without(&removed_query))
+    }
+
+    fn parse_query_token(&self, namespace_string: &str, query_token: &str,
+        namespace_query: &mut NamespaceQuery) -> ResultBtTfs<()>
+    {
+        if let Some(namespace_tag) = self.tags.get_by_name(query_token) {
+            namespace_query.tags.0.insert(namespace_tag.inode);
+        } else if let Some(file_predicate) = parse_file_predicate(query_token)
+            .map_err_inner(|e| NameError::new_invalid(namespace_string,
+                e.to_string().trim_end_matches('.')))?
+        {
+            namespace_query.file_predicates.push(file_predicate);
+        } else if let Some(tag_predicate) = parse_comparison(query_token) {
+            namespace_query.predicates.push(tag_predicate);
+        } else if self.tags.get_is_key(query_token) {
+            namespace_query.predicates.push(TagPredicate::new_present(query_token));
+        } else {
+            Err(TfsError::NotFound(format!("`{query_token}` does not exist.")))?;
+        }
+        Ok(())
+    }
+
+    pub fn insert_namespace_(&mut self, tag_inodes: TagInodes) -> ResultBtTfs<NamespaceInode> {
+        let mut namespace_query = NamespaceQuery::new();
+        namespace_query.tags = tag_inodes;
+        self.insert_namespace_query(namespace_query)
+    }
+
+    /// Tag directories behave as the namespace of their tag, e.g., `tag_1` as
+    /// `{ tag_1 }`.
+    pub fn insert_tag_namespace(&mut self, tag_inode: &TagInode) -> ResultBtTfs<NamespaceInode> {
+        let tag_inode = self.get_tag(tag_inode)?.inode;
+        self.insert_namespace_(TagInodes::from(tag_inode))
+    }
+
+    /// Namespace reached by going into a tag's directory from within a namespace,
+    /// e.g., `tag_2` under `{ tag_1 }` is `{ tag_1, tag_2 }`.
+    pub fn insert_nested_namespace(&mut self, parent_inode: &NamespaceInode,
+        tag_inode: &TagInode) -> ResultBtTfs<NamespaceInode>
+    {
+        let parent_namespace = self.get_namespace(parent_inode)?;
+        if parent_namespace.is_trash {
+            Err(TfsError::NotFound(String::from("Tags in the trash aren't directories.")))?;
+        }
+        let mut namespace_query = parent_namespace.get_query();
+        namespace_query.tags.0.insert(self.get_tag(tag_inode)?.inode);
+        self.insert_namespace_query(namespace_query)
     }
 
-    pub fn insert_namespace_(&mut self, tag_inodes: TagInodes) -> ResultBtAny<NamespaceInode> {
-        self.namespaces.add(TfsNamespace::builder()
-            .name(Self::get_namespace_string_from_tags(&self.tags, &tag_inodes)?)
+    fn insert_namespace_query(&mut self, namespace_query: NamespaceQuery)
+    -> ResultBtTfs<NamespaceInode> {
+        let namespace_query = namespace_query.into_canonical();
+        if let Some(existing_namespace) = self.namespaces.get_by_query(&namespace_query) {
+            return Ok(existing_namespace.inode);
+        }
+
+        let namespace_string = if namespace_query.is_trash {
+            format_tags([TRASH_NAME].into_iter())
+        } else {
+            Self::get_namespace_string_from_tags(&self.tags, &namespace_query.tags,
+                &namespace_query.predicates, &namespace_query.file_predicates)?
+        };
+        self.add_namespace(TfsNamespace::builder()
+            .name(namespace_string)
             .inode(self.get_free_namespace_inode()?)
-            .tags(tag_inodes)
+            .tags(namespace_query.tags)
+            .predicates(namespace_query.predicates)
+            .file_predicates(namespace_query.file_predicates)
+            .is_trash(namespace_query.is_trash)
             .build())
     }
     
     pub fn remove_file_by_name_and_tags<'a>(&mut self, file_name: &str,
         tag_inodes: impl Into<&'a TagInodes>)
-    -> ResultBtAny<TfsFile> {
+    -> ResultBtTfs<TfsFile> {
         let tag_inodes = tag_inodes.into();
         let removed_file = self.files.remove_by_name_and_tags(file_name, tag_inodes)
-            .ok_or(format!("No file matching name `{file_name}` and tag inodes \
-                `{tag_inodes}`."))?;
+            .ok_or(TfsError::NotFound(format!("No file matching name `{file_name}` \
+                and tag inodes `{tag_inodes}`.")))?;
         self.storage.delete(&removed_file.inode)?;
+        self.contents.remove_by_inode(&removed_file.inode);
+        self.release_file_inode(&removed_file.inode);
+        self.invalidate_names([file_name]);
         Ok(removed_file)
     }
 
+    /// Moves a file into the trash. It keeps its inode, and so its delegate content,
+    /// until it's purged.
+    pub fn trash_file_by_name_and_tags<'a>(&mut self, file_name: &str,
+        tag_inodes: impl Into<&'a TagInodes>)
+    -> ResultBtTfs<&TfsTrashed> {
+        let tag_inodes = tag_inodes.into();
+        let trashed_file = self.files.remove_by_name_and_tags(file_name, tag_inodes)
+            .ok_or(TfsError::NotFound(format!("No file matching name `{file_name}` \
+                and tag inodes `{tag_inodes}`.")))?;
+        self.contents.remove_by_inode(&trashed_file.inode);
+        self.invalidate_names([file_name]);
+        Ok(self.trash.add(TrashedItem::File(trashed_file), SystemTime::now()))
+    }
+
+    /// Whether any file, outside of the trash, is tagged with it.
+    pub fn get_is_tag_carried(&self, tag_inode: &TagInode) -> bool {
+        self.files.get_tag_sets()
+            .any(|tag_set| tag_set.0.contains(tag_inode))
+    }
+
+    /// Deletes a tag into the trash, remembering which files carried it.
+    pub fn trash_tag(&mut self, tag_name: &str) -> ResultBtTfs<&TfsTrashed> {
+        let tag_inode = self.get_tag_by_name(tag_name)?.inode;
+        let file_inodes = self.files.get_all()
+            .filter(|file| file.tags.0.contains(&tag_inode))
+            .map(|file| file.inode)
+            .collect();
+        let trashed_tag = self.delete_tag(tag_name)?;
+        Ok(self.trash.add(TrashedItem::Tag(trashed_tag, file_inodes), SystemTime::now()))
+    }
+
+    /// Takes an item out of the trash under `new_name`. Files get `new_tags`, or
+    /// otherwise whichever of their original tags still exist. Tags are put back
+    /// on the files that carried them, if those haven't been deleted since.
+    pub fn restore_trashed(&mut self, trash_name: &str, new_name: String,
+        new_tags: Option<TagInodes>) -> ResultBtTfs<()>
+    {
+        let trashed = self.get_trashed(trash_name)?;
+        match &trashed.item {
+            TrashedItem::File(trashed_file) => {
+                let mut restored_file = trashed_file.clone();
+                restored_file.name = new_name;
+                restored_file.tags = new_tags.unwrap_or(trashed_file.tags.0.iter()
+                    .filter(|inode| self.tags.get_by_inode(inode).is_some())
+                    .copied()
+                    .into());
+                self.check_tags_exist(&restored_file.tags)?;
+                self.check_if_file_is_valid(&restored_file)?;
+
+                self.trash.remove_by_name(trash_name);
+                self.contents.mark_stale(restored_file.inode);
+                self.files.add(restored_file)?;
+            },
+            TrashedItem::Tag(trashed_tag, file_inodes) => {
+                if new_tags.is_some() {
+                    Err(TfsError::CrossDevice(format!("Tag `{trash_name}` can only be \
+                        restored to the root.")))?;
+                }
+                let mut restored_tag = trashed_tag.clone();
+                restored_tag.name = new_name;
+                self.check_if_tag_is_valid_(&restored_tag)?;
+
+                let file_inodes = file_inodes.clone();
+                self.trash.remove_by_name(trash_name);
+                let tag_inode = self.tags.add(restored_tag)?.inode;
+                for file_inode in file_inodes {
+                    let Some(tfs_file) = self.files.get_by_inode(&file_inode) else {
+                        continue;
+                    };
+                    let mut file_tags = tfs_file.tags.clone();
+                    file_tags.0.insert(tag_inode);
+                    let did_retag = self.files.do_by_inode(&file_inode,
+                        |mut file| file.try_set_tags(file_tags))
+                        .flatten();
+                    if let Err(e) = did_retag {
+                        warn!("Could not put restored tag back on file inode \
+                            `{file_inode}`. {e:?}");
+                    }
+                }
+                self.invalidate_root_link_count();
+            }
+        }
+        Ok(())
+    }
+
+    /// Permanently deletes an item in the trash, along with any delegate content.
+    pub fn purge_trashed(&mut self, trash_name: &str) -> ResultBtTfs<TfsTrashed> {
+        let purged = self.trash.remove_by_name(trash_name)
+            .ok_or(TfsError::NotFound(format!("`{trash_name}` is not in the trash.")))?;
+        self.release_purged(&purged)?;
+        Ok(purged)
+    }
+
+    /// Frees what a purged item was holding onto, i.e., its inode and content.
+    fn release_purged(&mut self, purged: &TfsTrashed) -> ResultBtTfs<()> {
+        match &purged.item {
+            TrashedItem::File(purged_file) => {
+                self.storage.delete(&purged_file.inode)?;
+                self.release_file_inode(&purged_file.inode);
+            },
+            TrashedItem::Tag(purged_tag, _) => {
+                self.release_tag_inode(&purged_tag.inode);
+            }
+        }
+        Ok(())
+    }
+
+    /// Inodes are reused once released, so nothing left in the trash can still refer
+    /// to them.
+    fn release_file_inode(&mut self, file_inode: &FileInode) {
+        self.trash.forget_file(file_inode);
+        self.file_inodes.release(file_inode);
+    }
+
+    fn release_tag_inode(&mut self, tag_inode: &TagInode) {
+        self.trash.forget_tag(tag_inode);
+        self.tag_inodes.release(tag_inode);
+    }
+
+    pub fn empty_trash(&mut self) -> ResultBtTfs<()> {
+        let trash_names: Vec<_> = self.trash.get_all()
+            .map(|trashed| trashed.name.clone())
+            .collect();
+        let purges: Vec<_> = trash_names.iter()
+            .map(|trash_name| self.purge_trashed(trash_name))
+            .collect();
+        Ok(collect_errors(purges.into_iter())?)
+    }
+
+    pub fn purge_expired_trash(&mut self) -> ResultBtTfs<()> {
+        let expired = self.trash.remove_expired(self.trash_retention, SystemTime::now());
+        let purges: Vec<_> = expired.iter()
+            .map(|expired| self.release_purged(expired))
+            .collect();
+        Ok(collect_errors(purges.into_iter())?)
+    }
+
     #[instrument(skip_all, fields(?tag_name))]
-    pub fn delete_tag(&mut self, tag_name: &str) -> ResultBtAny<TfsTag> {
+    pub fn delete_tag(&mut self, tag_name: &str) -> ResultBtTfs<TfsTag> {
         let removed_tag = self.tags.remove_by_name(&tag_name)
-            .ok_or(format!("Tag `{tag_name}` does not exist."))?;
+            .ok_or(TfsError::NotFound(format!("Tag `{tag_name}` does not exist.")))?;
         let tag_sets: Vec<_> = self.files.get_tag_sets()
             .cloned()
             .collect();
+        let mut stale_names = vec![removed_tag.name.clone()];
         for tag_set in tag_sets {
             if !tag_set.0.contains(&removed_tag.inode) {
                 continue;
             }
+            stale_names.extend(self.files.get_by_tags(&tag_set)
+                .map(|file| file.name.clone()));
+            stale_names.extend(self.get_tag_names(&tag_set)
+                .map(str::to_string));
             
             self.files.do_by_tags(&tag_set, |target_files| {
                 *target_files = target_files.drain()
@@ -488,13 +1301,17 @@ where Storage: TfsStorage, Snapshots: TfsSnapshots {
         let namespace_updates = self.namespaces.do_for_all(|namespace_update| {
             if namespace_update.tags.0.remove(&removed_tag.inode) {
                 let namespace_string = Self::get_namespace_string_from_tags(
-                    &self.tags, namespace_update.tags)?;
-                *namespace_update.name = namespace_string;
+                    &self.tags, namespace_update.tags, namespace_update.predicates,
+                    namespace_update.file_predicates)?;
+                stale_names.push(std::mem::replace(namespace_update.name, namespace_string));
             }
             Ok::<_, WithBacktrace<AnyError>>(())
         });
-        collect_errors(namespace_updates)?;
+        collect_errors(namespace_updates.into_iter())?;
 
+        self.invalidate_names(stale_names.iter().map(String::as_str));
+        self.invalidate(Invalidation::Inode(removed_tag.inode.get_id()));
+        self.invalidate_root_link_count();
         Ok(removed_tag)
     }
 }
@@ -517,9 +1334,20 @@ impl TagFilesystem<StubStorage, StubSnapshots> {
             files: IndexedFiles::new(),
             tags: IndexedTags::new(),
             namespaces: IndexedNamepsaces::new(),
+            contents: IndexedContents::new(),
+            trash: IndexedTrash::new(),
+            file_inodes: InodeAllocator::new(),
+            tag_inodes: InodeAllocator::new(),
+            namespace_inodes: InodeAllocator::new(),
+            lookups: HashMap::new(),
             storage: StubStorage,
             snapshots: StubSnapshots,
             journal: TfsJournal::new(),
+            invalidations: None,
+            caching: CachingPolicy::default(),
+            trash_retention: IndexedTrash::DEFAULT_RETENTION,
+            is_read_only: false,
+            failures: FailureLog::default()
         }
     }
 }
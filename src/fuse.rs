@@ -2,18 +2,21 @@ use std::{ffi::OsStr, fmt::Display, thread::sleep, time::{Duration, SystemTime}}
 
 use bon::Builder;
 use derive_more::Error;
-use fuser::{FileAttr, FileType, Filesystem, ReplyAttr, ReplyCreate,
-    ReplyData, ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyWrite, Request,
-    TimeOrNow, FUSE_ROOT_ID};
-use libc::{c_int, EINVAL, ENOENT};
+use fuser::{consts::{FOPEN_DIRECT_IO, FOPEN_KEEP_CACHE, FUSE_DO_READDIRPLUS,
+    FUSE_WRITEBACK_CACHE},
+    FileAttr, FileType, Filesystem, KernelConfig, ReplyAttr, ReplyCreate, ReplyData,
+    ReplyDirectory, ReplyDirectoryPlus, ReplyEmpty, ReplyEntry, ReplyOpen, ReplyStatfs,
+    ReplyWrite, Request, TimeOrNow, FUSE_ROOT_ID};
+use libc::{c_int, RENAME_EXCHANGE, RENAME_NOREPLACE};
 use tracing::{debug, error, info, instrument, trace, warn, Level};
 
-use crate::{entries::TfsEntry, errors::{ResultBt, StringExt},
-    files::TfsFile, filesystem::TagFilesystem,
-    inodes::{get_is_inode_root, FileInode,
-    NamespaceInode, TagInode, TagInodes}, namespaces, storage::TfsStorage,
-    tags::TfsTag, ttl::{ANY_TTL, NO_TTL}, ResultExt,
-    ResultExt2};
+use crate::{caching::NO_TTL, entries::TfsEntry, errors::{ResultBt, StringExt, TfsError},
+    failures::{Failure, LAST_ERROR_NAME, TFS_DIRECTORY_NAME}, files::TfsFile,
+    filesystem::{RenameMode, TagFilesystem},
+    inodes::{get_is_inode_root, get_is_inode_virtual, FileInode, NamespaceInode, TagInode,
+    TagInodes, LAST_ERROR_INODE, TFS_DIRECTORY_INODE},
+    names::{NameError, MAX_NAME_LENGTH, RESERVED_NAMES},
+    storage::TfsStorage, tags::TfsTag, ResultExt, ResultExt2};
 
 macro_rules! event_ {
     ($tracing_level: expr, $error_message: expr, $($message_arguments: expr), *) => {{
@@ -33,7 +36,15 @@ macro_rules! handle_error_reply {
             event_!($error_reply.level, "{}", $error_reply.message);
             $fuser_reply.error($error_reply.code);
         }
-    }
+    };
+    // Also kept for `.tfs/last_error`, as the kernel only passes on the errno.
+    ($self: ident, $request: ident, $operation: literal, $fuser_reply: ident,
+        $error_reply: ident) => {
+        {
+            $self.record_failure($request, $operation, &$error_reply);
+            handle_error_reply!($fuser_reply, $error_reply)
+        }
+    };
 }
 
 // TODO: Some reply.error should not really log as an error.
@@ -41,16 +52,28 @@ macro_rules! handle_error_reply {
 // TODO: Sometimes the below error for `ct tag_2` when tag2 does exist.
 // Error: Os { code: 2, kind: NotFound, message: "No such file or directory" }
 // 
-// TODO: Should have 2 (or infinite) depth query sets? Cause like { tag_1, tag_2 }/file_1 
-// want to add tag_3, how to do with good ui
-// cwd at { tag_1, tag_2 }, mv file_1 ./{ tag_3 }
-// and want to remove a tag, mv file_1 ./{ ~tag_2 }
-//
 // TODO: Check they reply errors are the most suitable ones.
-// TODO: Errors need to be displayed to the user not just logged.
 // TODO: What does TTL, generation, fh, flags do?
 // TODO: Make some of the FUSE ops atomic
 impl<Storage: TfsStorage> Filesystem for TagFilesystem<Storage> {
+    #[instrument(skip_all)]
+    fn init(&mut self, _request: &Request<'_>, kernel_config: &mut KernelConfig)
+        -> Result<(), c_int>
+    {
+        let caching = self.get_caching();
+        let capabilities = [
+            (caching.writeback_cache, FUSE_WRITEBACK_CACHE, "writeback caching"),
+            (caching.readdir_plus, FUSE_DO_READDIRPLUS, "readdir-plus")
+        ];
+        for (is_wanted, capability, capability_name) in capabilities {
+            if is_wanted && kernel_config.add_capabilities(capability).is_err() {
+                warn!("Kernel doesn't support {capability_name}, going without it.");
+            }
+        }
+        info!("Initialised with `{caching:?}`.");
+        Ok(())
+    }
+
     #[instrument(skip_all, fields(?parent_inode, ?file_name))]
     fn create(&mut self, request: &Request<'_>, parent_inode: u64,
         file_name: &OsStr, _mode: u32, _umask: u32, _flags: i32,
@@ -58,11 +81,12 @@ impl<Storage: TfsStorage> Filesystem for TagFilesystem<Storage> {
     {
         match self.create_inner(request, parent_inode, file_name, _mode, _umask, _flags) {
             Ok(_reply) => {
+                self.remember_lookup(_reply.attr.ino);
                 reply.created(&_reply.ttl, &_reply.attr, _reply.generation, _reply.fh,
                     _reply.flags);
                 info!("Created file.");
             },
-            Err(_reply) => handle_error_reply!(reply, _reply)
+            Err(_reply) => handle_error_reply!(self, request, "create", reply, _reply)
         }
     }
 
@@ -72,10 +96,11 @@ impl<Storage: TfsStorage> Filesystem for TagFilesystem<Storage> {
     {
         match self.mkdir_inner(request, parent_inode, tag_name, _mode, _umask) {
             Ok(_reply) => {
+                self.remember_lookup(_reply.attr.ino);
                 reply.entry(&_reply.ttl, &_reply.attr, _reply.generation);
                 info!("Created tag.");
             },
-            Err(_reply) => handle_error_reply!(reply, _reply)
+            Err(_reply) => handle_error_reply!(self, request, "mkdir", reply, _reply)
         }
     }
     
@@ -85,13 +110,21 @@ impl<Storage: TfsStorage> Filesystem for TagFilesystem<Storage> {
     {
         match self.lookup_inner(request, parent_inode, predicate) {
             Ok(_reply) => {
+                self.remember_lookup(_reply.attr.ino);
                 reply.entry(&_reply.ttl, &_reply.attr, _reply.generation);
                 info!(_reply.message);
             },
+            // Misses are routine, e.g., shells probing for files, so aren't kept.
             Err(_reply) => handle_error_reply!(reply, _reply)
         }
     }
 
+    #[instrument(skip_all, fields(?inode_id, ?lookup_count))]
+    fn forget(&mut self, _request: &Request<'_>, inode_id: u64, lookup_count: u64) {
+        self.forget_lookups(inode_id, lookup_count);
+        trace!("Forgot lookups.");
+    }
+
     #[instrument(skip_all, fields(?inode_id))]
     fn getattr(&mut self, _request: &Request<'_>, inode_id: u64,
         _file_handle: Option<u64>, reply: ReplyAttr)
@@ -105,71 +138,122 @@ impl<Storage: TfsStorage> Filesystem for TagFilesystem<Storage> {
         }
     }
 
-    // TODO: Should probably have to -f when deleting tags w/ files under them.
-    // in `/tmp/tfs/` doing `rmdir tag_1` vs `rmdir "{ tag_1 }"
     // TODO: Should allow listing of root or only allow {}?
     // TODO: Determine if pagination can probably be race cond. in multi user
     // TODO: remove _ prefix if used
     #[instrument(skip_all, fields(?inode_id))]
-    fn readdir(&mut self, _request: &Request, inode_id: u64, _file_handle: u64,
+    fn readdir(&mut self, request: &Request, inode_id: u64, _file_handle: u64,
         pagination_offset: i64, mut reply: ReplyDirectory)
     {
-        match self.readdir_inner(_request, inode_id, _file_handle, pagination_offset,
+        match self.readdir_inner(request, inode_id, _file_handle, pagination_offset,
             &mut reply)
         {
             Ok(message) => {
                 reply.ok();
                 info!(message);
             },
-            Err(_reply) => handle_error_reply!(reply, _reply)
+            Err(_reply) => handle_error_reply!(self, request, "readdir", reply, _reply)
         }
     }
 
+    #[instrument(skip_all, fields(?inode_id))]
+    fn readdirplus(&mut self, request: &Request<'_>, inode_id: u64, _file_handle: u64,
+        pagination_offset: i64, mut reply: ReplyDirectoryPlus)
+    {
+        match self.readdirplus_inner(request, inode_id, _file_handle, pagination_offset,
+            &mut reply)
+        {
+            Ok(message) => {
+                reply.ok();
+                info!(message);
+            },
+            Err(_reply) => handle_error_reply!(self, request, "readdirplus", reply, _reply)
+        }
+    }
+
+    #[instrument(skip_all, fields(?inode_id))]
+    fn statfs(&mut self, request: &Request<'_>, inode_id: u64, reply: ReplyStatfs) {
+        match self.statfs_inner(request, inode_id) {
+            Ok(_reply) => {
+                reply.statfs(_reply.blocks, _reply.bfree, _reply.bavail, _reply.files,
+                    _reply.ffree, _reply.bsize, _reply.namelen, _reply.frsize);
+                info!(_reply.message);
+            },
+            Err(_reply) => handle_error_reply!(self, request, "statfs", reply, _reply)
+        }
+    }
+
+    #[instrument(skip_all, fields(?inode_id))]
+    fn open(&mut self, _request: &Request<'_>, inode_id: u64, _flags: i32, reply: ReplyOpen) {
+        // Made up on every read, so it mustn't be cached, nor cut short at a stale size.
+        let open_flags = if inode_id == LAST_ERROR_INODE {
+            FOPEN_DIRECT_IO
+        } else {
+            self.get_open_flags()
+        };
+        reply.opened(ANY_FILE_HANDLE, open_flags);
+        trace!("Opened.");
+    }
+
     // TODO: Use rest of args, or at least understand them.
     #[instrument(skip_all, fields(?target_inode, ?start_position, ?read_amount))]
-    fn read(&mut self, _request: &Request<'_>, target_inode: u64, _file_handle: u64,
+    fn read(&mut self, request: &Request<'_>, target_inode: u64, _file_handle: u64,
         start_position: i64, read_amount: u32, flags: i32, _lock_owner: Option<u64>,
         reply: ReplyData)
     {
-        match self.read_inner(_request, target_inode, _file_handle,
+        match self.read_inner(request, target_inode, _file_handle,
             start_position, read_amount, flags, _lock_owner)
         {
             Ok(_reply) => {
                 reply.data(&_reply.data);
                 info!(_reply.message);
             },
-            Err(_reply) => handle_error_reply!(reply, _reply)
+            Err(_reply) => handle_error_reply!(self, request, "read", reply, _reply)
         }
     }
 
     #[instrument(skip_all, fields(?target_inode))]
-    fn fsyncdir(&mut self, _request: &Request<'_>, target_inode: u64,
+    fn fsyncdir(&mut self, request: &Request<'_>, target_inode: u64,
         _file_handle: u64, _datasync: bool, reply: ReplyEmpty)
     {
-        match self.fsyncdir_inner(_request, target_inode, _file_handle,
+        match self.fsyncdir_inner(request, target_inode, _file_handle,
             _datasync)
         {
             Ok(message) => {
                 reply.ok();
                 info!(message);
             },
-            Err(_reply) => handle_error_reply!(reply, _reply)
+            Err(_reply) => handle_error_reply!(self, request, "fsyncdir", reply, _reply)
         }
     }
 
     #[instrument(skip_all, fields(?previous_parent, ?previous_name, ?new_parent, ?new_name))]
-    fn rename(&mut self, _request: &Request<'_>, previous_parent: u64,
+    fn rename(&mut self, request: &Request<'_>, previous_parent: u64,
         previous_name: &OsStr, new_parent: u64, new_name: &OsStr, _flags: u32,
         reply: ReplyEmpty)
     {
-        match self.rename_inner(_request, previous_parent, previous_name,
+        match self.rename_inner(request, previous_parent, previous_name,
             new_parent, new_name, _flags)
         {
             Ok(message) => {
                 reply.ok();
                 info!(message);
             },
-            Err(_reply) => handle_error_reply!(reply, _reply)
+            Err(_reply) => handle_error_reply!(self, request, "rename", reply, _reply)
+        }
+    }
+
+    #[instrument(skip_all, fields(?file_inode, ?new_parent, ?new_name))]
+    fn link(&mut self, request: &Request<'_>, file_inode: u64, new_parent: u64,
+        new_name: &OsStr, reply: ReplyEntry)
+    {
+        match self.link_inner(request, file_inode, new_parent, new_name) {
+            Ok(_reply) => {
+                self.remember_lookup(_reply.attr.ino);
+                reply.entry(&_reply.ttl, &_reply.attr, _reply.generation);
+                info!(_reply.message);
+            },
+            Err(_reply) => handle_error_reply!(self, request, "link", reply, _reply)
         }
     }
 
@@ -177,69 +261,106 @@ impl<Storage: TfsStorage> Filesystem for TagFilesystem<Storage> {
     // TODO: Does {e} get rendered?
     // TODO: set nowrap in nvim and reformat width of all codes
     #[instrument(skip_all, fields(?target_inode, ?start_position))]
-    fn write(&mut self, _request: &Request<'_>, target_inode: u64, _file_handle: u64,
+    fn write(&mut self, request: &Request<'_>, target_inode: u64, _file_handle: u64,
         start_position: i64, to_write: &[u8], write_flags: u32, flags: i32,
         _lock_owner: Option<u64>, reply: ReplyWrite)
     {
-        match self.write_inner(_request, target_inode, _file_handle, start_position,
+        match self.write_inner(request, target_inode, _file_handle, start_position,
             to_write, write_flags, flags, _lock_owner)
         {
             Ok(_reply) => {
                 reply.written(_reply.amount);
                 info!(_reply.message);
             },
-            Err(_reply) => handle_error_reply!(reply, _reply)
+            Err(_reply) => handle_error_reply!(self, request, "write", reply, _reply)
+        }
+    }
+
+    #[instrument(skip_all, fields(?source_inode, ?target_inode))]
+    fn copy_file_range(&mut self, request: &Request<'_>, source_inode: u64,
+        _source_handle: u64, source_position: i64, target_inode: u64, _target_handle: u64,
+        target_position: i64, length: u64, flags: u32, reply: ReplyWrite)
+    {
+        match self.copy_file_range_inner(request, source_inode, source_position,
+            target_inode, target_position, length, flags)
+        {
+            Ok(_reply) => {
+                reply.written(_reply.amount);
+                info!(_reply.message);
+            },
+            Err(_reply) => handle_error_reply!(self, request, "copy_file_range", reply,
+                _reply)
         }
     }
 
     #[instrument(skip_all, fields(?target_inode))]
-    fn setattr(&mut self, _request: &Request<'_>, target_inode: u64,
+    fn flush(&mut self, request: &Request<'_>, target_inode: u64, _file_handle: u64,
+        _lock_owner: u64, reply: ReplyEmpty)
+    {
+        match self.flush_inner(request, target_inode, _file_handle, _lock_owner) {
+            Ok(message) => {
+                reply.ok();
+                info!(message);
+            },
+            Err(_reply) => handle_error_reply!(self, request, "flush", reply, _reply)
+        }
+    }
+
+    #[instrument(skip_all, fields(?target_inode))]
+    fn setattr(&mut self, request: &Request<'_>, target_inode: u64,
         _mode: Option<u32>, uid: Option<u32>, gid: Option<u32>, size: Option<u64>,
         _atime: Option<TimeOrNow>, _mtime: Option<TimeOrNow>, _ctime: Option<SystemTime>,
         fh: Option<u64>, _crtime: Option<SystemTime>, _chgtime: Option<SystemTime>,
         _bkuptime: Option<SystemTime>, flags: Option<u32>, reply: ReplyAttr)
     {
-        match self.setattr_inner(_request, target_inode, _mode, uid, gid, size,
+        match self.setattr_inner(request, target_inode, _mode, uid, gid, size,
             _atime, _mtime, _ctime, fh, _crtime, _chgtime, _bkuptime, flags)
         {
             Ok(_reply) => {
                 reply.attr(&_reply.ttl, &_reply.attr);
                 info!(_reply.message);
             },
-            Err(_reply) => handle_error_reply!(reply, _reply)
+            Err(_reply) => handle_error_reply!(self, request, "setattr", reply, _reply)
         }
     }
 
     #[instrument(skip_all, fields(?parent_inode, ?file_name))]
-    fn unlink(&mut self, _request: &Request<'_>, parent_inode: u64, file_name: &OsStr,
+    fn unlink(&mut self, request: &Request<'_>, parent_inode: u64, file_name: &OsStr,
         reply: ReplyEmpty)
     {
-        match self.unlink_inner(_request, parent_inode, file_name) {
+        match self.unlink_inner(request, parent_inode, file_name) {
             Ok(message) => {
                 reply.ok();
                 info!(message);
             },
-            Err(_reply) => handle_error_reply!(reply, _reply)
+            Err(_reply) => handle_error_reply!(self, request, "unlink", reply, _reply)
         }
     }
 
     #[instrument(skip_all, fields(?parent_inode, ?tag_name))]
-    fn rmdir(&mut self, _request: &Request<'_>, parent_inode: u64, tag_name: &OsStr,
+    fn rmdir(&mut self, request: &Request<'_>, parent_inode: u64, tag_name: &OsStr,
         reply: ReplyEmpty)
     {
-        match self.rmdir_inner(_request, parent_inode, tag_name) {
+        match self.rmdir_inner(request, parent_inode, tag_name) {
             Ok(message) => {
                 reply.ok();
                 info!(message);
             },
-            Err(_reply) => handle_error_reply!(reply, _reply)
+            Err(_reply) => handle_error_reply!(self, request, "rmdir", reply, _reply)
         }
     }
 
     #[instrument(skip_all)]
     fn destroy(&mut self) {
+        if self.get_is_read_only() {
+            info!("Read-only, so not saving.");
+            return;
+        }
         let max_tries = 4;
         let initial_cooldown = 1;
+        if let Err(e) = self.refresh_contents() {
+            error!("Failed to refresh content index. {}", e.to_string_wbt());
+        }
         for try_index in 0..max_tries {
             if let Err(e) = self.save_persistently() {
                 error!("Failed to save TFS. {}", e.to_string_wbt());
@@ -281,14 +402,6 @@ impl Display for ErrorReply {
 //     }
 // }
 impl ErrorReply {
-    fn new(code: c_int, message: impl Into<String>) -> Self {
-        Self {
-            code,
-            level: Level::ERROR,
-            message: message.into()
-        }
-    }
-
     fn new_with_level(code: c_int, level: Level, message: impl Into<String>) -> Self {
         Self {
             code,
@@ -298,6 +411,19 @@ impl ErrorReply {
     }
 }
 
+impl From<TfsError> for ErrorReply {
+    fn from(tfs_error: TfsError) -> Self {
+        Self::new_with_level(tfs_error.get_errno(), tfs_error.get_level(),
+            tfs_error.to_string())
+    }
+}
+
+impl From<NameError> for ErrorReply {
+    fn from(name_error: NameError) -> Self {
+        TfsError::from(name_error).into()
+    }
+}
+
 struct CreateReply {
     ttl: Duration,
     attr: FileAttr,
@@ -312,6 +438,13 @@ struct MkdirReply {
     generation: u64,
 }
 
+struct LinkReply {
+    ttl: Duration,
+    attr: FileAttr,
+    generation: u64,
+    message: &'static str
+}
+
 struct LookupReply {
     ttl: Duration,
     attr: FileAttr,
@@ -341,60 +474,142 @@ struct SetattrReply {
     message: &'static str
 }
 
+struct StatfsReply {
+    blocks: u64,
+    bfree: u64,
+    bavail: u64,
+    files: u64,
+    ffree: u64,
+    bsize: u32,
+    namelen: u32,
+    frsize: u32,
+    message: &'static str
+}
+
 // TODO: create f! macro
 
 impl<Storage: TfsStorage> TagFilesystem<Storage> {
+    /// Tag directories are browsed as their tag's namespace, so directory operations
+    /// go through that namespace's inode instead.
+    fn get_directory_inode(&mut self, inode_id: u64) -> ResultBt<u64, ErrorReply> {
+        let Ok(tag_inode) = TagInode::try_from(inode_id) else {
+            return Ok(inode_id);
+        };
+        let namespace_inode = self.insert_tag_namespace(&tag_inode)
+            .map_err_inner(ErrorReply::from)?;
+        Ok(namespace_inode.get_id())
+    }
+
+    fn get_ttl(&self, inode_id: u64) -> Duration {
+        self.get_caching().ttls.get(inode_id)
+    }
+
+    fn get_open_flags(&self) -> u32 {
+        if self.get_caching().keep_cache { FOPEN_KEEP_CACHE } else { 0 }
+    }
+
+    fn record_failure(&mut self, request: &Request<'_>, operation: &'static str,
+        error_reply: &ErrorReply)
+    {
+        self.get_failures_mut().record(request.pid(), Failure {
+            when_failed: SystemTime::now(),
+            operation,
+            errno: error_reply.code,
+            message: error_reply.message.clone()
+        });
+    }
+
+    /// `.tfs` and `last_error` aren't in the snapshots, so aren't counted as links,
+    /// nor listed under the root, like `.trash`.
+    fn get_virtual_fuser(&self, inode_id: u64) -> FileAttr {
+        if inode_id == TFS_DIRECTORY_INODE {
+            return FileAttr {
+                ino: TFS_DIRECTORY_INODE,
+                perm: 0o555,
+                ..ROOT_ATTRIBUTES
+            };
+        }
+        FileAttr {
+            ino: LAST_ERROR_INODE,
+            size: u64::try_from(self.get_failures().render().len()).unwrap_or(u64::MAX),
+            kind: FileType::RegularFile,
+            perm: 0o444,
+            nlink: 1,
+            ..ROOT_ATTRIBUTES
+        }
+    }
+
+    fn check_if_writable(&self) -> ResultBt<(), ErrorReply> {
+        if self.get_is_read_only() {
+            Err(ErrorReply::from(TfsError::ReadOnly(String::from("Mount is read-only."))))?;
+        }
+        Ok(())
+    }
+
     fn create_inner(&mut self, request: &Request<'_>, parent_inode: u64,
         file_name: &OsStr, _mode: u32, _umask: u32, _flags: i32)
         -> ResultBt<CreateReply, ErrorReply>
     {
+        self.check_if_writable()?;
+        let parent_inode = self.get_directory_inode(parent_inode)?;
         if !get_is_inode_root(parent_inode) && !NamespaceInode::get_is_namespace(parent_inode) {
-            Err(ErrorReply::new(ENOENT, "Not child of TFS root nor a namespace."))?;
+            Err(ErrorReply::from(TfsError::NotFound(String::from("Not child of TFS root \
+                nor a namespace."))))?;
         }
 
         let new_file = TfsFile::builder()
             .name(file_name.to_string_lossy().clone())
             .inode(self.get_free_file_inode()
-                .map_err_inner(|e| ErrorReply::new(ENOENT, e.to_string()))?)
+                .map_err_inner(ErrorReply::from)?)
             .owner(request.uid())
             .group(request.gid());
 
         if get_is_inode_root(parent_inode) {
-            let new_file = self.add_file(new_file.build())
-                .map_err_inner(|e| ErrorReply::new(EINVAL, e.to_string()))?;
+            let new_file = self.add_file(new_file.build()
+                .map_err_inner(ErrorReply::from)?)
+                .map_err_inner(ErrorReply::from)?;
             // TODO: I swear this should not be needed : \
             let file_inode = new_file.inode;
             let fuser_attributes = self.get_file_fuser(&file_inode)
-                .map_err_inner(|e| ErrorReply::new(ENOENT, e.to_string()))?;
+                .map_err_inner(ErrorReply::from)?;
             return Ok(CreateReply {
-                ttl: ANY_TTL,
+                ttl: self.get_ttl(fuser_attributes.ino),
                 attr: fuser_attributes,
-                generation: ANY_GENERATION,
+                generation: self.get_generation(fuser_attributes.ino),
                 fh: ANY_FILE_HANDLE,
-                flags: ANY_FLAGS
+                flags: self.get_open_flags()
             });
         }
 
         let namespace_inode = NamespaceInode::try_from(parent_inode)
-            .map_err_inner(|e| ErrorReply::new(ENOENT, e.to_string()))?;
+            .map_err_inner(|e| ErrorReply::from(TfsError::NotFound(e.to_string())))?;
         let tfs_namespace = self.get_namespaces()
             .get_map()
             .get(&namespace_inode)
-            .ok_or(ErrorReply::new(ENOENT, format!("Namespace with id \
-                `{namespace_inode}` does not exist.")))?;
+            .ok_or(ErrorReply::from(TfsError::NotFound(format!("Namespace with id \
+                `{namespace_inode}` does not exist."))))?;
+        if tfs_namespace.is_trash {
+            Err(ErrorReply::from(TfsError::PermissionDenied(String::from("Files can't be \
+                created in the trash."))))?;
+        }
+        if !tfs_namespace.get_is_exact() {
+            Err(ErrorReply::from(TfsError::PermissionDenied(format!("Namespace `{}` has \
+                predicates, files can't be created under it.", tfs_namespace.name))))?;
+        }
 
-        let new_file = self.add_file(new_file.tags(tfs_namespace.tags.clone()).build())
-            .map_err_inner(|e| ErrorReply::new(ENOENT, e.to_string()))?;
+        let new_file = self.add_file(new_file.tags(tfs_namespace.tags.clone())
+            .build()
+            .map_err_inner(ErrorReply::from)?)
+            .map_err_inner(ErrorReply::from)?;
         let file_inode = new_file.inode;
         let fuser_attributes = self.get_file_fuser(&file_inode)
-            // TODO: More appropriate error code.
-            .map_err_inner(|e| ErrorReply::new(ENOENT, e.to_string()))?;
+            .map_err_inner(ErrorReply::from)?;
         Ok(CreateReply {
-            ttl: ANY_TTL,
+            ttl: self.get_ttl(fuser_attributes.ino),
             attr: fuser_attributes,
-            generation: ANY_GENERATION,
+            generation: self.get_generation(fuser_attributes.ino),
             fh: ANY_FILE_HANDLE,
-            flags: ANY_FLAGS
+            flags: self.get_open_flags()
         })
     }
 
@@ -402,55 +617,66 @@ impl<Storage: TfsStorage> TagFilesystem<Storage> {
         tag_name: &OsStr, _mode: u32, _umask: u32)
         -> ResultBt<MkdirReply, ErrorReply>
     {
+        self.check_if_writable()?;
         let tag_name = tag_name.to_string_lossy();
 
         // TODO: Maybe accept mkdir everywhere, just always create at global.
         if !get_is_inode_root(parent_inode) {
-            Err(ErrorReply::new(ENOENT, "Needs to be under the root directory."))?;
-        }
-
-        let is_file_conflicting = self.get_files()
-            .get_by_name_and_tags(&tag_name, &TagInodes::new())
-            .is_some();
-        if is_file_conflicting {
-            Err(ErrorReply::new(EINVAL, format!("Untagged file already exists with \
-                name `{tag_name}`.")))?;
+            Err(ErrorReply::from(TfsError::PermissionDenied(String::from("Tags need to be \
+                created under the root directory."))))?;
         }
 
         let new_tag = self.add_tag(TfsTag::builder()
             .name(tag_name)
             .inode(self.get_free_tag_inode()
-                .map_err_inner(|e| ErrorReply::new(
-                    EINVAL, format!("No free tag inode. {}", e.to_string())))?)
+                .map_err_inner(ErrorReply::from)?)
             .owner(request.uid())
             .group(request.gid())
-            .build())
-            .map_err_inner(|e| ErrorReply::new(ENOENT, e.to_string()))?;
+            .build()
+            .map_err_inner(ErrorReply::from)?)
+            .map_err_inner(ErrorReply::from)?;
         let tag_inode = new_tag.inode;
         let fuser_attributes = self.get_tag_fuser(&tag_inode)
-            .map_err_inner(|e| ErrorReply::new(ENOENT, e.to_string()))?;
+            .map_err_inner(ErrorReply::from)?;
         Ok(MkdirReply {
-            ttl: ANY_TTL,
+            ttl: self.get_ttl(fuser_attributes.ino),
             attr: fuser_attributes,
-            generation: ANY_GENERATION
+            generation: self.get_generation(fuser_attributes.ino)
         })
     }
 
     fn lookup_inner(&mut self, _: &Request, parent_inode: u64,
         predicate: &OsStr) -> ResultBt<LookupReply, ErrorReply>
     {
+        let parent_inode = self.get_directory_inode(parent_inode)?;
         // TODO: Is there not just a method that returns String instead of Cow?
         let predicate = predicate.to_string_lossy().to_string();
 
+        let virtual_inode = match (parent_inode, predicate.as_str()) {
+            (FUSE_ROOT_ID, TFS_DIRECTORY_NAME) => Some(TFS_DIRECTORY_INODE),
+            (TFS_DIRECTORY_INODE, LAST_ERROR_NAME) => Some(LAST_ERROR_INODE),
+            (TFS_DIRECTORY_INODE, _) => Err(ErrorReply::from(TfsError::NotFound(format!(
+                "`{predicate}` isn't under `{TFS_DIRECTORY_NAME}`."))))?,
+            _ => None
+        };
+        if let Some(virtual_inode) = virtual_inode {
+            return Ok(LookupReply {
+                ttl: NO_TTL,
+                attr: self.get_virtual_fuser(virtual_inode),
+                generation: self.get_generation(virtual_inode),
+                message: format!("Completed lookup under `{TFS_DIRECTORY_NAME}`.")
+            });
+        }
+
         if get_is_inode_root(parent_inode) {
             if get_is_a_namespace(&predicate) {
                 let namespace_inode = self.insert_namespace(predicate)
-                    .map_err_inner(|e| ErrorReply::new(ENOENT,
-                        format!("Namespace lookup failed. {}", e.to_string())))?;
+                    .map_err_inner(ErrorReply::from)?;
                 return Ok(LookupReply {
-                    ttl: NO_TTL,
-                    attr: namespaces::get_fuse_attributes(&namespace_inode),
-                    generation: ANY_GENERATION,
+                    ttl: self.get_ttl(namespace_inode.get_id()),
+                    attr: self.get_namespace_fuser(&namespace_inode)
+                        .map_err_inner(ErrorReply::from)?,
+                    generation: self.get_generation(namespace_inode.get_id()),
                     message: String::from("Completed namespace lookup.")
                 });
             }
@@ -462,101 +688,168 @@ impl<Storage: TfsStorage> TagFilesystem<Storage> {
                 .or(self.get_files()
                     .get_by_name_and_tags(&predicate, &TagInodes::new())
                     .map(|file| file.inode.get_id()))
-                .ok_or(ErrorReply::new_with_level(ENOENT, Level::INFO, format!(
-                    "Tag/file lookup failed, `{predicate}` is not a tag nor an \
-                    untagged file.")))?;
+                .ok_or(ErrorReply::from(TfsError::NotFound(format!("Tag/file lookup \
+                    failed, `{predicate}` is not a tag nor an untagged file."))))?;
 
             let fuser_attributes = self.get_fuser_attributes(target_inode)
-                .map_err_inner(|e| ErrorReply::new(ENOENT, e.to_string()))?;
+                .map_err_inner(ErrorReply::from)?;
             // TODO: See if setting query to None after this is appropriate.
             return Ok(LookupReply {
-                ttl: ANY_TTL,
+                ttl: self.get_ttl(fuser_attributes.ino),
                 attr: fuser_attributes,
-                generation: ANY_GENERATION,
+                generation: self.get_generation(fuser_attributes.ino),
                 message: String::from("Completed tag/file lookup under root.")
             });
         }
 
-        // TODO: Need to add checks as to what file and tag names can be created
-        // to make them not conflict, no dupes when ls'ing
         if let Ok(parent_namespace) = self.get_namespaces().get_by_inode_id(parent_inode) {
-            // TODO: Can this be put in function with twin-ish
-            let fuser = self.get_inrange_tags(parent_namespace)
-                .and_then(|tags| tags.into_iter()
-                    .find(|tag| tag.name == predicate)
-                    .ok_or(format!("No matching neighbour tag w/ name `{}` \
-                        for namespace `{}`.", predicate, parent_namespace.inode).into()))
-                .and_then(|tag| self.get_tag_fuser(&tag.inode))
-                .or(self.get_file_by_name_and_namespace_inode(&predicate, &parent_namespace.inode)
-                    .and_then(|file| self.get_file_fuser(&file.inode)))
-                .map_err_inner(|e| ErrorReply::new(ENOENT, format!("Tag/file \
-                    lookup failed, `{predicate}` is not a tag not a file under \
-                    inode `{parent_inode}`. {}", e.to_string())))?;
+            if parent_namespace.is_trash {
+                let fuser = self.get_trashed_fuser(&predicate)
+                    .map_err_inner(ErrorReply::from)?;
+                return Ok(LookupReply {
+                    ttl: NO_TTL,
+                    attr: fuser,
+                    generation: self.get_generation(fuser.ino),
+                    message: String::from("Completed lookup in trash.")
+                });
+            }
+
+            let parent_namespace_inode = parent_namespace.inode;
+            // Files come and go under these as time passes, without any change to tell
+            // the kernel about.
+            let is_time_based = parent_namespace.get_is_time_based();
+            if get_is_a_namespace(&predicate) {
+                let namespace_inode = self.insert_relative_namespace(&parent_namespace_inode,
+                    predicate)
+                    .map_err_inner(ErrorReply::from)?;
+                return Ok(LookupReply {
+                    ttl: self.get_ttl(namespace_inode.get_id()),
+                    attr: self.get_namespace_fuser(&namespace_inode)
+                        .map_err_inner(ErrorReply::from)?,
+                    generation: self.get_generation(namespace_inode.get_id()),
+                    message: String::from("Completed relative namespace lookup.")
+                });
+            }
+
+            let parent_tags = parent_namespace.tags.clone();
+            let inrange_tag_inode = self.get_namespace_inrange_tags(parent_namespace)
+                .map_err_inner(ErrorReply::from)?
+                .into_iter()
+                .find(|tag| tag.name == predicate)
+                .map(|tag| tag.inode);
+            // A namespace can't contain itself, so its own tags stay tag directories.
+            if let Some(tag_inode) = inrange_tag_inode
+                && parent_tags.0.contains(&tag_inode)
+            {
+                let fuser = self.get_tag_fuser(&tag_inode)
+                    .map_err_inner(ErrorReply::from)?;
+                return Ok(LookupReply {
+                    ttl: self.get_ttl(fuser.ino),
+                    attr: fuser,
+                    generation: self.get_generation(fuser.ino),
+                    message: String::from("Completed tag lookup under namespace.")
+                });
+            }
+            if let Some(tag_inode) = inrange_tag_inode {
+                let namespace_inode = self.insert_nested_namespace(&parent_namespace_inode,
+                    &tag_inode)
+                    .map_err_inner(ErrorReply::from)?;
+                return Ok(LookupReply {
+                    ttl: self.get_ttl(namespace_inode.get_id()),
+                    attr: self.get_namespace_fuser(&namespace_inode)
+                        .map_err_inner(ErrorReply::from)?,
+                    generation: self.get_generation(namespace_inode.get_id()),
+                    message: String::from("Completed nested namespace lookup.")
+                });
+            }
+
+            let fuser = self.get_file_by_name_and_namespace_inode(&predicate,
+                &parent_namespace_inode)
+                .and_then(|file| self.get_file_fuser(&file.inode))
+                .map_err_inner(ErrorReply::from)?;
             // TODO: no magic variables.
             return Ok(LookupReply {
-                ttl: NO_TTL,
+                ttl: if is_time_based { NO_TTL } else { self.get_ttl(fuser.ino) },
                 attr: fuser,
-                generation: ANY_GENERATION,
-                message: String::from("Completed tag/file lookup under namespace.")
+                generation: self.get_generation(fuser.ino),
+                message: String::from("Completed file lookup under namespace.")
             });
         } 
 
-        Err(ErrorReply::new(ENOENT, format!("No tags or files matching \
-            `{predicate}` under root and/or namespace, `{parent_inode}`.")))?
+        Err(ErrorReply::from(TfsError::NotFound(format!("No tags or files matching \
+            `{predicate}` under root and/or namespace, `{parent_inode}`."))))?
     }
 
     fn getattr_inner(&mut self, _request: &Request<'_>, inode_id: u64,
         _file_handle: Option<u64>) -> ResultBt<GetattrReply, ErrorReply>
     {
-        // TODO: See impact of NO_TTL
         // TODO: Use get_is_inode_root
         if get_is_inode_root(inode_id) {
             return Ok(GetattrReply {
-                ttl: NO_TTL,
-                attr: ROOT_ATTRIBUTES,
+                ttl: self.get_ttl(inode_id),
+                attr: FileAttr {
+                    nlink: self.get_root_link_count(),
+                    ..ROOT_ATTRIBUTES
+                },
                 message: "Replied w/ root."
             });
         }
+
+        if get_is_inode_virtual(inode_id) {
+            return Ok(GetattrReply {
+                ttl: NO_TTL,
+                attr: self.get_virtual_fuser(inode_id),
+                message: "Replied w/ `.tfs` entry."
+            });
+        }
         
         if let Ok(namespace_inode) = NamespaceInode::try_from(inode_id) {
             return Ok(GetattrReply {
-                ttl: NO_TTL,
-                attr: namespaces::get_fuse_attributes(&namespace_inode),
+                ttl: self.get_ttl(inode_id),
+                attr: self.get_namespace_fuser(&namespace_inode)
+                    .map_err_inner(ErrorReply::from)?,
                 message: "Replied w/ namespace."
             });
         }
 
-        if let Ok(fuser_attributes) = FileInode::try_from(inode_id)
-            .and_then(|inode| self.get_file_fuser(&inode))
-            .or(TagInode::try_from(inode_id)
-                .and_then(|inode| self.get_tag_fuser(&inode))) {
-                    return Ok(GetattrReply {
-                        ttl: ANY_TTL,
-                        attr: fuser_attributes,
-                        message: "Replied w/ file."
-                    });
-                }
+        let fuser_attributes = self.get_fuser_attributes(inode_id)
+            .map_err_inner(ErrorReply::from)?;
+        Ok(GetattrReply {
+            ttl: self.get_ttl(fuser_attributes.ino),
+            attr: fuser_attributes,
+            message: "Replied w/ file or tag."
+        })
+    }
 
-        Err(ErrorReply::new(ENOENT, "Did not match any inode."))?
+    /// What's listed under a directory, in order, as `(inode, kind, name)`.
+    fn get_directory_listing(&mut self, inode_id: u64)
+        -> ResultBt<Vec<(u64, FileType, String)>, ErrorReply>
+    {
+        // Namespaces are only ever reached from the root, as far as `..` goes.
+        let mut listing = vec![
+            (inode_id, FileType::Directory, String::from(".")),
+            (FUSE_ROOT_ID, FileType::Directory, String::from(".."))
+        ];
+        listing.extend(self.get_directory_entries(inode_id)?);
+        Ok(listing)
     }
 
-    fn readdir_inner(&mut self, _request: &Request, inode_id: u64, _file_handle: u64,
-        mut pagination_offset: i64, mut reply: &mut ReplyDirectory)
-        -> ResultBt<&'static str, ErrorReply>
+    fn get_directory_entries(&mut self, inode_id: u64)
+        -> ResultBt<Vec<(u64, FileType, String)>, ErrorReply>
     {
+        let inode_id = self.get_directory_inode(inode_id)?;
+        if inode_id == TFS_DIRECTORY_INODE {
+            return Ok(vec![(LAST_ERROR_INODE, FileType::RegularFile,
+                LAST_ERROR_NAME.to_string())]);
+        }
         let is_listing_root = get_is_inode_root(inode_id);
         if !is_listing_root && !NamespaceInode::get_is_namespace(inode_id) {
-            Err(ErrorReply::new(ENOENT, "Inode not root or a namespace."))?;
+            Err(ErrorReply::from(TfsError::NotFound(String::from("Inode not root or a \
+                namespace."))))?;
         }
 
-        let pagination_offset_: usize = pagination_offset.try_into().with_bt()
-                .map_err_inner(|e| ErrorReply::new(
-                    EINVAL, format!("Can't convert offset. {e}")))?;
-
-        let add = |tfs_entry: &dyn TfsEntry, pagination_offset: i64, reply: &mut ReplyDirectory| {
-            reply.add(tfs_entry.get_inode_id(), pagination_offset,
-                tfs_entry.get_file_kind(), tfs_entry.get_name())
-        };
+        let to_listed = |tfs_entry: &dyn TfsEntry| (tfs_entry.get_inode_id(),
+            tfs_entry.get_file_kind(), tfs_entry.get_name().to_string());
 
         if is_listing_root {
             let mut tagless_files: Vec<_> = self.get_files()
@@ -569,48 +862,135 @@ impl<Storage: TfsStorage> TagFilesystem<Storage> {
             all_tags.sort();
             let all_tags = all_tags.into_iter().map(|tag| tag as &dyn TfsEntry);
 
-            for tfs_entry in tagless_files.chain(all_tags).skip(pagination_offset_) {
-                pagination_offset += 1;
-                let had_space = add(tfs_entry, pagination_offset, &mut reply);
-                if !had_space {
-                    return Ok("Partially listed root.");
-                }
-            }
-
-            return Ok("Finished listing root.");
+            return Ok(tagless_files.chain(all_tags)
+                .map(to_listed)
+                .collect());
         }
 
         let current_namespace = self.get_namespaces().get_by_inode_id(inode_id)
-            .map_err_inner(|e| ErrorReply::new(EINVAL,  format!("Could not get \
-                namespace. {e}")))?;
+            .map_err_inner(|e| ErrorReply::from(TfsError::NotFound(format!("Could not \
+                get namespace. {e}"))))?;
+
+        if current_namespace.is_trash {
+            self.purge_expired_trash()
+                .map_err_inner(ErrorReply::from)?;
+            let mut all_trashed: Vec<_> = self.get_trash().get_all().collect();
+            all_trashed.sort_by(|a, b| a.name.cmp(&b.name));
+
+            return Ok(all_trashed.into_iter()
+                .map(|trashed| {
+                    let tfs_entry = trashed.get_entry();
+                    (tfs_entry.get_inode_id(), tfs_entry.get_file_kind(), trashed.name.clone())
+                })
+                .collect());
+        }
 
-        let mut inrange_tags = self.get_inrange_tags(current_namespace)
-            .map_err_inner(|e| ErrorReply::new(EINVAL, format!("Could not get \
-                inrange tags. {e}")))?;
+        let mut inrange_tags = self.get_namespace_inrange_tags(current_namespace)
+            .map_err_inner(ErrorReply::from)?;
         inrange_tags.sort();
         let inrange_tags = inrange_tags.into_iter()
             .map(|tag| tag as &dyn TfsEntry);
 
         let mut inscope_files: Vec<_> = self.get_files_by_namespace_inode(
             &current_namespace.inode)
-            .map_err_inner(|e| ErrorReply::new(
-                EINVAL, format!("Could not get files under namespace. {e}")))?
+            .map_err_inner(ErrorReply::from)?
             .collect();
         inscope_files.sort();
         let inscope_files = inscope_files.into_iter()
             .map(|file| file as &dyn TfsEntry);
 
-        for to_list in inscope_files.chain(inrange_tags)
-            .skip(pagination_offset_)
-        {
+        Ok(inscope_files.chain(inrange_tags)
+            .map(to_listed)
+            .collect())
+    }
+
+    fn readdir_inner(&mut self, _request: &Request, inode_id: u64, _file_handle: u64,
+        mut pagination_offset: i64, reply: &mut ReplyDirectory)
+        -> ResultBt<&'static str, ErrorReply>
+    {
+        let pagination_offset_: usize = pagination_offset.try_into().with_bt()
+                .map_err_inner(|e| ErrorReply::from(TfsError::InvalidArgument(
+                    format!("Can't convert offset. {e}"))))?;
+
+        let listing = self.get_directory_listing(inode_id)?;
+        for (listed_inode, file_kind, name) in listing.into_iter().skip(pagination_offset_) {
             pagination_offset += 1;
-            let had_space = add(to_list, pagination_offset, &mut reply);
-            if !had_space {
-                return Ok("Partially finished listing namespace.");
+            let is_full = reply.add(listed_inode, pagination_offset, file_kind, name);
+            if is_full {
+                return Ok("Partially listed directory.");
             }
         }
 
-        Ok("Finished finished listing namespace.")
+        Ok("Finished listing directory.")
+    }
+
+    /// Lists like `readdir_inner`, with each entry looked up the way `lookup_inner`
+    /// would, e.g., tags under a namespace being its nested namespaces.
+    fn readdirplus_inner(&mut self, request: &Request, inode_id: u64, _file_handle: u64,
+        mut pagination_offset: i64, reply: &mut ReplyDirectoryPlus)
+        -> ResultBt<&'static str, ErrorReply>
+    {
+        let pagination_offset_: usize = pagination_offset.try_into().with_bt()
+                .map_err_inner(|e| ErrorReply::from(TfsError::InvalidArgument(
+                    format!("Can't convert offset. {e}"))))?;
+
+        let listing = self.get_directory_listing(inode_id)?;
+        // Only counted once the whole reply is sent, the kernel drops it on an error.
+        let mut looked_up_inodes = Vec::new();
+        for (listed_inode, _, name) in listing.into_iter().skip(pagination_offset_) {
+            pagination_offset += 1;
+            // The kernel counts each listed entry as looked up, except for `.` and `..`.
+            let is_looked_up = !RESERVED_NAMES.contains(&name.as_str());
+            let (ttl, attr) = if is_looked_up {
+                let entry = self.lookup_inner(request, inode_id, OsStr::new(&name))?;
+                (entry.ttl, entry.attr)
+            } else {
+                let entry = self.getattr_inner(request, listed_inode, None)?;
+                (entry.ttl, entry.attr)
+            };
+            let is_full = reply.add(attr.ino, pagination_offset, &name, &ttl, &attr,
+                self.get_generation(attr.ino));
+            if is_full {
+                self.remember_lookups(looked_up_inodes);
+                return Ok("Partially listed directory plus.");
+            }
+            if is_looked_up {
+                looked_up_inodes.push(attr.ino);
+            }
+        }
+
+        self.remember_lookups(looked_up_inodes);
+        Ok("Finished listing directory plus.")
+    }
+
+    fn remember_lookups(&mut self, inode_ids: Vec<u64>) {
+        for inode_id in inode_ids {
+            self.remember_lookup(inode_id);
+        }
+    }
+
+    fn statfs_inner(&mut self, _request: &Request<'_>, _inode_id: u64)
+        -> ResultBt<StatfsReply, ErrorReply>
+    {
+        let storage_space = self.get_storage()
+            .get_space()
+            .map_err_inner(|e| ErrorReply::from(TfsError::from(e)))?;
+        // Trashed items hold onto their inodes too.
+        let used_inodes = self.get_files().get_all().count()
+            + self.get_tags().get_all().count()
+            + self.get_trash().get_all().count();
+        let used_inodes = u64::try_from(used_inodes).unwrap_or(u64::MAX);
+        Ok(StatfsReply {
+            blocks: storage_space.total_blocks,
+            bfree: storage_space.free_blocks,
+            bavail: storage_space.available_blocks,
+            files: used_inodes.saturating_add(storage_space.free_files),
+            ffree: storage_space.free_files,
+            bsize: storage_space.block_size,
+            namelen: MAX_NAME_LENGTH as u32,
+            frsize: storage_space.fragment_size,
+            message: "Replied w/ filesystem statistics."
+        })
     }
 
     fn read_inner(&mut self, _request: &Request<'_>, target_inode: u64, _file_handle: u64,
@@ -618,19 +998,30 @@ impl<Storage: TfsStorage> TagFilesystem<Storage> {
         -> ResultBt<DataReply, ErrorReply>
     {
         let file_inode: FileInode = target_inode.try_into()
-            .map_err_inner(|e| ErrorReply::new(
-                EINVAL, format!("Not a file inode. {e}")))?; 
+            .map_err_inner(|e| ErrorReply::from(TfsError::InvalidArgument(
+                format!("Not a file inode. {e}"))))?; 
         let start_position: u64 = start_position.try_into().with_bt()
-            .map_err_inner(|e| ErrorReply::new(
-                EINVAL, format!("Offset value can't be converted. {e}")))?; 
+            .map_err_inner(|e| ErrorReply::from(TfsError::InvalidArgument(
+                format!("Offset value can't be converted. {e}"))))?; 
         let read_amount: usize = read_amount.try_into().with_bt()
-            .map_err_inner(|e| ErrorReply::new(
-                EINVAL, format!("Amount to read can't be converted. {e}")))?;
+            .map_err_inner(|e| ErrorReply::from(TfsError::InvalidArgument(
+                format!("Amount to read can't be converted. {e}"))))?;
+
+        if target_inode == LAST_ERROR_INODE {
+            let rendered = self.get_failures().render().into_bytes();
+            let start_position = usize::try_from(start_position).unwrap_or(usize::MAX)
+                .min(rendered.len());
+            let end_position = start_position.saturating_add(read_amount)
+                .min(rendered.len());
+            return Ok(DataReply {
+                data: rendered[start_position..end_position].to_vec(),
+                message: "Read failures."
+            });
+        }
 
         let content_read = self.get_storage()
             .read(&file_inode, start_position, read_amount)
-            .map_err_inner(|e| ErrorReply::new(
-                EINVAL, format!("Failed to read file. {e}")))?;
+            .map_err_inner(|e| ErrorReply::from(TfsError::from(e)))?;
 
         Ok(DataReply {
             data: content_read,
@@ -642,67 +1033,180 @@ impl<Storage: TfsStorage> TagFilesystem<Storage> {
         _file_handle: u64, _datasync: bool) -> ResultBt<&'static str, ErrorReply>
     {
         let should_fsync_all = get_is_inode_root(target_inode); 
+        if should_fsync_all && self.get_is_read_only() {
+            return Ok("Read-only, so nothing to save.");
+        }
         if should_fsync_all {
+            self.refresh_contents()
+                .map_err_inner(ErrorReply::from)?;
             self.save_persistently()
-                .map_err_inner(|e| ErrorReply::new(
-                    EINVAL, format!("Failed to save TFS state. {e}")))?;
+                .map_err_inner(ErrorReply::from)?;
             return Ok("Saved all.");
         }
 
         // TODO
-        Err(ErrorReply::new(EINVAL, "Not implemented yet."))?
+        Err(ErrorReply::from(TfsError::InvalidArgument(String::from("Not implemented \
+            yet."))))?
     }
 
     fn rename_inner(&mut self, _request: &Request<'_>, previous_parent: u64,
-        previous_name: &OsStr, new_parent: u64, new_name: &OsStr, _flags: u32)
+        previous_name: &OsStr, new_parent: u64, new_name: &OsStr, flags: u32)
         -> ResultBt<&'static str, ErrorReply>
     {
+        self.check_if_writable()?;
+        let previous_parent = self.get_directory_inode(previous_parent)?;
+        let new_parent = self.get_directory_inode(new_parent)?;
         let previous_name = previous_name.to_string_lossy();
         let new_name = new_name.to_string_lossy().to_string();
+        let rename_mode = match flags {
+            0 => RenameMode::Replace,
+            RENAME_NOREPLACE => RenameMode::NoReplace,
+            RENAME_EXCHANGE => RenameMode::Exchange,
+            _ => Err(ErrorReply::from(TfsError::InvalidArgument(format!("Unsupported \
+                rename flags `{flags:#x}`."))))?
+        };
 
-        if get_is_inode_root(previous_parent) && get_is_inode_root(new_parent) {
+        if let Ok(trash_namespace) = self.get_namespaces().get_by_inode_id(previous_parent)
+            && trash_namespace.is_trash
+        {
+            let new_tags = match self.get_namespaces().get_by_inode_id(new_parent) {
+                _ if get_is_inode_root(new_parent) => None,
+                Ok(new_namespace) if new_namespace.get_is_exact() =>
+                    Some(new_namespace.tags.clone()),
+                _ => Err(ErrorReply::from(TfsError::CrossDevice(String::from("Restoring \
+                    from the trash, the new parent has to be the root or a namespace \
+                    without predicates."))))?
+            };
+            self.restore_trashed(&previous_name, new_name, new_tags)
+                .map_err_inner(ErrorReply::from)?;
+            return Ok("Restored from trash.");
+        }
+
+        if let Ok(trash_namespace) = self.get_namespaces().get_by_inode_id(new_parent)
+            && trash_namespace.is_trash
+        {
+            if !get_is_inode_root(previous_parent)
+                || self.get_tags().get_by_name(&previous_name).is_none()
+            {
+                Err(ErrorReply::from(TfsError::PermissionDenied(String::from("Only tags \
+                    under the root can be moved into the trash, delete files instead."))))?;
+            }
+            self.trash_tag(&previous_name)
+                .map_err_inner(ErrorReply::from)?;
+            return Ok("Forcibly trashed tag.");
+        }
+
+        if get_is_inode_root(previous_parent) && self.get_tags().get_by_name(&previous_name)
+            .is_some()
+        {
+            if !get_is_inode_root(new_parent) {
+                Err(ErrorReply::from(TfsError::CrossDevice(String::from("Renaming a tag, \
+                    the new parent has to be the root."))))?;
+            }
+            if rename_mode == RenameMode::Exchange {
+                Err(ErrorReply::from(TfsError::PermissionDenied(String::from("Tags \
+                    can't be exchanged."))))?;
+            }
             self.rename_tag(&previous_name, new_name)
-                .map_err_inner(|e| ErrorReply::new(
-                    EINVAL, format!("Failed to rename tag. {e}")))?;
+                .map_err_inner(ErrorReply::from)?;
             return Ok("Renamed tag.");
         }
 
+        // Untagged files are under the root, so files can move between it and
+        // namespaces without predicates.
         let all_namespaces = self.get_namespaces();
         let _previous_parent = all_namespaces.get_by_inode_id(previous_parent);
         let _new_parent = all_namespaces.get_by_inode_id(new_parent);
-        if let (Ok(previous_parent), Ok(new_parent)) = (&_previous_parent, &_new_parent) {
-            self.move_file(
-                &previous_parent.tags.clone(), &previous_name,
-                new_parent.tags.clone(), new_name)
-                .map_err_inner(|e| ErrorReply::new(
-                    EINVAL, format!("Failed to rename file. {e}")))?;
+        let previous_tags = match &_previous_parent {
+            _ if get_is_inode_root(previous_parent) => Some(TagInodes::new()),
+            Ok(previous_parent) => Some(self.get_file_by_name_and_namespace_inode(
+                &previous_name, &previous_parent.inode)
+                .map_err_inner(ErrorReply::from)?
+                .tags
+                .clone()),
+            Err(_) => None
+        };
+        let new_tags = match &_new_parent {
+            _ if get_is_inode_root(new_parent) => Some(TagInodes::new()),
+            Ok(new_parent) if !new_parent.get_is_exact() =>
+                Err(ErrorReply::from(TfsError::PermissionDenied(format!("Namespace `{}` \
+                    has predicates, files can't be moved into it.", new_parent.name))))?,
+            Ok(new_parent) => Some(new_parent.tags.clone()),
+            Err(_) => None
+        };
+        if let (Some(previous_tags), Some(new_tags)) = (previous_tags, new_tags) {
+            self.move_file(&previous_tags, &previous_name, new_tags, new_name, rename_mode)
+                .map_err_inner(ErrorReply::from)?;
             return Ok("Renamed file.");
         }
 
         let mut e = format!("Renaming a tag, the parent has to be the root. \
-            Renaming a file, the parent has to be a valid namespace. Previous \
-            and new parent inodes are `{}` and `{}`.", previous_parent, new_parent);
+            Renaming a file, the parent has to be the root or a valid namespace. \
+            Previous and new parent inodes are `{}` and `{}`.", previous_parent, new_parent);
         e.append_if_error(_previous_parent);
         e.append_if_error(_new_parent);
-        Err(ErrorReply::new(EINVAL, e))?
+        Err(ErrorReply::from(TfsError::CrossDevice(e)))?
+    }
+
+    /// Files have one name and one set of tags, so linking a file into a namespace
+    /// adds the namespace's tags to it rather than making a second file.
+    fn link_inner(&mut self, _request: &Request<'_>, file_inode: u64, new_parent: u64,
+        new_name: &OsStr) -> ResultBt<LinkReply, ErrorReply>
+    {
+        self.check_if_writable()?;
+        let file_inode: FileInode = file_inode.try_into()
+            .map_err_inner(|e| ErrorReply::from(TfsError::PermissionDenied(format!("Only \
+                files can be linked. {e}"))))?;
+        let new_parent = self.get_directory_inode(new_parent)?;
+        let new_name = new_name.to_string_lossy();
+
+        let new_tags = match self.get_namespaces().get_by_inode_id(new_parent) {
+            _ if get_is_inode_root(new_parent) => TagInodes::new(),
+            Ok(new_namespace) if new_namespace.get_is_exact() => new_namespace.tags.clone(),
+            Ok(new_namespace) => Err(ErrorReply::from(TfsError::PermissionDenied(format!(
+                "Namespace `{}` has predicates, files can't be linked into it.",
+                new_namespace.name))))?,
+            Err(e) => Err(ErrorReply::from(TfsError::NotFound(e.to_string())))?
+        };
+        let file_name = &self.get_files()
+            .get_by_inode(&file_inode)
+            .ok_or(ErrorReply::from(TfsError::NotFound(format!("File with inode \
+                `{file_inode}` does not exist."))))?
+            .name;
+        if *file_name != new_name {
+            Err(ErrorReply::from(TfsError::PermissionDenied(format!("Linking only adds \
+                tags, so the link has to keep the file's name, `{file_name}`."))))?;
+        }
+
+        self.add_tags_to_file(&file_inode, &new_tags)
+            .map_err_inner(ErrorReply::from)?;
+        let fuser_attributes = self.get_file_fuser(&file_inode)
+            .map_err_inner(ErrorReply::from)?;
+        Ok(LinkReply {
+            ttl: self.get_ttl(fuser_attributes.ino),
+            attr: fuser_attributes,
+            generation: self.get_generation(fuser_attributes.ino),
+            message: "Added the namespace's tags to the file."
+        })
     }
 
     fn write_inner(&mut self, _request: &Request<'_>, target_inode: u64,
         _file_handle: u64, start_position: i64, to_write: &[u8], write_flags: u32,
         flags: i32, _lock_owner: Option<u64>) -> ResultBt<WriteReply, ErrorReply>
     {
+        self.check_if_writable()?;
         let byte_amount: u32 = to_write.len().try_into().with_bt()
-            .map_err_inner(|e| ErrorReply::new(
-                EINVAL, format!("Writing too much data. {e}")))?;
+            .map_err_inner(|e| ErrorReply::from(TfsError::InvalidArgument(
+                format!("Writing too much data. {e}"))))?;
 
         let file_inode: FileInode = target_inode.try_into()
-            .map_err_inner(|e| ErrorReply::new(
-                EINVAL, format!("Is not a file inode. {e}")))?;
+            .map_err_inner(|e| ErrorReply::from(TfsError::InvalidArgument(
+                format!("Is not a file inode. {e}"))))?;
         let start_position: u64 = start_position.try_into().with_bt()
-            .map_err_inner(|e| ErrorReply::new(
-                EINVAL, format!("Can't convert offset. {e}")))?;
+            .map_err_inner(|e| ErrorReply::from(TfsError::InvalidArgument(
+                format!("Can't convert offset. {e}"))))?;
         self.write_to_file(&file_inode, start_position, to_write)
-            .map_err_inner(|e| ErrorReply::new(EINVAL, e.to_string()))?;
+            .map_err_inner(ErrorReply::from)?;
 
         Ok(WriteReply {
             amount: byte_amount,
@@ -710,6 +1214,58 @@ impl<Storage: TfsStorage> TagFilesystem<Storage> {
         })
     }
 
+    fn copy_file_range_inner(&mut self, _request: &Request<'_>, source_inode: u64,
+        source_position: i64, target_inode: u64, target_position: i64, length: u64,
+        flags: u32) -> ResultBt<WriteReply, ErrorReply>
+    {
+        self.check_if_writable()?;
+        if flags != 0 {
+            return Err(ErrorReply::from(TfsError::InvalidArgument(format!(
+                "Unsupported flags `{flags:#x}`."))))?;
+        }
+        if get_is_inode_virtual(source_inode) || get_is_inode_virtual(target_inode) {
+            return Err(ErrorReply::from(TfsError::CrossDevice(
+                "Virtual files have no storage to share.".to_owned())))?;
+        }
+
+        let source_inode: FileInode = source_inode.try_into()
+            .map_err_inner(|e| ErrorReply::from(TfsError::InvalidArgument(
+                format!("Source is not a file inode. {e}"))))?;
+        let target_inode: FileInode = target_inode.try_into()
+            .map_err_inner(|e| ErrorReply::from(TfsError::InvalidArgument(
+                format!("Target is not a file inode. {e}"))))?;
+        let source_position: u64 = source_position.try_into().with_bt()
+            .map_err_inner(|e| ErrorReply::from(TfsError::InvalidArgument(
+                format!("Can't convert source offset. {e}"))))?;
+        let target_position: u64 = target_position.try_into().with_bt()
+            .map_err_inner(|e| ErrorReply::from(TfsError::InvalidArgument(
+                format!("Can't convert target offset. {e}"))))?;
+        // Replies can only count up to `u32::MAX`, the caller asks again for the rest.
+        let length = length.min(u32::MAX.into());
+        let copied_amount = self.copy_between_files(&source_inode, source_position,
+            &target_inode, target_position, length)
+            .map_err_inner(ErrorReply::from)?;
+
+        Ok(WriteReply {
+            amount: copied_amount.try_into().with_bt()
+                .map_err_inner(|e| ErrorReply::from(TfsError::InvalidArgument(
+                    format!("Copied too much data. {e}"))))?,
+            message: "Copied."
+        })
+    }
+
+    fn flush_inner(&mut self, _request: &Request<'_>, target_inode: u64,
+        _file_handle: u64, _lock_owner: u64) -> ResultBt<&'static str, ErrorReply>
+    {
+        if !FileInode::get_is_file(target_inode) || get_is_inode_virtual(target_inode) {
+            return Ok("Nothing to flush.");
+        }
+
+        self.refresh_contents()
+            .map_err_inner(ErrorReply::from)?;
+        Ok("Flushed.")
+    }
+
     fn setattr_inner(&mut self, _request: &Request<'_>, target_inode: u64,
         _mode: Option<u32>, uid: Option<u32>, gid: Option<u32>, size: Option<u64>,
         _atime: Option<TimeOrNow>, _mtime: Option<TimeOrNow>, _ctime: Option<SystemTime>,
@@ -717,13 +1273,13 @@ impl<Storage: TfsStorage> TagFilesystem<Storage> {
         _bkuptime: Option<SystemTime>, flags: Option<u32>)
         -> ResultBt<SetattrReply, ErrorReply>
     {
+        self.check_if_writable()?;
         let fuser_attributes = self.get_fuser_attributes(target_inode)
-            .map_err_inner(|e| ErrorReply::new(
-                ENOENT, format!("Inode does not match anything. {e}")))?;
+            .map_err_inner(ErrorReply::from)?;
 
         // TODO
         Ok(SetattrReply {
-            ttl: ANY_TTL,
+            ttl: self.get_ttl(fuser_attributes.ino),
             attr: fuser_attributes,
             message: "Set attributes."
         })
@@ -732,44 +1288,76 @@ impl<Storage: TfsStorage> TagFilesystem<Storage> {
     fn unlink_inner(&mut self, _request: &Request<'_>, parent_inode: u64,
         file_name: &OsStr) -> ResultBt<&'static str, ErrorReply>
     {
+        self.check_if_writable()?;
+        let parent_inode = self.get_directory_inode(parent_inode)?;
         if !get_is_inode_root(parent_inode) && !NamespaceInode::get_is_namespace(parent_inode) {
-            Err(ErrorReply::new(ENOENT, String::from("Not child of TFS root nor \
-                a namespace.")))?;
+            Err(ErrorReply::from(TfsError::NotFound(String::from("Not child of TFS root \
+                nor a namespace."))))?;
         }
 
         let file_name = file_name.to_string_lossy();
 
         if get_is_inode_root(parent_inode) {
-            self.remove_file_by_name_and_tags(&file_name, &TagInodes::new())
-                .map_err_inner(|e| ErrorReply::new(ENOENT, e.to_string()))?;
+            self.trash_file_by_name_and_tags(&file_name, &TagInodes::new())
+                .map_err_inner(ErrorReply::from)?;
         }
 
         if let Ok(parent_namespace) = self.get_namespaces().get_by_inode_id(parent_inode) {
-            self.remove_file_by_name_and_tags( &file_name, &parent_namespace.tags.clone())
-                .map_err_inner(|e| ErrorReply::new(ENOENT, e.to_string()))?;
+            if parent_namespace.is_trash {
+                self.purge_trashed(&file_name)
+                    .map_err_inner(ErrorReply::from)?;
+                return Ok("Purged from trash.");
+            }
+
+            let file_tags = self.get_file_by_name_and_namespace_inode(&file_name,
+                &parent_namespace.inode)
+                .map_err_inner(ErrorReply::from)?
+                .tags
+                .clone();
+            self.trash_file_by_name_and_tags(&file_name, &file_tags)
+                .map_err_inner(ErrorReply::from)?;
         }
 
-        Ok("Deleted.")
+        Ok("Trashed.")
     }
 
     fn rmdir_inner(&mut self, _request: &Request<'_>, parent_inode: u64,
         tag_name: &OsStr) -> ResultBt<&'static str, ErrorReply>
     {
+        self.check_if_writable()?;
+        let tag_name = tag_name.to_string_lossy();
+
+        if let Ok(parent_namespace) = self.get_namespaces().get_by_inode_id(parent_inode)
+            && parent_namespace.is_trash
+        {
+            self.purge_trashed(&tag_name)
+                .map_err_inner(ErrorReply::from)?;
+            return Ok("Purged from trash.");
+        }
+
         if !get_is_inode_root(parent_inode) {
-            Err(ErrorReply::new(ENOENT, "Not child of TFS root."))?
+            Err(ErrorReply::from(TfsError::NotFound(String::from("Not child of TFS \
+                root."))))?
+        }
+
+        let tag_inode = self.get_tags().get_by_name(&tag_name)
+            .ok_or(ErrorReply::from(TfsError::NotFound(format!("Tag `{tag_name}` does \
+                not exist."))))?
+            .inode;
+        if self.get_is_tag_carried(&tag_inode) {
+            Err(ErrorReply::from(TfsError::NotEmpty(format!("Tag `{tag_name}` is still \
+                on files. Move it into `{{ .trash }}` or use `tfs tags delete --force` to \
+                delete it anyway."))))?;
         }
         
-        self.delete_tag(&tag_name.to_string_lossy())
-            .map_err_inner(|e| ErrorReply::new(
-                ENOENT, format!("Failed to delete tag. {e}")))?;
+        self.trash_tag(&tag_name)
+            .map_err_inner(ErrorReply::from)?;
 
-        Ok("Deleted.")
+        Ok("Trashed.")
     }
 }
 
-const ANY_GENERATION: u64 = 0;
 const ANY_FILE_HANDLE: u64 = 0;
-const ANY_FLAGS: u32 = 0;
 
 // TODO: Give proper values.
 const ROOT_ATTRIBUTES: FileAttr = FileAttr {
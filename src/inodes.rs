@@ -1,11 +1,8 @@
-use std::{borrow::Borrow, collections::BTreeSet, fmt::Display};
+use std::{collections::{BTreeSet, HashMap, HashSet}, fmt::Display, marker::PhantomData};
 
 use fuser::FUSE_ROOT_ID;
-use itertools::Itertools;
-use rand::random_range;
 
-use crate::{errors::{AnyError, ResultBtAny}, unwrap_or,
-    wrappers::write_btreeset, WithBacktrace};
+use crate::{errors::{AnyError, ResultBtAny}, wrappers::write_btreeset, WithBacktrace};
 
 // TODO: Add numbers to tabs in VIM.
 
@@ -27,27 +24,137 @@ pub fn get_is_inode_root(inode_id: u64) -> bool {
     inode_id == FUSE_ROOT_ID
 }
 
-fn generate_jumpoff_inode() -> u64 {
-    let unscaled_start = CUSTOM_INODE_START / INODE_TYPE_COUNT;
-    let unscaled_end = (u64::MAX) / INODE_TYPE_COUNT;
-    random_range(unscaled_start..=unscaled_end) * INODE_TYPE_COUNT
+/// `.tfs` and what's in it are made up on the fly, so take ids from the very top,
+/// which the allocators never get to. Being of the file type, they're never taken to
+/// be a tag's or namespace's directory.
+pub const TFS_DIRECTORY_INODE: u64 = u64::MAX;
+pub const LAST_ERROR_INODE: u64 = u64::MAX - INODE_TYPE_COUNT;
+
+pub fn get_is_inode_virtual(inode_id: u64) -> bool {
+    inode_id == TFS_DIRECTORY_INODE || inode_id == LAST_ERROR_INODE
+}
+
+pub trait TypedInode: TryFrom<u64, Error = WithBacktrace<AnyError>> + Copy {
+    const TYPE_REMAINDER: u64;
+
+    fn get_id(&self) -> u64;
+
+    fn get_first_id() -> u64 {
+        CUSTOM_INODE_START + (INODE_TYPE_COUNT + Self::TYPE_REMAINDER
+            - CUSTOM_INODE_START % INODE_TYPE_COUNT) % INODE_TYPE_COUNT
+    }
+}
+
+/// Hands out the ids of one inode type. Released ids are reused, lowest first,
+/// before new ones are, and each reuse bumps the id's generation so that the
+/// kernel can tell it apart from what it used to be.
+#[derive(Debug, Clone)]
+pub struct InodeAllocator<I> {
+    inuse: HashSet<u64>,
+    released: BTreeSet<u64>,
+    /// Lowest id that has never been handed out.
+    next_id: u64,
+    /// Only ids that have been reused, the rest being on generation 0.
+    generations: HashMap<u64, u64>,
+    inode_type: PhantomData<I>
 }
 
-fn generate_free_inode_<'a, T, R>(
-    mut inodes_inuse: impl Itertools<Item = &'a T>,
-    type_remainder: u64) -> ResultBtAny<R>
-    where T: PartialEq + 'a,
-    R: TryFrom<u64> + PartialEq + Ord + Borrow<T>
-{
-    loop {
-        let inode_id = generate_jumpoff_inode() + type_remainder;
-        let candidate_inode = unwrap_or!(R::try_from(inode_id), _e, continue);
-        if !inodes_inuse.contains(candidate_inode.borrow()) {
-            return Ok(candidate_inode);
+impl<I: TypedInode> InodeAllocator<I> {
+    pub fn new() -> Self {
+        Self {
+            inuse: HashSet::new(),
+            released: BTreeSet::new(),
+            next_id: I::get_first_id(),
+            generations: HashMap::new(),
+            inode_type: PhantomData
+        }
+    }
+
+    /// Restores what was persisted, which doesn't include the ids in use. Those
+    /// are marked again as the entries holding them are loaded.
+    pub fn try_from_persisted(next_id: u64, released: impl Iterator<Item = u64>,
+        generations: impl Iterator<Item = (u64, u64)>) -> ResultBtAny<Self>
+    {
+        let mut inode_allocator = Self::new();
+        // Snapshots from before the allocator was persisted have it zeroed.
+        if next_id != 0 {
+            inode_allocator.next_id = I::try_from(next_id)?.get_id();
+        }
+        for released_id in released {
+            inode_allocator.released.insert(I::try_from(released_id)?.get_id());
+        }
+        for (inode_id, generation) in generations {
+            inode_allocator.generations.insert(I::try_from(inode_id)?.get_id(), generation);
+        }
+        Ok(inode_allocator)
+    }
+
+    pub fn get_next_id(&self) -> u64 {
+        self.next_id
+    }
+
+    pub fn get_released(&self) -> impl Iterator<Item = &u64> {
+        self.released.iter()
+    }
+
+    pub fn get_generations(&self) -> impl Iterator<Item = (&u64, &u64)> {
+        self.generations.iter()
+    }
+
+    pub fn get_generation(&self, inode_id: u64) -> u64 {
+        self.generations.get(&inode_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn get_is_inuse(&self, inode: &I) -> bool {
+        self.inuse.contains(&inode.get_id())
+    }
+
+    /// The id that would be handed out next. It's only taken once marked as used.
+    pub fn get_free(&self) -> ResultBtAny<I> {
+        if let Some(released_id) = self.released.first() {
+            return I::try_from(*released_id);
+        }
+
+        let mut candidate_id = self.next_id;
+        while self.inuse.contains(&candidate_id) {
+            candidate_id = candidate_id.checked_add(INODE_TYPE_COUNT)
+                .ok_or("Ran out of inodes.")?;
+        }
+        I::try_from(candidate_id)
+    }
+
+    pub fn mark_used(&mut self, inode: &I) {
+        let inode_id = inode.get_id();
+        if !self.inuse.insert(inode_id) {
+            return;
+        }
+        if self.released.remove(&inode_id) {
+            *self.generations.entry(inode_id).or_default() += 1;
+        }
+        while self.inuse.contains(&self.next_id) {
+            let Some(next_id) = self.next_id.checked_add(INODE_TYPE_COUNT) else {
+                break;
+            };
+            self.next_id = next_id;
+        }
+    }
+
+    pub fn release(&mut self, inode: &I) {
+        let inode_id = inode.get_id();
+        if self.inuse.remove(&inode_id) {
+            self.released.insert(inode_id);
         }
     }
 }
 
+impl<I: TypedInode> Default for InodeAllocator<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(PartialOrd, Ord, PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub struct FileInode {
     id: u64,
@@ -60,11 +167,12 @@ impl FileInode {
         get_is_inode_type(inode_id, FILE_TYPE_REMAINDER)
     }
 
-    pub fn try_from_free_inodes<'a>(inodes_inuse: impl Iterator<Item = &'a FileInode>)
-        -> ResultBtAny<FileInode>
-    {
-        generate_free_inode_(inodes_inuse, FILE_TYPE_REMAINDER)
-    }
+}
+
+impl TypedInode for FileInode {
+    const TYPE_REMAINDER: u64 = FILE_TYPE_REMAINDER;
+
+    fn get_id(&self) -> u64 { self.id }
 }
 
 impl TryFrom<u64> for FileInode {
@@ -97,12 +205,12 @@ impl TagInode {
     pub fn get_is_tag(inode_id: u64) -> bool {
         get_is_inode_type(inode_id, TAG_TYPE_REMAINDER)
     }
-    
-    pub fn try_from_free_inodes<'a>(inodes_inuse: impl Iterator<Item = &'a TagInode>)
-        -> ResultBtAny<TagInode>
-    {
-        generate_free_inode_(inodes_inuse, TAG_TYPE_REMAINDER)
-    }
+}
+
+impl TypedInode for TagInode {
+    const TYPE_REMAINDER: u64 = TAG_TYPE_REMAINDER;
+
+    fn get_id(&self) -> u64 { self.id }
 }
 
 impl TryFrom<u64> for TagInode {
@@ -166,12 +274,12 @@ impl NamespaceInode {
     pub fn get_is_namespace(inode_id: u64) -> bool {
         get_is_inode_type(inode_id, NAMESPACE_TYPE_REMAINDER)
     }
+}
 
-    pub fn try_from_free_inodes<'a>(inodes_inuse: impl Iterator<Item = &'a NamespaceInode>)
-        -> ResultBtAny<NamespaceInode>
-    {
-        generate_free_inode_(inodes_inuse, NAMESPACE_TYPE_REMAINDER)
-    }
+impl TypedInode for NamespaceInode {
+    const TYPE_REMAINDER: u64 = NAMESPACE_TYPE_REMAINDER;
+
+    fn get_id(&self) -> u64 { self.id }
 }
 
 impl Display for NamespaceInode {
@@ -0,0 +1,44 @@
+use std::{ffi::OsStr, io::ErrorKind, sync::mpsc::{Receiver, Sender}, thread::{self,
+    JoinHandle}};
+
+use fuser::Notifier;
+use tracing::{trace, warn};
+
+/// Something the kernel might have cached, that a change to the filesystem made
+/// stale.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum Invalidation {
+    /// A name under a directory, e.g., a moved file's under a namespace.
+    Entry { parent_inode: u64, name: String },
+    /// The attributes of an inode, e.g., a renamed tag's change time.
+    Inode(u64)
+}
+
+pub type Invalidations = Sender<Invalidation>;
+
+impl Invalidation {
+    fn send_with(&self, notifier: &Notifier) -> std::io::Result<()> {
+        match self {
+            Invalidation::Entry { parent_inode, name } =>
+                notifier.inval_entry(*parent_inode, OsStr::new(name)),
+            // A negative offset leaves the inode's cached content alone.
+            Invalidation::Inode(inode_id) => notifier.inval_inode(*inode_id, -1, 0)
+        }
+    }
+}
+
+/// Sends invalidations from their own thread, as the kernel can wait on the request
+/// that caused them before acting on them.
+pub fn spawn_invalidator(notifier: Notifier, to_invalidate: Receiver<Invalidation>)
+-> JoinHandle<()> {
+    thread::spawn(move || {
+        for invalidation in to_invalidate {
+            match invalidation.send_with(&notifier) {
+                Ok(()) => trace!("Invalidated `{invalidation:?}`."),
+                // Nothing to invalidate if the kernel never cached it.
+                Err(e) if e.kind() == ErrorKind::NotFound => (),
+                Err(e) => warn!("Couldn't invalidate `{invalidation:?}`. {e}")
+            }
+        }
+    })
+}
@@ -3,26 +3,39 @@
 capnp::generated_code!(pub mod filesystem_capnp);
 capnp::generated_code!(pub mod file_capnp);
 capnp::generated_code!(pub mod tag_capnp);
+capnp::generated_code!(pub mod content_capnp);
+capnp::generated_code!(pub mod trash_capnp);
+capnp::generated_code!(pub mod inode_capnp);
 
 drums::define_with_backtrace!();
 
+pub mod caching;
 pub mod cli;
+pub mod configuration;
+pub mod contents;
+pub mod doctor;
 pub mod entries;
 pub mod errors;
+pub mod failures;
 pub mod files;
 pub mod filesystem;
 pub mod fuse;
 #[cfg(test)]
 mod tests;
 pub mod inodes;
+pub mod invalidations;
 pub mod journal;
+pub mod mounts;
+pub mod names;
 pub mod namespaces;
 pub mod os;
 pub mod path;
 pub mod persistence;
+pub mod queries;
 pub mod snapshots;
 pub mod storage;
+pub mod stores;
 pub mod tags;
 pub mod tracing;
-pub mod ttl;
+pub mod trash;
 pub mod wrappers;
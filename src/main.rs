@@ -1,19 +1,21 @@
-use std::process::exit;
+use std::{env, process::exit};
 
 use clap::Parser;
 use tag_filesystem::{cli::ProgramParameters, errors::ResultBtAny};
 
-// TODO: More user friendly error messages.
-// Error: Os { code: 21, kind: IsADirectory, message: "Is a directory" }
 fn main() -> ResultBtAny<()> {
-    if let Err(e) = _main() {
-        println!("{}", e.to_string_wbt());
+    let mut program_arguments = ProgramParameters::parse();
+    if program_arguments.verbose {
+        // Safe, as no other threads have been started yet.
+        unsafe { env::set_var("RUST_LIB_BACKTRACE", "1") };
+    }
+    if let Err(e) = program_arguments.run() {
+        if program_arguments.verbose {
+            eprintln!("Error: {}", e.to_string_wbt());
+        } else {
+            eprintln!("Error: {}\nRerun with `-v` for a backtrace.", e.error);
+        }
         exit(1);
     }
     Ok(())
 }
-
-fn _main() -> ResultBtAny<()> {
-    let mut program_arguments = ProgramParameters::parse();
-    program_arguments.run()
-}
@@ -0,0 +1,255 @@
+use std::{env, fs::{self, create_dir_all, OpenOptions}, io::ErrorKind, os::fd::AsRawFd,
+    path::{Path, PathBuf}, process::Command, thread::sleep, time::{Duration, Instant}};
+
+use mount_watcher::mount::list_current_mounts;
+use nix::{errno::Errno, mount::{umount2, MntFlags}, sys::{signal::{kill, Signal},
+    wait::{waitpid, WaitPidFlag, WaitStatus}}, unistd::{dup2, fork, getpid, setsid,
+    ForkResult, Pid}};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+
+use crate::{errors::{AnyError, ResultBtAny}, path::get_configuration_directory,
+    stores::StoreLocation, wrappers::PathExt, ResultExt};
+
+/// What TFS mounts are called in `/proc/mounts`, to tell them from other FUSE mounts.
+pub const FILESYSTEM_NAME: &str = "tfs";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const FUSERMOUNT_NAMES: [&str; 2] = ["fusermount3", "fusermount"];
+
+/// Left by a running mount, for `tfs status` and `tfs unmount` to find it by.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct MountRecord {
+    pub mount_path: PathBuf,
+    pub pid: i32,
+    /// Named store that's mounted, if it isn't the data kept for the mount's path.
+    pub store: Option<String>,
+    /// Where the mount's snapshots are written, to tell when they last were.
+    pub snapshot_directory: PathBuf,
+    pub is_read_only: bool
+}
+
+impl MountRecord {
+    const MOUNTS_DIRECTORY_NAME: &str = "mounts";
+    const RECORD_FILENAME: &str = "mount.json";
+    const PIDFILE_FILENAME: &str = "tfs.pid";
+
+    /// For this process, having mounted `store_location` at `mount_path`.
+    pub fn new(mount_path: &Path, store_location: &StoreLocation, data_directory: &Path,
+        is_read_only: bool) -> Self
+    {
+        Self {
+            mount_path: mount_path.to_path_buf(),
+            pid: getpid().as_raw(),
+            store: match store_location {
+                StoreLocation::MountPath(_) => None,
+                StoreLocation::Named(store_name) => Some(store_name.clone())
+            },
+            snapshot_directory: store_location.get_snapshot_directory(data_directory),
+            is_read_only
+        }
+    }
+
+    /// Keyed by the mount path, under the configuration directory.
+    pub fn get_record_directory(mount_path: &Path) -> PathBuf {
+        get_configuration_directory().join(Self::MOUNTS_DIRECTORY_NAME)
+            .join(mount_path.__strip_prefix("/"))
+    }
+
+    pub fn get_pidfile_path(mount_path: &Path) -> PathBuf {
+        Self::get_record_directory(mount_path).join(Self::PIDFILE_FILENAME)
+    }
+
+    fn get_record_path(mount_path: &Path) -> PathBuf {
+        Self::get_record_directory(mount_path).join(Self::RECORD_FILENAME)
+    }
+
+    /// Writes the pidfile, then the record, which marks the mount as ready.
+    pub fn save(&self) -> ResultBtAny<()> {
+        create_dir_all(Self::get_record_directory(&self.mount_path))?;
+        fs::write(Self::get_pidfile_path(&self.mount_path), format!("{}\n", self.pid))?;
+        let record_path = Self::get_record_path(&self.mount_path);
+        fs::write(&record_path, serde_json::to_string_pretty(self)?)?;
+        info!("Wrote mount record `{}`.", record_path.to_string_lossy());
+        Ok(())
+    }
+
+    pub fn load(mount_path: &Path) -> ResultBtAny<Option<Self>> {
+        match fs::read(Self::get_record_path(mount_path)) {
+            Ok(as_json) => Ok(Some(serde_json::from_slice(&as_json)?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e)?
+        }
+    }
+
+    /// Leaves the directories, as other mounts' records can be nested in them.
+    pub fn remove(mount_path: &Path) -> ResultBtAny<()> {
+        let to_remove = [Self::get_record_path(mount_path),
+            Self::get_pidfile_path(mount_path)];
+        for to_remove in to_remove {
+            match fs::remove_file(&to_remove) {
+                Ok(()) => info!("Removed `{}`.", to_remove.to_string_lossy()),
+                Err(e) if e.kind() == ErrorKind::NotFound => {},
+                Err(e) => Err(e)?
+            }
+        }
+        Ok(())
+    }
+
+    fn get_pid(&self) -> Pid {
+        Pid::from_raw(self.pid)
+    }
+
+    /// Whether the process that left the record is still around.
+    pub fn get_is_alive(&self) -> bool {
+        kill(self.get_pid(), None) != Err(Errno::ESRCH)
+    }
+
+    /// Whether the process that left the record is still around and running this
+    /// program, rather than being an unrelated one that's since reused its PID.
+    pub fn get_is_mount_process(&self) -> bool {
+        let Ok(current_exe) = env::current_exe() else {
+            return false;
+        };
+        self.get_is_alive() && fs::read_link(format!("/proc/{}/exe", self.pid))
+            .is_ok_and(|process_exe| process_exe == current_exe)
+    }
+}
+
+/// TFS mounts in `/proc/mounts`, along with their records, if they left one.
+pub fn get_active_mounts() -> ResultBtAny<Vec<(PathBuf, Option<MountRecord>)>> {
+    let mut active_mounts = vec![];
+    for linux_mount in list_current_mounts()? {
+        if linux_mount.spec != FILESYSTEM_NAME {
+            continue;
+        }
+        let mount_path = PathBuf::from(linux_mount.mount_point);
+        let mount_record = MountRecord::load(&mount_path)?;
+        active_mounts.push((mount_path, mount_record));
+    }
+    Ok(active_mounts)
+}
+
+pub fn get_is_mounted(mount_path: &Path) -> ResultBtAny<bool> {
+    Ok(list_current_mounts()?.into_iter()
+        .any(|linux_mount| Path::new(&linux_mount.mount_point) == mount_path))
+}
+
+/// Whether `mount_path` is a FUSE mount whose process is gone, e.g., killed with
+/// `SIGKILL`, which the kernel answers everything on with `ENOTCONN`.
+pub fn get_is_stale(mount_path: &Path) -> bool {
+    fs::metadata(mount_path)
+        .is_err_and(|e| e.raw_os_error() == Some(Errno::ENOTCONN as i32))
+}
+
+/// Lazily unmounts a stale mount. Only root can `umount2` it, so everyone else goes
+/// through `fusermount -uz`.
+#[instrument]
+pub fn detach_stale_mount(mount_path: &Path) -> ResultBtAny<()> {
+    match umount2(mount_path, MntFlags::MNT_DETACH) {
+        Ok(()) => info!("Detached stale mount `{}`.", mount_path.to_string_lossy()),
+        Err(Errno::EPERM) => run_fusermount(&["-u", "-z"], mount_path)
+            .map_err_inner(|e| AnyError::from(format!("`{0}` is a stale mount, left by a process that's \
+                gone, and couldn't be unmounted. {e} As root, `umount -l {0}` would.",
+                mount_path.to_string_lossy())))?,
+        Err(e) => Err(e)?
+    }
+    MountRecord::remove(mount_path)
+}
+
+/// `fusermount3`, which comes with FUSE 3, or else the older `fusermount`, from
+/// `PATH`.
+pub fn find_fusermount() -> Option<PathBuf> {
+    let search_paths = env::var_os("PATH")?;
+    FUSERMOUNT_NAMES.into_iter()
+        .flat_map(|fusermount_name| env::split_paths(&search_paths)
+            .map(move |search_path| search_path.join(fusermount_name)))
+        .find(|fusermount_path| fusermount_path.is_file())
+}
+
+fn run_fusermount(arguments: &[&str], mount_path: &Path) -> ResultBtAny<()> {
+    let fusermount_path = find_fusermount()
+        .ok_or("Neither `fusermount3` nor `fusermount` is installed.")?;
+    let status = Command::new(fusermount_path)
+        .args(arguments)
+        .arg(mount_path)
+        .status()
+        .map_err(|e| format!("Couldn't run `fusermount`. {e}"))?;
+    if !status.success() {
+        Err(format!("`fusermount {} {}` failed with `{status}`.", arguments.join(" "),
+            mount_path.to_string_lossy()))?;
+    }
+    info!("Ran `fusermount {}` on `{}`.", arguments.join(" "), mount_path.to_string_lossy());
+    Ok(())
+}
+
+/// Forks off a child that carries on mounting, detached from the terminal. Returns
+/// the child's pid to the parent, and nothing to the child. Has to be called before
+/// any other threads are started.
+#[instrument]
+pub fn daemonize() -> ResultBtAny<Option<Pid>> {
+    // Safe, as this process is still single threaded.
+    if let ForkResult::Parent { child } = unsafe { fork() }? {
+        info!("Forked daemon `{child}`.");
+        return Ok(Some(child));
+    }
+    setsid()?;
+    let null = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/null")?;
+    for standard_stream in 0..=2 {
+        dup2(null.as_raw_fd(), standard_stream)?;
+    }
+    Ok(None)
+}
+
+/// Waits for the daemon to have left its record, which it does once mounted.
+#[instrument]
+pub fn wait_for_daemon(mount_path: &Path, daemon_pid: Pid, timeout: Duration)
+-> ResultBtAny<()> {
+    let started = Instant::now();
+    while started.elapsed() < timeout {
+        if MountRecord::load(mount_path)?
+            .is_some_and(|mount_record| mount_record.pid == daemon_pid.as_raw())
+        {
+            return Ok(());
+        }
+        match waitpid(daemon_pid, Some(WaitPidFlag::WNOHANG))? {
+            WaitStatus::StillAlive => sleep(POLL_INTERVAL),
+            wait_status => Err(format!("Daemon `{daemon_pid}` exited before mounting, \
+                with `{wait_status:?}`, its log might say why."))?
+        }
+    }
+    Err(format!("Daemon `{daemon_pid}` hasn't mounted after `{timeout:?}`."))?
+}
+
+/// Signals the mount's process to save and unmount, falling back to `fusermount -u`
+/// when there's no process left to, or it doesn't in time.
+#[instrument]
+pub fn unmount(mount_path: &Path, timeout: Duration) -> ResultBtAny<()> {
+    // Records outlive crashes, so their PID is only trusted while the mount's live.
+    let is_live = !get_is_stale(mount_path) && get_is_mounted(mount_path)?;
+    if is_live
+        && let Some(mount_record) = MountRecord::load(mount_path)?
+        && mount_record.get_is_mount_process()
+    {
+        kill(mount_record.get_pid(), Signal::SIGTERM)?;
+        info!("Sent `SIGTERM` to `{}`.", mount_record.pid);
+        let started = Instant::now();
+        while mount_record.get_is_alive() && started.elapsed() < timeout {
+            sleep(POLL_INTERVAL);
+        }
+        if mount_record.get_is_alive() {
+            warn!("Process `{}` is still running after `{timeout:?}`.", mount_record.pid);
+        }
+    }
+
+    if get_is_stale(mount_path) {
+        return detach_stale_mount(mount_path);
+    }
+    if get_is_mounted(mount_path)? {
+        run_fusermount(&["-u"], mount_path)?;
+    }
+    MountRecord::remove(mount_path)
+}
@@ -0,0 +1,94 @@
+use derive_more::{Display, Error};
+use drums::Backtrace;
+use libc::{c_int, EINVAL, ENAMETOOLONG};
+
+use crate::{errors::ResultBt, failures::TFS_DIRECTORY_NAME, namespaces::NamespaceQuery,
+    queries::FilePredicate, trash::TRASH_NAME};
+
+/// Longest name, in bytes, that the kernel lets through.
+pub const MAX_NAME_LENGTH: usize = 255;
+pub const RESERVED_NAMES: [&str; 2] = [".", ".."];
+/// Characters that can't be in any name, as paths can't have them.
+const PATH_CHARACTERS: [char; 2] = ['/', '\0'];
+/// Characters that namespaces and their queries are spelt with, so tags having
+/// them couldn't be queried.
+const QUERY_CHARACTERS: [char; 5] = ['{', '}', ',', '<', '>'];
+
+#[derive(Debug, Display, Error, Backtrace)]
+pub enum NameError {
+    #[display("Name `{name}` is invalid, {reason}.")]
+    Invalid { name: String, reason: String },
+    #[display("Name `{name}` is longer than {MAX_NAME_LENGTH} bytes.")]
+    TooLong { name: String }
+}
+
+impl NameError {
+    pub fn new_invalid(name: &str, reason: impl Into<String>) -> Self {
+        Self::Invalid { name: name.to_string(), reason: reason.into() }
+    }
+
+    pub fn get_errno(&self) -> c_int {
+        match self {
+            NameError::Invalid { .. } => EINVAL,
+            NameError::TooLong { .. } => ENAMETOOLONG
+        }
+    }
+}
+
+fn check_name(name: &str) -> ResultBt<(), NameError> {
+    if name.is_empty() {
+        Err(NameError::new_invalid(name, "it's empty"))?;
+    }
+    if RESERVED_NAMES.contains(&name) {
+        Err(NameError::new_invalid(name, "it refers to a directory"))?;
+    }
+    if name == TFS_DIRECTORY_NAME {
+        Err(NameError::new_invalid(name, "it's where TFS reports on itself"))?;
+    }
+    if name.len() > MAX_NAME_LENGTH {
+        Err(NameError::TooLong { name: name.to_string() })?;
+    }
+    if let Some(character) = name.chars().find(|character| PATH_CHARACTERS.contains(character)) {
+        Err(NameError::new_invalid(name, format!("it has `{}`", character.escape_default())))?;
+    }
+    Ok(())
+}
+
+/// Files can be named almost anything, except what the root would take to be a
+/// namespace.
+pub fn check_file_name(file_name: &str) -> ResultBt<(), NameError> {
+    check_name(file_name)?;
+    if file_name.starts_with('{') {
+        Err(NameError::new_invalid(file_name, "it'd be taken to be a namespace"))?;
+    }
+    Ok(())
+}
+
+/// Tags have to be spellable within namespaces, e.g., `{ tag_1, tag_2 }`.
+pub fn check_tag_name(tag_name: &str) -> ResultBt<(), NameError> {
+    check_name(tag_name)?;
+    if tag_name == TRASH_NAME {
+        Err(NameError::new_invalid(tag_name, "it's reserved for the trash"))?;
+    }
+    if let Some(character) = tag_name.chars()
+        .find(|character| QUERY_CHARACTERS.contains(character))
+    {
+        Err(NameError::new_invalid(tag_name, format!("it has `{character}`")))?;
+    }
+    let prefixes = [FilePredicate::CONTENT_PREFIX].into_iter()
+        .chain(NamespaceQuery::REMOVAL_PREFIXES);
+    for prefix in prefixes {
+        if tag_name.starts_with(prefix) {
+            Err(NameError::new_invalid(tag_name, format!("it starts with `{prefix}`")))?;
+        }
+    }
+    if tag_name.trim() != tag_name {
+        Err(NameError::new_invalid(tag_name, "it starts or ends with whitespace"))?;
+    }
+    Ok(())
+}
+
+/// Stores name a directory of their own, so only have to be valid as one.
+pub fn check_store_name(store_name: &str) -> ResultBt<(), NameError> {
+    check_name(store_name)
+}
@@ -4,14 +4,99 @@ use bon::Builder;
 use fuser::{FileAttr, FileType};
 
 use crate::{errors::ResultBtAny, inodes::{NamespaceInode, TagInodes},
-    os::{COMMON_BLOCK_SIZE, NO_RDEV, ROOT_GID, ROOT_UID}, wrappers::write_iter};
+    os::{COMMON_BLOCK_SIZE, NO_RDEV, ROOT_GID, ROOT_UID},
+    queries::{FilePredicate, TagPredicate}, wrappers::write_iter};
 
 #[derive(Builder, Debug)]
 #[builder(on(String, into))]
 pub struct TfsNamespace {
     pub name: String,
     pub inode: NamespaceInode,
-    pub tags: TagInodes
+    pub tags: TagInodes,
+    #[builder(default = Vec::new())]
+    pub predicates: Vec<TagPredicate>,
+    #[builder(default = Vec::new())]
+    pub file_predicates: Vec<FilePredicate>,
+    /// Lists what's in the trash rather than files by their tags.
+    #[builder(default = false)]
+    pub is_trash: bool
+}
+
+impl TfsNamespace {
+    /// Namespaces without predicates list files with exactly the namespace's tags,
+    /// so only these can have files created or moved into them.
+    pub fn get_is_exact(&self) -> bool {
+        !self.is_trash && self.predicates.is_empty() && self.file_predicates.is_empty()
+    }
+
+    pub fn get_is_time_based(&self) -> bool {
+        self.file_predicates.iter().any(FilePredicate::get_is_time_based)
+    }
+
+    pub fn get_query(&self) -> NamespaceQuery {
+        NamespaceQuery {
+            tags: self.tags.clone(),
+            predicates: self.predicates.clone(),
+            file_predicates: self.file_predicates.clone(),
+            is_trash: self.is_trash
+        }
+    }
+}
+
+/// What a namespace selects, which is what namespaces are deduplicated by.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct NamespaceQuery {
+    pub tags: TagInodes,
+    pub predicates: Vec<TagPredicate>,
+    pub file_predicates: Vec<FilePredicate>,
+    pub is_trash: bool
+}
+
+impl NamespaceQuery {
+    /// Mark tokens of a nested query that take away from its parent's query, e.g.,
+    /// `{ ~tag_2 }` or `{ !tag_2 }` under `{ tag_1, tag_2 }` is `{ tag_1 }`.
+    pub const REMOVAL_PREFIXES: [char; 2] = ['~', '!'];
+    /// Stands for the query a nested one is relative to, e.g., `{ ., tag_2 }`.
+    pub const RELATIVE_TOKEN: &str = ".";
+
+    pub fn new() -> Self {
+        Self {
+            tags: TagInodes::new(),
+            predicates: vec![],
+            file_predicates: vec![],
+            is_trash: false
+        }
+    }
+
+    /// Orders and deduplicates predicates, so that however a query was spelt, ones
+    /// selecting the same things are equal.
+    pub fn into_canonical(mut self) -> Self {
+        for file_predicate in &mut self.file_predicates {
+            if let FilePredicate::Content(terms) = file_predicate {
+                terms.sort();
+                terms.dedup();
+            }
+        }
+        self.predicates.sort_by_cached_key(TagPredicate::to_string);
+        self.predicates.dedup();
+        self.file_predicates.sort_by_cached_key(FilePredicate::to_string);
+        self.file_predicates.dedup();
+        self
+    }
+
+    pub fn without(mut self, removed: &NamespaceQuery) -> Self {
+        self.tags.0.retain(|inode| !removed.tags.0.contains(inode));
+        self.predicates.retain(|predicate| !removed.predicates.contains(predicate));
+        self.file_predicates.retain(|file_predicate| !removed.file_predicates
+            .contains(file_predicate));
+        self
+    }
+}
+
+impl Default for NamespaceQuery {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> From<&'a TfsNamespace> for &'a TagInodes {
@@ -34,16 +119,23 @@ impl Display for TfsNamespace {
 
 #[derive(Debug)]
 pub struct IndexedNamepsaces {
-    namespaces: HashMap<NamespaceInode, TfsNamespace>
+    namespaces: HashMap<NamespaceInode, TfsNamespace>,
+    by_query: HashMap<NamespaceQuery, NamespaceInode>
 }
 
 impl IndexedNamepsaces {
     pub fn new() -> Self {
         Self {
-            namespaces: HashMap::new()
+            namespaces: HashMap::new(),
+            by_query: HashMap::new()
         }
     }
 
+    pub fn get_by_query(&self, namespace_query: &NamespaceQuery) -> Option<&TfsNamespace> {
+        self.by_query.get(namespace_query)
+            .and_then(|namespace_inode| self.namespaces.get(namespace_inode))
+    }
+
     pub fn get_by_inode(&self, namespace_inode: &NamespaceInode) -> ResultBtAny<&TfsNamespace> {
         self.namespaces.get(&namespace_inode)
             .ok_or(Self::get_namespace_404_message(namespace_inode).into())
@@ -76,11 +168,6 @@ impl IndexedNamepsaces {
         &self.namespaces 
     }
 
-    pub fn get_free_inode(&self) -> ResultBtAny<NamespaceInode> {
-        let inodes_inuse = self.namespaces.keys();
-        NamespaceInode::try_from_free_inodes(inodes_inuse)
-    }
-
     pub fn add(&mut self, to_add: TfsNamespace) -> ResultBtAny<NamespaceInode> {
         let namespace_inode = to_add.inode;
 
@@ -89,16 +176,32 @@ impl IndexedNamepsaces {
             Err(format!("Namespace with id `{}` already exists.", namespace_inode))?;
         }
         
+        self.by_query.entry(to_add.get_query())
+            .or_insert(namespace_inode);
         self.namespaces.insert(namespace_inode, to_add);
         Ok(namespace_inode)
     }
 
-    pub fn do_for_all<'a, T>(&'a mut self,
-        mut to_do: impl FnMut(NamespaceUpdate) -> T + 'a)
-        -> impl Iterator<Item = T> + 'a
-    {
-        self.namespaces.values_mut()
-            .map(move |namespace| to_do(namespace.into()))
+    pub fn remove_by_inode(&mut self, namespace_inode: &NamespaceInode) -> Option<TfsNamespace> {
+        let removed_namespace = self.namespaces.remove(namespace_inode)?;
+        let removed_query = removed_namespace.get_query();
+        if self.by_query.get(&removed_query) == Some(namespace_inode) {
+            self.by_query.remove(&removed_query);
+        }
+        Some(removed_namespace)
+    }
+
+    /// Updates are done eagerly, as namespaces are re-indexed by their queries after.
+    pub fn do_for_all<T>(&mut self, mut to_do: impl FnMut(NamespaceUpdate) -> T) -> Vec<T> {
+        let done: Vec<_> = self.namespaces.values_mut()
+            .map(|namespace| to_do(namespace.into()))
+            .collect();
+        self.by_query.clear();
+        for tfs_namespace in self.namespaces.values() {
+            self.by_query.entry(tfs_namespace.get_query())
+                .or_insert(tfs_namespace.inode);
+        }
+        done
     }
 }
 
@@ -111,7 +214,9 @@ impl Display for IndexedNamepsaces {
 pub struct NamespaceUpdate<'a> {
     pub name: &'a mut String,
     inode: &'a NamespaceInode,
-    pub tags: &'a mut TagInodes
+    pub tags: &'a mut TagInodes,
+    pub predicates: &'a Vec<TagPredicate>,
+    pub file_predicates: &'a Vec<FilePredicate>
 }
 
 impl<'a> NamespaceUpdate<'a> {
@@ -125,12 +230,14 @@ impl<'a> From<&'a mut TfsNamespace> for NamespaceUpdate<'a> {
         NamespaceUpdate {
             name: &mut value.name,
             inode: &mut value.inode,
-            tags: &mut value.tags
+            tags: &mut value.tags,
+            predicates: &value.predicates,
+            file_predicates: &value.file_predicates
         }
     }
 }
 
-pub fn get_fuse_attributes(namespace_inode: &NamespaceInode) -> FileAttr {
+pub fn get_fuse_attributes(namespace_inode: &NamespaceInode, link_count: u32) -> FileAttr {
     FileAttr {
         ino: namespace_inode.get_id(),
         size: 0,
@@ -141,7 +248,7 @@ pub fn get_fuse_attributes(namespace_inode: &NamespaceInode) -> FileAttr {
         crtime: SystemTime::UNIX_EPOCH,
         kind: FileType::Directory,
         perm: 0o777,
-        nlink: 0,
+        nlink: link_count,
         uid: ROOT_UID,
         gid: ROOT_GID,
         rdev: NO_RDEV,
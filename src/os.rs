@@ -4,3 +4,15 @@ pub const ROOT_GID: u32 = 0;
 pub const NO_RDEV: u32 = 0;
 
 pub const COMMON_BLOCK_SIZE: u32 = 4096;
+
+/// Tells tools like `find` that a directory's subdirectories weren't counted, for
+/// tag and namespace directories, whose neighbours change along with any file's tags.
+pub const UNCOUNTED_DIRECTORY_LINKS: u32 = 1;
+
+/// Directories are linked to from their parent, their own `.`, and each of their
+/// subdirectories' `..`.
+pub fn get_directory_link_count(subdirectory_count: usize) -> u32 {
+    u32::try_from(subdirectory_count)
+        .unwrap_or(u32::MAX)
+        .saturating_add(2)
+}
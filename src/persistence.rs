@@ -1,12 +1,18 @@
-use std::{io::{BufRead, Write}, time::{Duration, SystemTime, UNIX_EPOCH}};
+use std::{collections::BTreeSet, io::{BufRead, Write}, time::{Duration, SystemTime,
+    UNIX_EPOCH}};
 
 use capnp::{message::{self, ReaderOptions}, serialize_packed};
 
-use crate::{errors::{AnyError, ResultBtAny}, files::TfsFile, filesystem_capnp::tag_filesystem,
-    inodes::{FileInode, TagInode}, tags::TfsTag};
+use crate::{contents::TfsContent, errors::{AnyError, ResultBtAny}, file_capnp::tfs_file,
+    files::TfsFile, filesystem_capnp::tag_filesystem, inode_capnp::tfs_inode_allocator,
+    inodes::{FileInode, InodeAllocator, TagInode, TypedInode}, tag_capnp::tfs_tag,
+    tags::TfsTag, trash::{TfsTrashed, TrashedItem}};
+
+pub type PersistedTagFilesystem = (Vec<TfsFile>, Vec<TfsTag>, Vec<TfsContent>, Vec<TfsTrashed>,
+    InodeAllocator<FileInode>, InodeAllocator<TagInode>);
 
 pub fn deserialize_tag_filesystem(read_location: impl BufRead)
-    -> ResultBtAny<(Vec<TfsFile>, Vec<TfsTag>)>
+    -> ResultBtAny<PersistedTagFilesystem>
 {
     let capnp_message = serialize_packed::read_message(read_location,
         ReaderOptions::new())?;
@@ -14,107 +20,165 @@ pub fn deserialize_tag_filesystem(read_location: impl BufRead)
 
     let mut tfs_files = vec![];
     for capnp_file in capnp_filesystem.get_files()? {
-        let file_name = capnp_file.get_name()
-            .map_err(AnyError::from)
-            .and_then(|name| name.to_string()
-                .map_err(AnyError::from));
-        let file_inode = FileInode::try_from(capnp_file.get_inode());
-        let when_accessed = as_system_time_unix_epoch(capnp_file.get_when_accessed());
-        let when_modified = as_system_time_unix_epoch(capnp_file.get_when_modified());
-        let when_changed = as_system_time_unix_epoch(capnp_file.get_when_changed());
-        let when_created = as_system_time_unix_epoch(capnp_file.get_when_created());
-        let tag_inodes = capnp_file.get_tags()
-            .map_err(AnyError::from)
-            .and_then(|inodes| {
-                let mut _inodes = vec![];
-                let mut errors = vec![];
-                for tag_inode in inodes {
-                    match TagInode::try_from(tag_inode) {
-                        Ok(inode) => _inodes.push(inode),
-                        Err(e) => errors.push(e),
-                    }
-                }
-                if !errors.is_empty() {
-                    return Err(errors.iter()
-                        .map(|e| e.to_string_wbt())
-                        .collect::<Vec<_>>()
-                        .join(". ")
-                        .into());
-                }
-                Ok(_inodes.into_iter())
-            });
-        
-        match (
-            file_name, file_inode, when_accessed,
-            when_modified, when_changed, when_created, tag_inodes
-        ) {
-            (
-                Ok(name), Ok(inode), Ok(accessed),
-                Ok(modified), Ok(changed), Ok(created), Ok(tags)
-            ) => {
-                tfs_files.push(TfsFile { 
-                    name,
-                    inode,
-                    owner: capnp_file.get_owner(),
-                    group: capnp_file.get_group(),
-                    permissions: capnp_file.get_permissions(),
-                    when_accessed: accessed,
-                    when_modified: modified,
-                    when_changed: changed,
-                    when_created: created,
-                    tags: tags.into(),
-                });
-            },
-            (name, inode, accessed, modified, changed, created, tags) => {
-                return Err(format!("Not all file fields could be deserialized: \
-                    name `{name:?}`, inode `{inode:?}`, accessed `{accessed:?}`, \
-                    modified `{modified:?}`, changed `{changed:?}`, \
-                    created `{created:?}`, tags `{tags:?}`.").into());
-            }
-        }
+        tfs_files.push(deserialize_file(capnp_file)?);
     }
 
     let mut tfs_tags = vec![];
     for capnp_tag in capnp_filesystem.get_tags()? {
-        let tag_name = capnp_tag.get_name()
-            .map_err(AnyError::from)
-            .and_then(|name| name.to_string()
-                .map_err(AnyError::from));
-        let tag_inode = TagInode::try_from(capnp_tag.get_inode());
-        let when_accessed = as_system_time_unix_epoch(capnp_tag.get_when_accessed());
-        let when_modified = as_system_time_unix_epoch(capnp_tag.get_when_modified());
-        let when_changed = as_system_time_unix_epoch(capnp_tag.get_when_changed());
-        let when_created = as_system_time_unix_epoch(capnp_tag.get_when_created());
-        match (
-            tag_name, tag_inode, when_accessed,
-            when_modified, when_changed, when_created
-        ) {
-            (
-                Ok(name), Ok(inode), Ok(accessed),
-                Ok(modified), Ok(changed), Ok(created)
-            ) => {
-                tfs_tags.push(TfsTag { 
-                    name,
-                    inode,
-                    owner: capnp_tag.get_owner(),
-                    group: capnp_tag.get_group(),
-                    permissions: capnp_tag.get_permissions(),
-                    when_accessed: accessed,
-                    when_modified: modified,
-                    when_changed: changed,
-                    when_created: created
-                });
-            },
-            (name, inode, accessed, modified, created, changed) => {
-                return Err(format!("Not all tag fields could be deserialized: \
-                    name `{name:?}`, inode `{inode:?}`, accessed `{accessed:?}`, \
-                    modified `{modified:?}`, changed `{changed:?}`, \
-                    created `{created:?}`.").into());
+        tfs_tags.push(deserialize_tag(capnp_tag)?);
+    }
+
+    let mut tfs_contents = vec![];
+    for capnp_content in capnp_filesystem.get_contents()? {
+        let file_inode = FileInode::try_from(capnp_content.get_inode())?;
+        let mut terms = BTreeSet::new();
+        for term in capnp_content.get_terms()? {
+            terms.insert(term?.to_string()
+                .map_err(AnyError::from)?);
+        }
+        tfs_contents.push(TfsContent {
+            inode: file_inode,
+            terms
+        });
+    }
+
+    let mut tfs_trashed = vec![];
+    for capnp_trashed in capnp_filesystem.get_trashed_files()? {
+        tfs_trashed.push(TfsTrashed {
+            name: capnp_trashed.get_name()?.to_string()
+                .map_err(AnyError::from)?,
+            when_trashed: as_system_time_unix_epoch(capnp_trashed.get_when_trashed())?,
+            item: TrashedItem::File(deserialize_file(capnp_trashed.get_file()?)?)
+        });
+    }
+    for capnp_trashed in capnp_filesystem.get_trashed_tags()? {
+        let mut file_inodes = vec![];
+        for file_inode in capnp_trashed.get_files()? {
+            file_inodes.push(FileInode::try_from(file_inode)?);
+        }
+        tfs_trashed.push(TfsTrashed {
+            name: capnp_trashed.get_name()?.to_string()
+                .map_err(AnyError::from)?,
+            when_trashed: as_system_time_unix_epoch(capnp_trashed.get_when_trashed())?,
+            item: TrashedItem::Tag(deserialize_tag(capnp_trashed.get_tag()?)?, file_inodes)
+        });
+    }
+
+    let file_inodes = deserialize_inode_allocator(capnp_filesystem.get_file_inodes()?)?;
+    let tag_inodes = deserialize_inode_allocator(capnp_filesystem.get_tag_inodes()?)?;
+
+    Ok((tfs_files, tfs_tags, tfs_contents, tfs_trashed, file_inodes, tag_inodes))
+}
+
+fn deserialize_inode_allocator<I: TypedInode>(capnp_allocator: tfs_inode_allocator::Reader)
+    -> ResultBtAny<InodeAllocator<I>>
+{
+    let mut generations = vec![];
+    for capnp_generation in capnp_allocator.get_generations()? {
+        generations.push((capnp_generation.get_inode(), capnp_generation.get_generation()));
+    }
+    InodeAllocator::try_from_persisted(capnp_allocator.get_next_id(),
+        capnp_allocator.get_released()?.iter(), generations.into_iter())
+}
+
+fn deserialize_file(capnp_file: tfs_file::Reader) -> ResultBtAny<TfsFile> {
+    let file_name = capnp_file.get_name()
+        .map_err(AnyError::from)
+        .and_then(|name| name.to_string()
+            .map_err(AnyError::from));
+    let file_inode = FileInode::try_from(capnp_file.get_inode());
+    let when_accessed = as_system_time_unix_epoch(capnp_file.get_when_accessed());
+    let when_modified = as_system_time_unix_epoch(capnp_file.get_when_modified());
+    let when_changed = as_system_time_unix_epoch(capnp_file.get_when_changed());
+    let when_created = as_system_time_unix_epoch(capnp_file.get_when_created());
+    let tag_inodes = capnp_file.get_tags()
+        .map_err(AnyError::from)
+        .and_then(|inodes| {
+            let mut _inodes = vec![];
+            let mut errors = vec![];
+            for tag_inode in inodes {
+                match TagInode::try_from(tag_inode) {
+                    Ok(inode) => _inodes.push(inode),
+                    Err(e) => errors.push(e),
+                }
             }
+            if !errors.is_empty() {
+                return Err(errors.iter()
+                    .map(|e| e.to_string_wbt())
+                    .collect::<Vec<_>>()
+                    .join(". ")
+                    .into());
+            }
+            Ok(_inodes.into_iter())
+        });
+    
+    match (
+        file_name, file_inode, when_accessed,
+        when_modified, when_changed, when_created, tag_inodes
+    ) {
+        (
+            Ok(name), Ok(inode), Ok(accessed),
+            Ok(modified), Ok(changed), Ok(created), Ok(tags)
+        ) => {
+            Ok(TfsFile { 
+                name,
+                inode,
+                owner: capnp_file.get_owner(),
+                group: capnp_file.get_group(),
+                permissions: capnp_file.get_permissions(),
+                when_accessed: accessed,
+                when_modified: modified,
+                when_changed: changed,
+                when_created: created,
+                tags: tags.into(),
+            })
+        },
+        (name, inode, accessed, modified, changed, created, tags) => {
+            Err(format!("Not all file fields could be deserialized: \
+                name `{name:?}`, inode `{inode:?}`, accessed `{accessed:?}`, \
+                modified `{modified:?}`, changed `{changed:?}`, \
+                created `{created:?}`, tags `{tags:?}`.").into())
         }
     }
+}
 
-    Ok((tfs_files, tfs_tags))
+fn deserialize_tag(capnp_tag: tfs_tag::Reader) -> ResultBtAny<TfsTag> {
+    let tag_name = capnp_tag.get_name()
+        .map_err(AnyError::from)
+        .and_then(|name| name.to_string()
+            .map_err(AnyError::from));
+    let tag_inode = TagInode::try_from(capnp_tag.get_inode());
+    let when_accessed = as_system_time_unix_epoch(capnp_tag.get_when_accessed());
+    let when_modified = as_system_time_unix_epoch(capnp_tag.get_when_modified());
+    let when_changed = as_system_time_unix_epoch(capnp_tag.get_when_changed());
+    let when_created = as_system_time_unix_epoch(capnp_tag.get_when_created());
+    match (
+        tag_name, tag_inode, when_accessed,
+        when_modified, when_changed, when_created
+    ) {
+        (
+            Ok(name), Ok(inode), Ok(accessed),
+            Ok(modified), Ok(changed), Ok(created)
+        ) => {
+            Ok(TfsTag { 
+                name,
+                inode,
+                owner: capnp_tag.get_owner(),
+                group: capnp_tag.get_group(),
+                permissions: capnp_tag.get_permissions(),
+                when_accessed: accessed,
+                when_modified: modified,
+                when_changed: changed,
+                when_created: created
+            })
+        },
+        (name, inode, accessed, modified, created, changed) => {
+            Err(format!("Not all tag fields could be deserialized: \
+                name `{name:?}`, inode `{inode:?}`, accessed `{accessed:?}`, \
+                modified `{modified:?}`, changed `{changed:?}`, \
+                created `{created:?}`.").into())
+        }
+    }
 }
 
 fn as_system_time_unix_epoch(unix_epoch: u64) -> ResultBtAny<SystemTime> {
@@ -124,8 +188,9 @@ fn as_system_time_unix_epoch(unix_epoch: u64) -> ResultBtAny<SystemTime> {
 }
 
 pub fn serialize_tag_filesystem(write_location: impl Write,
-    tfs_files: Vec<&TfsFile>, tfs_tags: Vec<&TfsTag>)
-    -> ResultBtAny<()>
+    tfs_files: Vec<&TfsFile>, tfs_tags: Vec<&TfsTag>, tfs_contents: Vec<&TfsContent>,
+    tfs_trashed: Vec<&TfsTrashed>, file_inodes: &InodeAllocator<FileInode>,
+    tag_inodes: &InodeAllocator<TagInode>) -> ResultBtAny<()>
 {
     type CapnpType = u32;
 
@@ -139,37 +204,7 @@ pub fn serialize_tag_filesystem(write_location: impl Write,
     let mut capnp_files = capnp_filesystem.reborrow().init_files(file_count);
     for (file_index, tfs_file) in tfs_files.iter().enumerate() {
         let file_index = CapnpType::try_from(file_index)?;
-        let when_accessed = tfs_file.when_accessed.duration_since(UNIX_EPOCH);
-        let when_modified = tfs_file.when_modified.duration_since(UNIX_EPOCH);
-        let when_changed = tfs_file.when_changed.duration_since(UNIX_EPOCH);
-
-        let file_tags = &tfs_file.tags.0;
-        let tags_count = CapnpType::try_from(file_tags.len());
-
-        match (when_accessed, when_modified, when_changed, tags_count) {
-            (Ok(accessed), Ok(modified), Ok(changed), Ok(tags_count)) => {
-                let mut capnp_file = capnp_files.reborrow().get(file_index);
-                capnp_file.set_name(tfs_file.name.clone());
-                capnp_file.set_inode(tfs_file.inode.get_id());
-                capnp_file.set_owner(tfs_file.owner);
-                capnp_file.set_group(tfs_file.group);
-                capnp_file.set_permissions(tfs_file.permissions);
-                capnp_file.set_when_accessed(accessed.as_secs());
-                capnp_file.set_when_modified(modified.as_secs());
-                capnp_file.set_when_changed(changed.as_secs());
-                let mut capnp_tags = capnp_file.init_tags(tags_count);
-                for (tag_index, file_tag) in file_tags.iter().enumerate() {
-                    capnp_tags.set(CapnpType::try_from(tag_index)?, file_tag.get_id());
-                } 
-            },
-            (accessed, modified, changed, tags_count) => {
-                return Err(format!("For file with name `{}` and inode `{}`, \
-                    not all fields could be serialized: \
-                    accessed `{accessed:?}`, modified `{modified:?}`, \
-                    changed `{changed:?}`, tags count `{tags_count:?}.",
-                    tfs_file.name, tfs_file.inode).into());
-            }
-        }
+        serialize_file(capnp_files.reborrow().get(file_index), tfs_file)?;
     }
 
     let tag_count = tfs_tags.len();
@@ -179,34 +214,156 @@ pub fn serialize_tag_filesystem(write_location: impl Write,
     let mut capnp_tags = capnp_filesystem.reborrow().init_tags(tag_count);
     for (tag_index, tfs_tag) in tfs_tags.iter().enumerate() {
         let tag_index = CapnpType::try_from(tag_index)?;
+        serialize_tag(capnp_tags.reborrow().get(tag_index), tfs_tag)?;
+    }
 
-        let when_accessed = tfs_tag.when_accessed.duration_since(UNIX_EPOCH);
-        let when_modified = tfs_tag.when_modified.duration_since(UNIX_EPOCH);
-        let when_changed = tfs_tag.when_changed.duration_since(UNIX_EPOCH);
-
-        match (when_accessed, when_modified, when_changed) {
-            (Ok(accessed), Ok(modified), Ok(changed)) => {
-                let mut capnp_tag = capnp_tags.reborrow().get(tag_index);
-                capnp_tag.set_name(tfs_tag.name.clone());
-                capnp_tag.set_inode(tfs_tag.inode.get_id());
-                capnp_tag.set_owner(tfs_tag.owner);
-                capnp_tag.set_group(tfs_tag.group);
-                capnp_tag.set_permissions(tfs_tag.permissions);
-                capnp_tag.set_when_accessed(accessed.as_secs());
-                capnp_tag.set_when_modified(modified.as_secs());
-                capnp_tag.set_when_changed(changed.as_secs());
-            },
-            (accessed, modified, changed) => {
-                return Err(format!("For tag with name `{}` and inode `{}`, \
-                    not all fields could be serialized: \
-                    accessed `{accessed:?}`, modified `{modified:?}`, \
-                    changed `{changed:?}`.",
-                    tfs_tag.name, tfs_tag.inode).into());
-            }
+    let content_count = CapnpType::try_from(tfs_contents.len())
+        .map_err(|e| format!("Cannot convert number of contents `{}` to Cap'n Proto \
+            length type. {e}", tfs_contents.len()))?;
+    let mut capnp_contents = capnp_filesystem.reborrow().init_contents(content_count);
+    for (content_index, tfs_content) in tfs_contents.iter().enumerate() {
+        let mut capnp_content = capnp_contents.reborrow()
+            .get(CapnpType::try_from(content_index)?);
+        capnp_content.set_inode(tfs_content.inode.get_id());
+        let mut capnp_terms = capnp_content.init_terms(
+            CapnpType::try_from(tfs_content.terms.len())?);
+        for (term_index, term) in tfs_content.terms.iter().enumerate() {
+            capnp_terms.set(CapnpType::try_from(term_index)?, term.as_str());
         }
     }
 
+    let trashed_files: Vec<_> = tfs_trashed.iter()
+        .filter_map(|trashed| match &trashed.item {
+            TrashedItem::File(tfs_file) => Some((trashed, tfs_file)),
+            TrashedItem::Tag(..) => None
+        })
+        .collect();
+    let mut capnp_trashed_files = capnp_filesystem.reborrow()
+        .init_trashed_files(CapnpType::try_from(trashed_files.len())?);
+    for (trashed_index, (trashed, tfs_file)) in trashed_files.into_iter().enumerate() {
+        let mut capnp_trashed = capnp_trashed_files.reborrow()
+            .get(CapnpType::try_from(trashed_index)?);
+        capnp_trashed.set_name(trashed.name.as_str());
+        capnp_trashed.set_when_trashed(trashed.when_trashed.duration_since(UNIX_EPOCH)
+            .map_err(|e| format!("Trashed `{}` has an invalid time. {e}", trashed.name))?
+            .as_secs());
+        serialize_file(capnp_trashed.init_file(), tfs_file)?;
+    }
+
+    let trashed_tags: Vec<_> = tfs_trashed.iter()
+        .filter_map(|trashed| match &trashed.item {
+            TrashedItem::File(_) => None,
+            TrashedItem::Tag(tfs_tag, file_inodes) => Some((trashed, tfs_tag, file_inodes))
+        })
+        .collect();
+    let mut capnp_trashed_tags = capnp_filesystem.reborrow()
+        .init_trashed_tags(CapnpType::try_from(trashed_tags.len())?);
+    for (trashed_index, (trashed, tfs_tag, file_inodes)) in trashed_tags.into_iter().enumerate() {
+        let mut capnp_trashed = capnp_trashed_tags.reborrow()
+            .get(CapnpType::try_from(trashed_index)?);
+        capnp_trashed.set_name(trashed.name.as_str());
+        capnp_trashed.set_when_trashed(trashed.when_trashed.duration_since(UNIX_EPOCH)
+            .map_err(|e| format!("Trashed `{}` has an invalid time. {e}", trashed.name))?
+            .as_secs());
+        let mut capnp_files = capnp_trashed.reborrow()
+            .init_files(CapnpType::try_from(file_inodes.len())?);
+        for (file_index, file_inode) in file_inodes.iter().enumerate() {
+            capnp_files.set(CapnpType::try_from(file_index)?, file_inode.get_id());
+        }
+        serialize_tag(capnp_trashed.init_tag(), tfs_tag)?;
+    }
+
+    serialize_inode_allocator(capnp_filesystem.reborrow().init_file_inodes(), file_inodes)?;
+    serialize_inode_allocator(capnp_filesystem.reborrow().init_tag_inodes(), tag_inodes)?;
+
     serialize_packed::write_message(write_location, &capnp_message)?;
 
     Ok(())
 }
+
+fn serialize_inode_allocator<I: TypedInode>(mut capnp_allocator: tfs_inode_allocator::Builder,
+    inode_allocator: &InodeAllocator<I>) -> ResultBtAny<()>
+{
+    type CapnpType = u32;
+
+    capnp_allocator.set_next_id(inode_allocator.get_next_id());
+    let released: Vec<_> = inode_allocator.get_released().collect();
+    let mut capnp_released = capnp_allocator.reborrow()
+        .init_released(CapnpType::try_from(released.len())?);
+    for (released_index, released_id) in released.into_iter().enumerate() {
+        capnp_released.set(CapnpType::try_from(released_index)?, *released_id);
+    }
+    let generations: Vec<_> = inode_allocator.get_generations().collect();
+    let mut capnp_generations = capnp_allocator
+        .init_generations(CapnpType::try_from(generations.len())?);
+    for (generation_index, (inode_id, generation)) in generations.into_iter().enumerate() {
+        let mut capnp_generation = capnp_generations.reborrow()
+            .get(CapnpType::try_from(generation_index)?);
+        capnp_generation.set_inode(*inode_id);
+        capnp_generation.set_generation(*generation);
+    }
+    Ok(())
+}
+
+fn serialize_file(mut capnp_file: tfs_file::Builder, tfs_file: &TfsFile) -> ResultBtAny<()> {
+    type CapnpType = u32;
+
+    let when_accessed = tfs_file.when_accessed.duration_since(UNIX_EPOCH);
+    let when_modified = tfs_file.when_modified.duration_since(UNIX_EPOCH);
+    let when_changed = tfs_file.when_changed.duration_since(UNIX_EPOCH);
+
+    let file_tags = &tfs_file.tags.0;
+    let tags_count = CapnpType::try_from(file_tags.len());
+
+    match (when_accessed, when_modified, when_changed, tags_count) {
+        (Ok(accessed), Ok(modified), Ok(changed), Ok(tags_count)) => {
+            capnp_file.set_name(tfs_file.name.clone());
+            capnp_file.set_inode(tfs_file.inode.get_id());
+            capnp_file.set_owner(tfs_file.owner);
+            capnp_file.set_group(tfs_file.group);
+            capnp_file.set_permissions(tfs_file.permissions);
+            capnp_file.set_when_accessed(accessed.as_secs());
+            capnp_file.set_when_modified(modified.as_secs());
+            capnp_file.set_when_changed(changed.as_secs());
+            let mut capnp_tags = capnp_file.init_tags(tags_count);
+            for (tag_index, file_tag) in file_tags.iter().enumerate() {
+                capnp_tags.set(CapnpType::try_from(tag_index)?, file_tag.get_id());
+            } 
+            Ok(())
+        },
+        (accessed, modified, changed, tags_count) => {
+            Err(format!("For file with name `{}` and inode `{}`, \
+                not all fields could be serialized: \
+                accessed `{accessed:?}`, modified `{modified:?}`, \
+                changed `{changed:?}`, tags count `{tags_count:?}.",
+                tfs_file.name, tfs_file.inode).into())
+        }
+    }
+}
+
+fn serialize_tag(mut capnp_tag: tfs_tag::Builder, tfs_tag: &TfsTag) -> ResultBtAny<()> {
+    let when_accessed = tfs_tag.when_accessed.duration_since(UNIX_EPOCH);
+    let when_modified = tfs_tag.when_modified.duration_since(UNIX_EPOCH);
+    let when_changed = tfs_tag.when_changed.duration_since(UNIX_EPOCH);
+
+    match (when_accessed, when_modified, when_changed) {
+        (Ok(accessed), Ok(modified), Ok(changed)) => {
+            capnp_tag.set_name(tfs_tag.name.clone());
+            capnp_tag.set_inode(tfs_tag.inode.get_id());
+            capnp_tag.set_owner(tfs_tag.owner);
+            capnp_tag.set_group(tfs_tag.group);
+            capnp_tag.set_permissions(tfs_tag.permissions);
+            capnp_tag.set_when_accessed(accessed.as_secs());
+            capnp_tag.set_when_modified(modified.as_secs());
+            capnp_tag.set_when_changed(changed.as_secs());
+            Ok(())
+        },
+        (accessed, modified, changed) => {
+            Err(format!("For tag with name `{}` and inode `{}`, \
+                not all fields could be serialized: \
+                accessed `{accessed:?}`, modified `{modified:?}`, \
+                changed `{changed:?}`.",
+                tfs_tag.name, tfs_tag.inode).into())
+        }
+    }
+}
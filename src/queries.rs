@@ -0,0 +1,295 @@
+use std::{cmp::Ordering, fmt::Display, time::{Duration, SystemTime}};
+
+use crate::{contents::{get_terms, IndexedContents}, errors::ResultBtAny, files::TfsFile,
+    storage::TfsStorage, tags::{TagValue, KEY_VALUE_SEPARATOR}};
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum TagComparison {
+    Present,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual
+}
+
+impl TagComparison {
+    // Two character operators come first so `>=` isn't mistaken for `>`.
+    const OPERATORS: [(&str, TagComparison); 4] = [
+        (">=", TagComparison::GreaterOrEqual),
+        ("<=", TagComparison::LessOrEqual),
+        (">", TagComparison::Greater),
+        ("<", TagComparison::Less)
+    ];
+
+    pub fn get_operator(&self) -> &'static str {
+        match self {
+            TagComparison::Present => "",
+            TagComparison::Greater => ">",
+            TagComparison::GreaterOrEqual => ">=",
+            TagComparison::Less => "<",
+            TagComparison::LessOrEqual => "<="
+        }
+    }
+
+    /// Whether `ordering`, of the candidate against the expected value, passes.
+    pub fn get_is_satisfied(&self, ordering: Ordering) -> bool {
+        match self {
+            TagComparison::Present => true,
+            TagComparison::Greater => ordering == Ordering::Greater,
+            TagComparison::GreaterOrEqual => ordering != Ordering::Less,
+            TagComparison::Less => ordering == Ordering::Less,
+            TagComparison::LessOrEqual => ordering != Ordering::Greater
+        }
+    }
+}
+
+/// A query token that matches key/value tags by their key, e.g., `year>=2020`
+/// or the bare key `year`.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct TagPredicate {
+    pub key: String,
+    pub comparison: TagComparison,
+    pub value: TagValue
+}
+
+impl TagPredicate {
+    pub fn new_present(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            comparison: TagComparison::Present,
+            value: TagValue::from("")
+        }
+    }
+
+    pub fn is_satisfied_by(&self, candidate: &TagValue) -> bool {
+        // Numbers are only compared with numbers, and words with words.
+        let ordering = match (self.value.as_number(), candidate.as_number()) {
+            (Some(expected), Some(actual)) => actual.total_cmp(&expected),
+            (None, None) => candidate.as_str().cmp(self.value.as_str()),
+            _ => return self.comparison == TagComparison::Present
+        };
+        self.comparison.get_is_satisfied(ordering)
+    }
+}
+
+impl Display for TagPredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}{}", self.key, self.comparison.get_operator(), self.value)
+    }
+}
+
+/// Parses a comparison token like `rating<=3`. Bare keys are not handled here
+/// as they can't be told apart from ordinary tag names without the filesystem.
+pub fn parse_comparison(query_token: &str) -> Option<TagPredicate> {
+    for (operator, comparison) in TagComparison::OPERATORS {
+        let Some((key, value)) = query_token.split_once(operator) else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if key.is_empty() || value.is_empty() {
+            return None;
+        }
+        return Some(TagPredicate {
+            key: key.to_string(),
+            comparison,
+            value: TagValue::from(value)
+        });
+    }
+    None
+}
+
+/// A query token that matches files by their metadata or content instead of their
+/// tags, e.g., `modified<7d`, `size>10M`, `name:*.pdf`, `owner:alice` or `?"deadline"`.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum FilePredicate {
+    /// Compares how long ago the file was last modified.
+    Modified(TagComparison, Duration),
+    Size(TagComparison, u64),
+    Name(String),
+    Owner(u32),
+    /// Terms that all have to be in the file's indexed content.
+    Content(Vec<String>)
+}
+
+impl FilePredicate {
+    pub const MODIFIED_KEY: &str = "modified";
+    pub const SIZE_KEY: &str = "size";
+    pub const NAME_KEY: &str = "name";
+    pub const OWNER_KEY: &str = "owner";
+    pub const CONTENT_PREFIX: char = '?';
+
+    const DURATION_UNITS: [(&str, u64); 5] = [
+        ("w", 7 * 24 * 60 * 60),
+        ("d", 24 * 60 * 60),
+        ("h", 60 * 60),
+        ("m", 60),
+        ("s", 1)
+    ];
+    const SIZE_UNITS: [(&str, u64); 5] = [
+        ("T", 1 << 40),
+        ("G", 1 << 30),
+        ("M", 1 << 20),
+        ("K", 1 << 10),
+        ("", 1)
+    ];
+
+    /// Time based predicates give different answers as time passes, so anything
+    /// listed with them must not be cached.
+    pub fn get_is_time_based(&self) -> bool {
+        matches!(self, FilePredicate::Modified(..))
+    }
+
+    pub fn is_satisfied_by(&self, tfs_file: &TfsFile, storage: &dyn TfsStorage,
+        contents: &IndexedContents, now: SystemTime) -> ResultBtAny<bool>
+    {
+        Ok(match self {
+            FilePredicate::Modified(comparison, age) => {
+                // Writes go straight to storage without touching `TfsFile`.
+                let when_modified = storage.get_last_modified(&tfs_file.inode)?
+                    .max(tfs_file.when_modified);
+                let file_age = now.duration_since(when_modified)
+                    .unwrap_or(Duration::ZERO);
+                comparison.get_is_satisfied(file_age.cmp(age))
+            },
+            FilePredicate::Size(comparison, size) => {
+                let file_size = storage.get_file_size(&tfs_file.inode)?;
+                comparison.get_is_satisfied(file_size.cmp(size))
+            },
+            FilePredicate::Name(pattern) => get_is_glob_match(pattern, &tfs_file.name),
+            FilePredicate::Owner(uid) => tfs_file.owner == *uid,
+            FilePredicate::Content(terms) => contents.get_is_match(&tfs_file.inode, terms)
+        })
+    }
+
+    fn parse_amount(query_token: &str, amount: &str, units: &[(&str, u64)])
+        -> ResultBtAny<u64>
+    {
+        let number_end = amount.find(|character: char| !character.is_ascii_digit()
+            && character != '.')
+            .unwrap_or(amount.len());
+        let (number, unit) = amount.split_at(number_end);
+        let number: f64 = number.parse()
+            .map_err(|_| format!("`{query_token}` does not have a valid amount."))?;
+        let (_, multiplier) = units.iter()
+            .find(|(unit_, _)| unit.trim_end_matches(['B', 'b'])
+                .eq_ignore_ascii_case(unit_))
+            .ok_or(format!("`{query_token}` does not have a valid unit."))?;
+        Ok((number * *multiplier as f64).round() as u64)
+    }
+
+    fn format_amount(amount: u64, units: &[(&str, u64)]) -> String {
+        let (unit, multiplier) = units.iter()
+            .find(|(_, multiplier)| amount != 0 && amount.is_multiple_of(*multiplier))
+            .unwrap_or(&units[units.len() - 1]);
+        format!("{}{unit}", amount / multiplier)
+    }
+}
+
+impl Display for FilePredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilePredicate::Modified(comparison, age) => write!(f, "{}{}{}",
+                Self::MODIFIED_KEY, comparison.get_operator(),
+                Self::format_amount(age.as_secs(), &Self::DURATION_UNITS)),
+            FilePredicate::Size(comparison, size) => write!(f, "{}{}{}",
+                Self::SIZE_KEY, comparison.get_operator(),
+                Self::format_amount(*size, &Self::SIZE_UNITS)),
+            FilePredicate::Name(pattern) => write!(f, "{}{KEY_VALUE_SEPARATOR}{pattern}",
+                Self::NAME_KEY),
+            FilePredicate::Owner(uid) => match users::get_user_by_uid(*uid) {
+                Some(user) => write!(f, "{}{KEY_VALUE_SEPARATOR}{}", Self::OWNER_KEY,
+                    user.name().to_string_lossy()),
+                None => write!(f, "{}{KEY_VALUE_SEPARATOR}{uid}", Self::OWNER_KEY)
+            },
+            FilePredicate::Content(terms) => write!(f, "{}\"{}\"", Self::CONTENT_PREFIX,
+                terms.join(" "))
+        }
+    }
+}
+
+/// Parses a metadata token like `size>10M` or a content token like `?"deadline"`.
+/// Gives `None` when the token's key isn't one of the metadata keys, which are
+/// otherwise reserved.
+pub fn parse_file_predicate(query_token: &str) -> ResultBtAny<Option<FilePredicate>> {
+    if let Some(search_string) = query_token.strip_prefix(FilePredicate::CONTENT_PREFIX) {
+        let terms: Vec<_> = get_terms(search_string).collect();
+        if terms.is_empty() {
+            Err(format!("`{query_token}` does not have any words to search for."))?;
+        }
+        return Ok(Some(FilePredicate::Content(terms)));
+    }
+
+    let Some(key_end) = query_token.find([KEY_VALUE_SEPARATOR, '<', '>']) else {
+        return Ok(None);
+    };
+    let key = query_token[..key_end].trim();
+
+    let file_predicate = match key {
+        FilePredicate::MODIFIED_KEY | FilePredicate::SIZE_KEY => {
+            let TagPredicate { comparison, value, .. } = parse_comparison(query_token)
+                .ok_or(format!("`{query_token}` needs one of `>`, `>=`, `<` or `<=`."))?;
+            if key == FilePredicate::MODIFIED_KEY {
+                let age = FilePredicate::parse_amount(query_token, value.as_str(),
+                    &FilePredicate::DURATION_UNITS)?;
+                FilePredicate::Modified(comparison, Duration::from_secs(age))
+            } else {
+                FilePredicate::Size(comparison, FilePredicate::parse_amount(query_token,
+                    value.as_str(), &FilePredicate::SIZE_UNITS)?)
+            }
+        },
+        FilePredicate::NAME_KEY | FilePredicate::OWNER_KEY => {
+            let value = query_token.split_once(KEY_VALUE_SEPARATOR)
+                .map(|(_, value)| value.trim())
+                .filter(|value| !value.is_empty())
+                .ok_or(format!("`{query_token}` needs to be in the form \
+                    `{key}{KEY_VALUE_SEPARATOR}...`."))?;
+            if key == FilePredicate::NAME_KEY {
+                FilePredicate::Name(value.to_string())
+            } else {
+                let uid = match value.parse() {
+                    Ok(uid) => uid,
+                    Err(_) => users::get_user_by_name(value)
+                        .ok_or(format!("User `{value}` does not exist."))?
+                        .uid()
+                };
+                FilePredicate::Owner(uid)
+            }
+        },
+        _ => return Ok(None)
+    };
+    Ok(Some(file_predicate))
+}
+
+/// Matches shell style globs, where `*` is any run of characters and `?` is any
+/// single character.
+pub fn get_is_glob_match(pattern: &str, candidate: &str) -> bool {
+    let pattern: Vec<_> = pattern.chars().collect();
+    let candidate: Vec<_> = candidate.chars().collect();
+
+    let (mut pattern_index, mut candidate_index) = (0, 0);
+    let mut last_star = None;
+    while candidate_index < candidate.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                last_star = Some((pattern_index, candidate_index));
+                pattern_index += 1;
+            },
+            Some(character) if *character == '?'
+                || *character == candidate[candidate_index] =>
+            {
+                pattern_index += 1;
+                candidate_index += 1;
+            },
+            _ => {
+                // Backtrack by letting the last `*` take one more character.
+                let Some((star_index, star_candidate_index)) = last_star else {
+                    return false;
+                };
+                pattern_index = star_index + 1;
+                candidate_index = star_candidate_index + 1;
+                last_star = Some((star_index, candidate_index));
+            }
+        }
+    }
+    pattern[pattern_index..].iter().all(|character| *character == '*')
+}
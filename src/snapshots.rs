@@ -1,4 +1,5 @@
-use std::{fs::{self, create_dir_all, File}, io::Write, path::{Path, PathBuf}};
+use std::{fs::{self, create_dir_all, File}, io::Write, path::{Path, PathBuf},
+    time::SystemTime};
 
 use derive_more::{Display, Error};
 use drums::Backtrace;
@@ -6,8 +7,7 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use tracing::{info, instrument};
 
-use crate::{errors::{AnyError, ResultBt, ResultBtAny},
-    path::get_configuration_directory, wrappers::PathExt};
+use crate::{errors::{AnyError, ResultBt, ResultBtAny}, wrappers::PathExt};
 
 pub trait TfsSnapshots {
     fn open_safe(&self) -> ResultBt<File, OpenError>;
@@ -41,16 +41,13 @@ pub struct PersistentSnapshots {
 }
 
 impl PersistentSnapshots {
-    const SNAPSHOT_DIRECTORY_NAME: &str = "snapshots";
+    pub const SNAPSHOT_DIRECTORY_NAME: &str = "snapshots";
     const POINTERS_FILENAME: &str = "pointers.json";
     const SNAPSHOT_FILENAME: &str = "tfs.snapshot";
     const SHA512_FILENAME: &str = "tfs.snapshot.sha256";
 
     #[instrument]
-    pub fn try_new(location_suffix: &PathBuf) -> ResultBtAny<Self> {
-        let snapshot_directory = get_configuration_directory()
-            .join(Self::SNAPSHOT_DIRECTORY_NAME)
-            .join(location_suffix.__strip_prefix("/"));
+    pub fn try_new(snapshot_directory: PathBuf) -> ResultBtAny<Self> {
         let does_exist = snapshot_directory.try_exists()?;
         if does_exist && !snapshot_directory.is_dir() {
             return Err(format!("`{}` already exists as a non-directory.",
@@ -79,15 +76,58 @@ impl PersistentSnapshots {
         Ok(_self)
     }
 
+    /// Opens snapshots that already exist, without creating anything.
+    #[instrument]
+    pub fn try_open(snapshot_directory: PathBuf) -> ResultBtAny<Self> {
+        let _self = Self {
+            root: snapshot_directory
+        };
+        if !_self.get_pointers_path().try_exists()? {
+            return Err(format!("No snapshots at `{}`, has the store been mounted \
+                writable yet?", _self.root.to_string_lossy()).into());
+        }
+        Ok(_self)
+    }
+
+    /// Where mounts without a store keep their snapshots, keyed by the mount path.
+    pub fn get_snapshot_directory(data_directory: &Path, location_suffix: &Path) -> PathBuf {
+        data_directory.join(Self::SNAPSHOT_DIRECTORY_NAME)
+            .join(location_suffix.__strip_prefix("/"))
+    }
+
+    /// When the current snapshot was written, if there's one yet. Doesn't create
+    /// anything, unlike opening the snapshots.
+    pub fn get_when_promoted(snapshot_directory: &Path) -> ResultBtAny<Option<SystemTime>> {
+        let _self = Self {
+            root: snapshot_directory.to_path_buf()
+        };
+        if !_self.get_pointers_path().try_exists()? {
+            return Ok(None);
+        }
+        match _self.get_snapshot_pointers()?.snapshot {
+            Some(to_snapshot) => Ok(Some(fs::metadata(to_snapshot)?.modified()?)),
+            None => Ok(None)
+        }
+    }
+
     fn get_pointers_path(&self) -> PathBuf {
         self.root.join(Self::POINTERS_FILENAME)
     }
 
+    /// Pointers are followed by file name only, so the snapshot directory can be
+    /// moved, e.g., when migrating it into a store.
     fn get_snapshot_pointers(&self) -> ResultBtAny<SnapshotPointers> {
         let to_read = self.get_pointers_path();
         let as_json = &fs::read(&to_read)?;
         info!("Read snapshot pointers file, `{}`.", to_read.to_string_lossy());
-        Ok(serde_json::from_slice(as_json)?)
+        let snapshot_pointers: SnapshotPointers = serde_json::from_slice(as_json)?;
+        let rebase = |pointer: Option<PathBuf>| pointer
+            .and_then(|pointer| pointer.file_name()
+                .map(|file_name| self.root.join(file_name)));
+        Ok(SnapshotPointers {
+            snapshot: rebase(snapshot_pointers.snapshot),
+            sha256: rebase(snapshot_pointers.sha256)
+        })
     }
 
     fn get_opposite_snapshot_path(&self) -> ResultBtAny<PathBuf> {
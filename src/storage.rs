@@ -1,9 +1,10 @@
 use std::{fs::{self, create_dir_all, remove_file, File, OpenOptions}, io::{Read, Seek, SeekFrom,
-    Write}, path::PathBuf, time::SystemTime};
+    Write}, path::{Path, PathBuf}, time::SystemTime};
 
+use nix::{fcntl::copy_file_range, sys::statvfs::statvfs};
 use tracing::{info, instrument, warn};
 
-use crate::{errors::ResultBtAny, inodes::FileInode, path::get_configuration_directory, wrappers::PathExt};
+use crate::{errors::ResultBtAny, inodes::FileInode, wrappers::PathExt};
 
 pub trait TfsStorage {
     fn get_file_size(&self, file_inode: &FileInode) -> ResultBtAny<u64>;
@@ -14,7 +15,24 @@ pub trait TfsStorage {
         read_amount: usize) -> ResultBtAny<Vec<u8>>;
     fn write(&mut self, file_inode: &FileInode, start_position: u64,
         to_write: &[u8]) -> ResultBtAny<()>;
+    /// Copies up to `length` bytes between files, returning how many were copied.
+    fn copy_range(&mut self, source_inode: &FileInode, source_position: u64,
+        target_inode: &FileInode, target_position: u64, length: u64) -> ResultBtAny<u64>;
     fn delete(&self, file_inode: &FileInode) -> ResultBtAny<()>;
+    fn get_space(&self) -> ResultBtAny<StorageSpace>;
+}
+
+/// How much room is left for content, in blocks of `block_size` bytes.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct StorageSpace {
+    pub block_size: u32,
+    pub fragment_size: u32,
+    pub total_blocks: u64,
+    pub free_blocks: u64,
+    /// Free blocks that aren't reserved for the superuser.
+    pub available_blocks: u64,
+    /// How many more files there's room for.
+    pub free_files: u64
 }
 
 #[derive(Debug)]
@@ -26,8 +44,7 @@ impl DelegateStorage {
     pub const DELEGATE_DIRECTORY_NAME: &str = "delegate_storage";
 
     #[instrument]
-    pub fn try_new(location_suffix: &PathBuf) -> ResultBtAny<Self> {
-        let delegate_directory = Self::get_delegate_directory(location_suffix);
+    pub fn try_new(delegate_directory: PathBuf) -> ResultBtAny<Self> {
         let does_exist = delegate_directory.try_exists()?;
         if does_exist && !delegate_directory.is_dir() {
             if !delegate_directory.is_dir() {
@@ -44,12 +61,19 @@ impl DelegateStorage {
         Ok(Self { root: delegate_directory })
     }
 
-    fn get_location_prefix() -> PathBuf {
-        get_configuration_directory().join(Self::DELEGATE_DIRECTORY_NAME)
+    /// Opens delegate storage that already exists, without creating anything.
+    #[instrument]
+    pub fn try_open(delegate_directory: PathBuf) -> ResultBtAny<Self> {
+        if !delegate_directory.is_dir() {
+            return Err(format!("No delegate storage at `{}`, has the store been \
+                mounted writable yet?", delegate_directory.to_string_lossy()).into());
+        }
+        Ok(Self { root: delegate_directory })
     }
 
-    pub fn get_delegate_directory(location_suffix: &PathBuf) -> PathBuf {
-        let mut delegate_directory = Self::get_location_prefix();
+    /// Where mounts without a store keep their content, keyed by the mount path.
+    pub fn get_delegate_directory(data_directory: &Path, location_suffix: &Path) -> PathBuf {
+        let mut delegate_directory = data_directory.join(Self::DELEGATE_DIRECTORY_NAME);
         delegate_directory.push(location_suffix.__strip_prefix("/"));
         delegate_directory
     }
@@ -106,11 +130,39 @@ impl TfsStorage for DelegateStorage {
         Ok(())
     }
 
+    /// Left to the kernel, so the delegate files share extents where the filesystem
+    /// they're on supports reflinks.
+    fn copy_range(&mut self, source_inode: &FileInode, source_position: u64,
+        target_inode: &FileInode, target_position: u64, length: u64) -> ResultBtAny<u64> {
+        let source_file = File::open(self.get_delegate_path(source_inode))?;
+        let target_file = OpenOptions::new()
+            .write(true)
+            .open(self.get_delegate_path(target_inode))?;
+        let mut source_position: i64 = source_position.try_into()?;
+        let mut target_position: i64 = target_position.try_into()?;
+        let copied_amount = copy_file_range(&source_file, Some(&mut source_position),
+            &target_file, Some(&mut target_position), length.try_into()?)?;
+        Ok(copied_amount.try_into()?)
+    }
+
     fn delete(&self, file_inode: &FileInode) -> ResultBtAny<()> {
         let delegate_path = self.get_delegate_path(file_inode);
         remove_file(delegate_path)
             .map_err(Into::into)
     }
+
+    /// Space of whichever filesystem the delegate directory is on.
+    fn get_space(&self) -> ResultBtAny<StorageSpace> {
+        let statistics = statvfs(&self.root)?;
+        Ok(StorageSpace {
+            block_size: statistics.block_size().try_into()?,
+            fragment_size: statistics.fragment_size().try_into()?,
+            total_blocks: statistics.blocks(),
+            free_blocks: statistics.blocks_free(),
+            available_blocks: statistics.blocks_available(),
+            free_files: statistics.files_free()
+        })
+    }
 }
 
 #[cfg(test)]
@@ -145,7 +197,24 @@ impl TfsStorage for StubStorage {
         Ok(())
     }
 
+    fn copy_range(&mut self, _source_inode: &FileInode, _source_position: u64,
+        _target_inode: &FileInode, _target_position: u64, _length: u64)
+    -> ResultBtAny<u64> {
+        Ok(0)
+    }
+
     fn delete(&self, _file_inode: &FileInode) -> ResultBtAny<()> {
         Ok(())
     }
+
+    fn get_space(&self) -> ResultBtAny<StorageSpace> {
+        Ok(StorageSpace {
+            block_size: 4096,
+            fragment_size: 4096,
+            total_blocks: 1000,
+            free_blocks: 500,
+            available_blocks: 400,
+            free_files: 100
+        })
+    }
 }
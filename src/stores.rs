@@ -0,0 +1,220 @@
+use std::{collections::BTreeMap, fs::{self, create_dir_all, File, OpenOptions},
+    io::{ErrorKind, Read, Seek, Write}, path::{Path, PathBuf}, process, time::SystemTime};
+
+use nix::{errno::Errno, fcntl::{Flock, FlockArg}};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument};
+
+use crate::{errors::{AnyError, ResultBtAny}, names::check_store_name,
+    snapshots::PersistentSnapshots, storage::DelegateStorage, wrappers::PathExt, ResultExt};
+
+/// Where a mount's snapshots and delegate storage are kept.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum StoreLocation {
+    /// Keyed by the mount path, so mounting elsewhere starts out empty. What mounts
+    /// without a store use.
+    MountPath(PathBuf),
+    /// Under the store's own directory, wherever it's mounted.
+    Named(String)
+}
+
+impl StoreLocation {
+    const STORES_DIRECTORY_NAME: &str = "stores";
+    const LOCK_FILENAME: &str = "tfs.lock";
+
+    pub fn get_store_directory(data_directory: &Path, store_name: &str) -> PathBuf {
+        data_directory.join(Self::STORES_DIRECTORY_NAME)
+            .join(store_name)
+    }
+
+    pub fn get_snapshot_directory(&self, data_directory: &Path) -> PathBuf {
+        match self {
+            Self::MountPath(mount_path) =>
+                PersistentSnapshots::get_snapshot_directory(data_directory, mount_path),
+            Self::Named(store_name) => Self::get_store_directory(data_directory, store_name)
+                .join(PersistentSnapshots::SNAPSHOT_DIRECTORY_NAME)
+        }
+    }
+
+    pub fn get_delegate_directory(&self, data_directory: &Path) -> PathBuf {
+        match self {
+            Self::MountPath(mount_path) =>
+                DelegateStorage::get_delegate_directory(data_directory, mount_path),
+            Self::Named(store_name) => Self::get_store_directory(data_directory, store_name)
+                .join(DelegateStorage::DELEGATE_DIRECTORY_NAME)
+        }
+    }
+
+    pub fn get_lock_path(&self, data_directory: &Path) -> PathBuf {
+        self.get_snapshot_directory(data_directory)
+            .join(Self::LOCK_FILENAME)
+    }
+
+    /// Relative to the log directory.
+    pub fn get_log_scoping(&self) -> PathBuf {
+        match self {
+            Self::MountPath(mount_path) => mount_path.__strip_prefix("/").to_path_buf(),
+            Self::Named(store_name) => PathBuf::from(Self::STORES_DIRECTORY_NAME)
+                .join(store_name)
+        }
+    }
+}
+
+/// Held for as long as a store is mounted, so no other mount writes to it too. The
+/// kernel releases it along with the process, so crashing doesn't leave it behind.
+#[derive(Debug)]
+pub struct StoreLock {
+    _lock: Flock<File>
+}
+
+impl StoreLock {
+    #[instrument]
+    pub fn try_acquire(lock_path: &Path) -> ResultBtAny<Self> {
+        if let Some(lock_directory) = lock_path.parent() {
+            create_dir_all(lock_directory)?;
+        }
+        let lock_file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(lock_path)?;
+        let mut lock = match Flock::lock(lock_file, FlockArg::LockExclusiveNonblock) {
+            Ok(lock) => lock,
+            Err((mut lock_file, Errno::EWOULDBLOCK)) => {
+                let mut holder_pid = String::new();
+                lock_file.read_to_string(&mut holder_pid)?;
+                Err(format!("`{}` is locked by process `{}`, which has it mounted.",
+                    lock_path.to_string_lossy(), holder_pid.trim()))?
+            },
+            Err((_, e)) => Err(e)?
+        };
+        // Only informational, for whoever finds it locked.
+        lock.set_len(0)?;
+        lock.rewind()?;
+        write!(lock, "{}", process::id())?;
+        info!("Locked `{}`.", lock_path.to_string_lossy());
+        Ok(Self { _lock: lock })
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct StoreRecord {
+    pub when_created: SystemTime,
+    /// Mount path the store's data was migrated from, if it was.
+    pub migrated_from: Option<PathBuf>
+}
+
+/// Names of the stores under a data directory, in `stores.json`.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Debug)]
+pub struct StoreRegistry {
+    stores: BTreeMap<String, StoreRecord>
+}
+
+impl StoreRegistry {
+    const REGISTRY_FILENAME: &str = "stores.json";
+
+    fn get_path(data_directory: &Path) -> PathBuf {
+        data_directory.join(Self::REGISTRY_FILENAME)
+    }
+
+    /// Falls back to no stores when there's no registry yet.
+    pub fn load(data_directory: &Path) -> ResultBtAny<Self> {
+        let registry_path = Self::get_path(data_directory);
+        match fs::read(&registry_path) {
+            Ok(as_json) => Ok(serde_json::from_slice(&as_json)?),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e)?
+        }
+    }
+
+    /// Written aside first, then renamed over the registry, as with snapshot pointers.
+    fn save(&self, data_directory: &Path) -> ResultBtAny<()> {
+        let registry_path = Self::get_path(data_directory);
+        let staging_path = PathBuf::from(format!("{}.staging",
+            registry_path.to_string_lossy()));
+        create_dir_all(data_directory)?;
+        fs::write(&staging_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&staging_path, &registry_path)?;
+        info!("Wrote store registry `{}`.", registry_path.to_string_lossy());
+        Ok(())
+    }
+
+    pub fn get(&self, store_name: &str) -> Option<&StoreRecord> {
+        self.stores.get(store_name)
+    }
+
+    pub fn get_all(&self) -> impl Iterator<Item = (&String, &StoreRecord)> {
+        self.stores.iter()
+    }
+
+    /// Where a mount of `store_name` keeps its data, which has to be registered.
+    pub fn get_location(&self, store_name: &str) -> ResultBtAny<StoreLocation> {
+        if self.get(store_name).is_none() {
+            Err(format!("Store `{store_name}` does not exist, it can be created with \
+                `tfs store create {store_name}`."))?;
+        }
+        Ok(StoreLocation::Named(store_name.to_string()))
+    }
+
+    fn check_if_free(&self, store_name: &str) -> ResultBtAny<()> {
+        check_store_name(store_name)
+            .map_err_inner(AnyError::from)?;
+        if self.get(store_name).is_some() {
+            Err(format!("Store `{store_name}` already exists."))?;
+        }
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub fn create(&mut self, data_directory: &Path, store_name: &str) -> ResultBtAny<()> {
+        self.check_if_free(store_name)?;
+        let store_location = StoreLocation::Named(store_name.to_string());
+        create_dir_all(store_location.get_snapshot_directory(data_directory))?;
+        create_dir_all(store_location.get_delegate_directory(data_directory))?;
+        self.stores.insert(store_name.to_string(), StoreRecord {
+            when_created: SystemTime::now(),
+            migrated_from: None
+        });
+        self.save(data_directory)
+    }
+
+    /// Moves the data that was kept for `mount_path` into a new store, so it can be
+    /// mounted anywhere from then on.
+    #[instrument(skip(self))]
+    pub fn migrate(&mut self, data_directory: &Path, store_name: &str, mount_path: &Path)
+    -> ResultBtAny<()> {
+        self.check_if_free(store_name)?;
+        let old_location = StoreLocation::MountPath(mount_path.to_path_buf());
+        let old_snapshot_directory = old_location.get_snapshot_directory(data_directory);
+        if !old_snapshot_directory.try_exists()? {
+            Err(format!("There's no data kept for mount `{}`.",
+                mount_path.to_string_lossy()))?;
+        }
+        let _old_lock = StoreLock::try_acquire(&old_location.get_lock_path(data_directory))?;
+
+        let new_location = StoreLocation::Named(store_name.to_string());
+        let moves = [
+            (old_snapshot_directory, new_location.get_snapshot_directory(data_directory)),
+            (old_location.get_delegate_directory(data_directory),
+                new_location.get_delegate_directory(data_directory))
+        ];
+        for (from_directory, to_directory) in moves {
+            if let Some(to_parent) = to_directory.parent() {
+                create_dir_all(to_parent)?;
+            }
+            if from_directory.try_exists()? {
+                fs::rename(&from_directory, &to_directory)?;
+                info!("Moved `{}` to `{}`.", from_directory.to_string_lossy(),
+                    to_directory.to_string_lossy());
+            } else {
+                create_dir_all(&to_directory)?;
+            }
+        }
+        self.stores.insert(store_name.to_string(), StoreRecord {
+            when_created: SystemTime::now(),
+            migrated_from: Some(mount_path.to_path_buf())
+        });
+        self.save(data_directory)
+    }
+}
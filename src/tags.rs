@@ -1,12 +1,17 @@
-use std::{collections::HashMap, fmt::Display, time::SystemTime};
+use std::{cmp::Ordering, collections::{BTreeMap, HashMap}, fmt::Display, ops::Bound,
+    time::SystemTime};
 
 use bon::{builder, Builder};
 use fuser::FileType;
 
-use crate::{entries::TfsEntry, errors::ResultBtAny, inodes::TagInode, wrappers::write_iter};
+use crate::{entries::TfsEntry, errors::{ResultBt, ResultBtAny},
+    inodes::{TagInode, TagInodes}, names::{check_tag_name, NameError}, queries::{TagComparison, TagPredicate},
+    wrappers::write_iter};
 
-#[derive(Builder, PartialEq, Eq, PartialOrd, Ord, Debug)]
-#[builder(on(String, into))]
+pub const KEY_VALUE_SEPARATOR: char = ':';
+
+#[derive(Builder, PartialEq, Eq, PartialOrd, Ord, Debug, Clone)]
+#[builder(on(String, into), finish_fn(name = build_unchecked, vis = ""))]
 pub struct TfsTag {
     pub name: String,
     pub inode: TagInode,
@@ -24,6 +29,30 @@ pub struct TfsTag {
     pub when_created: SystemTime
 }
 
+impl<S: tfs_tag_builder::IsComplete> TfsTagBuilder<S> {
+    /// Builds the tag, as long as it has a valid name.
+    pub fn build(self) -> ResultBt<TfsTag, NameError> {
+        let tfs_tag = self.build_unchecked();
+        check_tag_name(&tfs_tag.name)?;
+        Ok(tfs_tag)
+    }
+}
+
+impl TfsTag {
+    /// Splits tags like `year:2024` into their key and value.
+    pub fn get_key_value(&self) -> Option<(&str, TagValue)> {
+        get_key_value(&self.name)
+    }
+}
+
+pub fn get_key_value(tag_name: &str) -> Option<(&str, TagValue)> {
+    let (key, value) = tag_name.split_once(KEY_VALUE_SEPARATOR)?;
+    if key.is_empty() || value.is_empty() {
+        return None;
+    }
+    Some((key, TagValue::from(value)))
+}
+
 impl TfsEntry for TfsTag {
     fn get_name(&self) -> &str {
         &self.name
@@ -72,13 +101,62 @@ impl Display for TfsTag {
     }
 }
 
+/// The value half of a key/value tag. Numbers order numerically and before
+/// anything else, otherwise values order as strings.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct TagValue(String);
+
+impl TagValue {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn as_number(&self) -> Option<f64> {
+        self.0.parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite())
+    }
+}
+
+impl From<&str> for TagValue {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl Ord for TagValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.as_number(), other.as_number()) {
+            (Some(this), Some(that)) => this.total_cmp(&that)
+                .then_with(|| self.0.cmp(&other.0)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => self.0.cmp(&other.0)
+        }
+    }
+}
+
+impl PartialOrd for TagValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for TagValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 type ByInode = HashMap<TagInode, TfsTag>;
 type ByName = HashMap<String, TagInode>;
+type ByKey = HashMap<String, BTreeMap<TagValue, TagInode>>;
 
 #[derive(Debug)]
 pub struct IndexedTags {
     tags: ByInode,
-    by_name: ByName
+    by_name: ByName,
+    by_key: ByKey
 }
 
 impl IndexedTags {
@@ -86,6 +164,7 @@ impl IndexedTags {
         Self {
             tags: ByInode::new(),
             by_name: ByName::new(),
+            by_key: ByKey::new()
         }
     }
 
@@ -99,6 +178,24 @@ impl IndexedTags {
             .ok_or(format!("Tag with inode `{tag_inode}` does not exist.").into())
     }
 
+    fn get_value_range(tag_predicate: &TagPredicate) -> (Bound<TagValue>, Bound<TagValue>) {
+        // Numbers are widened by the smallest step, as the same number can be
+        // spelt differently (e.g., `2020` and `2020.0`).
+        let (lower_value, upper_value) = match tag_predicate.value.as_number() {
+            Some(number) => (
+                TagValue::from(number.next_down().to_string().as_str()),
+                TagValue::from(number.next_up().to_string().as_str())),
+            None => (tag_predicate.value.clone(), tag_predicate.value.clone())
+        };
+        match tag_predicate.comparison {
+            TagComparison::Present => (Bound::Unbounded, Bound::Unbounded),
+            TagComparison::Greater | TagComparison::GreaterOrEqual =>
+                (Bound::Included(lower_value), Bound::Unbounded),
+            TagComparison::Less | TagComparison::LessOrEqual =>
+                (Bound::Unbounded, Bound::Included(upper_value))
+        }
+    }
+
     fn get_by_inode_mut(&mut self, tag_inode: &TagInode) -> Option<&mut TfsTag> {
         self.tags.get_mut(tag_inode)
     }
@@ -117,6 +214,23 @@ impl IndexedTags {
         self.tags.values()
     }
 
+    pub fn get_is_key(&self, tag_key: &str) -> bool {
+        self.by_key.contains_key(tag_key)
+    }
+
+    pub fn get_by_predicate(&self, tag_predicate: &TagPredicate) -> TagInodes {
+        let Some(key_values) = self.by_key.get(&tag_predicate.key) else {
+            return TagInodes::new();
+        };
+        let value_range = Self::get_value_range(tag_predicate);
+        // The index only narrows the search, values equal to the bounds and of
+        // the wrong kind (i.e., words vs. numbers) are settled by the predicate.
+        key_values.range::<TagValue, _>(value_range)
+            .filter(|(candidate, _)| tag_predicate.is_satisfied_by(candidate))
+            .map(|(_, inode)| *inode)
+            .into()
+    }
+
     fn get_all_mut(&mut self) -> impl Iterator<Item = &mut TfsTag> {
         self.tags.values_mut()
     }
@@ -125,11 +239,6 @@ impl IndexedTags {
         self.tags.keys()
     }
 
-    pub fn get_free_inode(&self) -> ResultBtAny<TagInode> {
-        let inodes_inuse = self.get_inuse_inodes();
-        TagInode::try_from_free_inodes(inodes_inuse)
-    }
-
     pub fn do_by_inode<T>(&mut self, tag_inode: &TagInode, to_do: impl FnOnce(TagUpdate) -> T)
     -> ResultBtAny<T> {
         self.do_or_rollback(tag_inode, to_do)
@@ -187,6 +296,11 @@ impl IndexedTags {
         let inode = to_add.inode;
         let name = to_add.name.clone();
 
+        if let Some((key, value)) = get_key_value(&name) {
+            _ = self.by_key.entry(key.to_string())
+                .or_default()
+                .insert(value, inode);
+        }
         _ = self.tags.insert(inode, to_add);
         _ = self.by_name.insert(name, inode);
 
@@ -197,6 +311,14 @@ impl IndexedTags {
     pub fn remove_by_inode(&mut self, tag_inode: &TagInode) -> Option<TfsTag> {
         let to_remove = self.tags.remove(tag_inode)?;
         _ = self.by_name.remove(&to_remove.name);
+        if let Some((key, value)) = to_remove.get_key_value()
+            && let Some(key_values) = self.by_key.get_mut(key)
+        {
+            _ = key_values.remove(&value);
+            if key_values.is_empty() {
+                _ = self.by_key.remove(key);
+            }
+        }
         Some(to_remove)
     }
 
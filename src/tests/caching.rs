@@ -0,0 +1,53 @@
+use std::{path::Path, time::Duration};
+
+use fuser::FUSE_ROOT_ID;
+
+use crate::{caching::{CachingPolicy, EntryTtls, NO_TTL},
+    cli::mount::plain::CachingArguments, configuration::TfsConfiguration};
+
+#[test]
+fn configuring_caching_per_mount() {
+    let tfs_configuration = TfsConfiguration::parse(r#"
+        [mounts."/home/user/tags".caching]
+        keep_cache = false
+        readdir_plus = true
+
+        [mounts."/home/user/tags".caching.ttls]
+        file = "0s"
+        namespace = "1m 30s"
+    "#).unwrap();
+
+    let caching = tfs_configuration.get_mount(Path::new("/home/user/tags")).caching;
+    assert_eq!(caching, CachingPolicy {
+        ttls: EntryTtls {
+            file: NO_TTL,
+            namespace: Duration::from_secs(90),
+            ..EntryTtls::default()
+        },
+        keep_cache: false,
+        readdir_plus: true,
+        ..CachingPolicy::default()
+    });
+    assert_eq!(tfs_configuration.get_mount(Path::new("/home/user/other")).caching,
+        CachingPolicy::default());
+
+    let caching = CachingArguments {
+        tag_ttl: Some(Duration::from_secs(5)),
+        readdir_plus: Some(false),
+        ..CachingArguments::default()
+    }.apply(caching);
+    assert_eq!(caching.ttls.get(FUSE_ROOT_ID), EntryTtls::default().root);
+    assert_eq!(caching.ttls.get(3), NO_TTL);
+    assert_eq!(caching.ttls.get(4), Duration::from_secs(5));
+    assert_eq!(caching.ttls.get(5), Duration::from_secs(90));
+    assert!(!caching.readdir_plus);
+
+    TfsConfiguration::parse(r#"
+        [mounts."/home/user/tags".caching]
+        keep_cach = false
+    "#).expect_err("To reject unknown settings.");
+    TfsConfiguration::parse(r#"
+        [mounts."/home/user/tags".caching.ttls]
+        file = "soon"
+    "#).expect_err("To reject durations that can't be parsed.");
+}
@@ -0,0 +1,67 @@
+use std::{path::{Path, PathBuf}, time::Duration};
+
+use clap::Parser;
+
+use crate::{caching::NO_TTL, cli::{config::show::ShowParameters, ProgramParameters,
+    ProgramSubcommands}, configuration::{MountOptionsConfiguration, TfsConfiguration}};
+
+#[test]
+fn layering_configuration() {
+    let tfs_configuration = TfsConfiguration::parse(r#"
+        [paths]
+        data_directory = "/srv/tfs"
+
+        [persistence]
+        persist_interval = "30s"
+        trash_retention = "7days"
+
+        [logging]
+        directives = "tag_filesystem::fuse=debug"
+
+        [mounts."/home/user/tags".persistence]
+        loop_interval = "2s"
+
+        [mounts."/home/user/tags".caching.ttls]
+        file = "0s"
+    "#).unwrap();
+
+    let mount_configuration = tfs_configuration.get_mount(Path::new("/home/user/tags"));
+    assert_eq!(mount_configuration.paths.get_data_directory(), PathBuf::from("/srv/tfs"));
+    assert_eq!(mount_configuration.persistence.loop_interval, Duration::from_secs(2));
+    assert_eq!(mount_configuration.persistence.persist_interval, Duration::from_secs(30));
+    assert_eq!(mount_configuration.persistence.trash_retention,
+        Duration::from_secs(7 * 24 * 60 * 60));
+    assert_eq!(mount_configuration.logging.directives, "tag_filesystem::fuse=debug");
+    assert_eq!(mount_configuration.caching.ttls.file, NO_TTL);
+    assert_eq!(tfs_configuration.get_mount(Path::new("/home/user/other")),
+        tfs_configuration.global);
+    assert_eq!(tfs_configuration.global.persistence.loop_interval, Duration::from_secs(1));
+
+    let mut program_arguments = ProgramParameters::parse_from(["tfs", "mount",
+        "/home/user/tags", "--persist-interval", "1m", "--allow-root", "false",
+        "--log-max-size", "1000", "plain"]);
+    program_arguments.configuration = tfs_configuration;
+    let ProgramSubcommands::Mount(mount_arguments) = &program_arguments.subcommand else {
+        panic!("To have parsed the mount subcommand.");
+    };
+    let mount_configuration = mount_arguments.get_configuration(&program_arguments);
+    assert_eq!(mount_configuration.persistence.loop_interval, Duration::from_secs(2));
+    assert_eq!(mount_configuration.persistence.persist_interval, Duration::from_secs(60));
+    assert!(!mount_configuration.mount_options.allow_root);
+    assert_eq!(mount_configuration.logging.max_size, 1000);
+
+    // What's shown can be read back in as the same settings.
+    let show_arguments = ShowParameters { mount_path: Some(PathBuf::from("/home/user/tags")) };
+    let shown_configuration = show_arguments.get_effective_configuration(&program_arguments)
+        .unwrap();
+    assert_eq!(TfsConfiguration::parse(&shown_configuration).unwrap().global,
+        program_arguments.configuration.get_mount(Path::new("/home/user/tags")));
+
+    TfsConfiguration::parse(r#"
+        [mounts."/home/user/tags".persistence]
+        persist_every = "1s"
+    "#).expect_err("To reject unknown settings within mounts.");
+    MountOptionsConfiguration { allow_other: true, ..MountOptionsConfiguration::default() }
+        .get_mount_options()
+        .expect_err("To reject both `allow_root` and `allow_other`.");
+}
@@ -0,0 +1,37 @@
+use crate::{contents::{get_terms, IndexedContents, TfsContent}, inodes::FileInode};
+
+#[test]
+fn getting_terms() {
+    assert_eq!(get_terms("The deadline's (soon), DEADLINE!").collect::<Vec<_>>(),
+        ["the", "deadline", "s", "soon", "deadline"]);
+    assert_eq!(get_terms(" -- ").count(), 0);
+}
+
+#[test]
+fn indexing_contents() {
+    let file_inodes: Vec<FileInode> = [3, 6, 9].into_iter()
+        .map(|inode_id| inode_id.try_into().unwrap())
+        .collect();
+    let mut indexed_contents = IndexedContents::new();
+    indexed_contents.add(TfsContent::from_text(file_inodes[0], "deadline on friday"))
+        .unwrap();
+    indexed_contents.add(TfsContent::from_text(file_inodes[1], "no deadline"))
+        .unwrap();
+    indexed_contents.add(TfsContent::from_text(file_inodes[2], "friday"))
+        .unwrap();
+    indexed_contents.add(TfsContent::from_text(file_inodes[2], "again"))
+        .expect_err("To have already indexed the file.");
+
+    let terms = |search_string: &str| get_terms(search_string).collect::<Vec<_>>();
+    assert_eq!(indexed_contents.get_by_terms(&terms("deadline")),
+        [file_inodes[0], file_inodes[1]].into());
+    assert_eq!(indexed_contents.get_by_terms(&terms("Friday deadline")),
+        [file_inodes[0]].into());
+    assert!(indexed_contents.get_by_terms(&terms("monday")).is_empty());
+    assert!(indexed_contents.get_is_match(&file_inodes[1], &terms("no")));
+    assert!(!indexed_contents.get_is_match(&file_inodes[1], &terms("no friday")));
+
+    indexed_contents.remove_by_inode(&file_inodes[0]);
+    assert_eq!(indexed_contents.get_by_terms(&terms("deadline")), [file_inodes[1]].into());
+    assert!(indexed_contents.get_by_terms(&terms("on")).is_empty());
+}
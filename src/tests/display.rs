@@ -1,5 +1,5 @@
-use crate::{entries::TfsEntry, files::TfsFile, filesystem::TagFilesystem, inodes::{FileInode,
-    TagInode, TagInodes}, tags::TfsTag};
+use crate::{entries::TfsEntry, files::TfsFile, filesystem::{RenameMode, TagFilesystem},
+    inodes::{FileInode, TagInode, TagInodes}, tags::TfsTag};
 
 #[test]
 fn displaying_file_things() {
@@ -11,7 +11,7 @@ fn displaying_file_things() {
         .inode(file_inode)
         .owner(1001)
         .group(1001)
-        .build();
+        .build().unwrap();
     assert_eq!(format!("{}", &tfs_file as &dyn TfsEntry),
         "test_file(id=6)");
 
@@ -36,7 +36,7 @@ fn displaying_tag_filesystem() {
         .inode(FileInode::try_from(3).unwrap())
         .owner(1000)
         .group(1000)
-        .build());
+        .build().unwrap());
     assert_eq!(format!("{}", tag_filesystem),
         "TagFilesystem(files=[file_1(id=3, tags={})], tags=[], namespaces=[])");
 
@@ -45,7 +45,7 @@ fn displaying_tag_filesystem() {
         .inode(TagInode::try_from(4).unwrap())
         .owner(1000)
         .group(1000)
-        .build());
+        .build().unwrap());
     assert_eq!(format!("{}", tag_filesystem),
         "TagFilesystem(files=[file_1(id=3, tags={})], \
             tags=[tag_1(id=4)], \
@@ -62,7 +62,7 @@ fn displaying_tag_filesystem() {
 
     tag_filesystem.move_file(
         &TagInodes::new(), "file_1",
-        TagInode::try_from(4).unwrap(), String::from("file_1"));
+        TagInode::try_from(4).unwrap(), String::from("file_1"), RenameMode::Replace);
     assert_eq!(format!("{}", tag_filesystem),
         format!("TagFilesystem(\
             files=[file_1(id=3, tags={{ 4 }})], \
@@ -0,0 +1,71 @@
+use std::{fs, io::Write};
+
+use askama::Template;
+use clap::Parser;
+use tempfile::tempdir;
+
+use crate::{cli::tags::setup::{ChangeTemplate, SetupParameters},
+    doctor::{check_change_script, check_directory, check_store, Severity},
+    snapshots::{PersistentSnapshots, TfsSnapshots},
+    stores::{StoreLocation, StoreRegistry}, tests::tracing::setup_tracing};
+
+#[test]
+fn diagnosing_stores() {
+    setup_tracing();
+    let data_directory = tempdir().unwrap();
+    let data_directory = data_directory.path();
+    StoreRegistry::load(data_directory).unwrap()
+        .create(data_directory, "work")
+        .unwrap();
+    let get_severities = || check_store("work", data_directory).into_iter()
+        .map(|diagnosis| diagnosis.severity)
+        .collect::<Vec<_>>();
+    assert_eq!(get_severities(), [Severity::Healthy, Severity::Healthy]);
+
+    let store_location = StoreLocation::Named(String::from("work"));
+    let snapshot_directory = store_location.get_snapshot_directory(data_directory);
+    let snapshots = PersistentSnapshots::try_new(snapshot_directory.clone()).unwrap();
+    snapshots.create_staging().unwrap()
+        .write_all(b"snapshot")
+        .unwrap();
+    snapshots.promote_staging().unwrap();
+    assert_eq!(get_severities(), [Severity::Healthy, Severity::Healthy]);
+
+    let snapshot_path = fs::read_dir(&snapshot_directory).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.to_string_lossy().ends_with("tfs.snapshot.blue"))
+        .unwrap();
+    fs::write(snapshot_path, "corrupted").unwrap();
+    fs::remove_dir(store_location.get_delegate_directory(data_directory)).unwrap();
+    assert_eq!(get_severities(), [Severity::Problem, Severity::Problem]);
+}
+
+#[test]
+fn diagnosing_change_scripts() {
+    setup_tracing();
+    let script_directory = tempdir().unwrap();
+    let script_path = script_directory.path().join("change_tags.sh");
+    assert_eq!(check_change_script(&script_path).severity, Severity::Warning);
+
+    let setup_arguments = SetupParameters::parse_from(["setup", "--script-path",
+        &script_path.to_string_lossy()]);
+    let script = ChangeTemplate::try_from(&setup_arguments).unwrap()
+        .render()
+        .unwrap();
+    fs::write(&script_path, &script).unwrap();
+    assert_eq!(check_change_script(&script_path).severity, Severity::Healthy);
+
+    fs::write(&script_path, script.replace(env!("CARGO_PKG_VERSION"), "0.0.0")).unwrap();
+    assert_eq!(check_change_script(&script_path).severity, Severity::Warning);
+}
+
+#[test]
+fn diagnosing_directories() {
+    setup_tracing();
+    let directory = tempdir().unwrap();
+    assert_eq!(check_directory("Data directory", directory.path()).severity,
+        Severity::Healthy);
+    let file_path = directory.path().join("file");
+    fs::write(&file_path, "").unwrap();
+    assert_eq!(check_directory("Data directory", &file_path).severity, Severity::Problem);
+}
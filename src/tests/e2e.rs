@@ -1,13 +1,18 @@
 use std::{env::{current_dir, set_current_dir}, error::Error, ffi::{OsStr, OsString}, fs::{self,
     rename, File, OpenOptions}, io::{stdout, Write}, path::PathBuf, process::{self, Command,
-    ExitStatus, Stdio}};
+    ExitStatus, Stdio}, thread::sleep, time::Duration};
 
 use clap::Parser;
+use libc::{EEXIST, EROFS};
+use nix::fcntl::{copy_file_range, renameat2, RenameFlags};
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::EnvFilter;
+use users::{get_current_gid, get_current_uid};
 
-use crate::{errors::ResultBtAny, tests::{fixtures::with_tfs_mount, tracing::setup_tracing},
-    wrappers::VecWrapper};
+use crate::{caching::{CachingPolicy, EntryTtls}, errors::ResultBtAny, files::TfsFile,
+    inodes::TagInodes, tags::TfsTag,
+    tests::{fixtures::{with_caching_tfs_mount, with_prepared_tfs_mount, with_tfs_mount},
+    tracing::setup_tracing}, wrappers::VecWrapper};
 
 #[test]
 fn listing_files_and_tags() {
@@ -42,7 +47,7 @@ fn listing_files_and_tags() {
 
         let output = cmd("ls").arg(mount_directory.join("tag_1"))
             .run_and_log()?;
-        assert_eq!(output, "");
+        assert_eq!(output, "file_2\ntag_1\ntag_2\n");
 
         let output = cmd("ls").arg(mount_directory.join("{ tag_1 }"))
             .run_and_log()?;
@@ -63,7 +68,7 @@ fn listing_files_and_tags() {
 
         let output = cmd("ls").arg(mount_directory.join("{ tag_1 }").join("tag_1"))
             .run_and_log()?;
-        assert_eq!(output, "");
+        assert_eq!(output, "file_2\ntag_1\ntag_2\n");
 
         Ok(())
     }).unwrap();
@@ -96,6 +101,236 @@ fn listing_namespace_to_show_neighbour_tags() {
     }).unwrap();
 }
 
+#[test]
+fn spelling_namespaces_differently() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir")
+            .arg(mount_directory.join("tag_1"))
+            .arg(mount_directory.join("tag_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("stat").args(["-c", "%i"])
+            .arg(mount_directory.join("{tag_2,tag_1}"))
+            .arg(mount_directory.join("{ tag_1, tag_2 }"))
+            .arg(mount_directory.join("{tag_1 , tag_2}"))
+            .run_and_log()?;
+        let namespace_inodes: Vec<_> = output.lines().collect();
+        assert_eq!(namespace_inodes.len(), 3);
+        assert!(namespace_inodes.iter().all(|inode| *inode == namespace_inodes[0]));
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn listing_namespaces_with_comparisons() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir")
+            .arg(mount_directory.join("year:2019"))
+            .arg(mount_directory.join("year:2021"))
+            .arg(mount_directory.join("year:2022"))
+            .arg(mount_directory.join("rating:5"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("touch")
+            .arg(mount_directory.join("{ year:2019 }").join("file_1"))
+            .arg(mount_directory.join("{ year:2021 }").join("file_2"))
+            .arg(mount_directory.join("{ rating:5, year:2022 }").join("file_3"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("ls").arg(mount_directory.join("{ year>=2020 }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_2\nrating:5\nyear:2021\nyear:2022\n");
+
+        let output = cmd("ls").arg(mount_directory.join("{ rating:5, year>=2020 }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_3\nrating:5\nyear:2022\n");
+
+        let output = cmd("ls").arg(mount_directory.join("{ year }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_1\nfile_2\nrating:5\nyear:2019\nyear:2021\nyear:2022\n");
+
+        cmd("touch").arg(mount_directory.join("{ year>=2020 }").join("file_4"))
+            .run_and_log()
+            .expect_err("To not create files under a namespace with comparisons.");
+        cmd("ls").arg(mount_directory.join("{ month }"))
+            .run_and_log()
+            .expect_err("To not have any tags with the key `month`.");
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn refusing_namesakes_under_namespaces() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir")
+            .arg(mount_directory.join("year:2021"))
+            .arg(mount_directory.join("year:2022"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("touch")
+            .arg(mount_directory.join("{ year:2021 }").join("file_1"))
+            .arg(mount_directory.join("{ year:2022 }").join("file_1"))
+            .arg(mount_directory.join("{ year:2022 }").join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("ls").arg(mount_directory.join("{ year>=2020 }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_2\nyear:2021\nyear:2022\n");
+        cmd("stat").arg(mount_directory.join("{ year>=2020 }").join("file_1"))
+            .run_and_log()
+            .expect_err("To refuse a name shared by two files under the namespace.");
+        let output = cmd("stat").args(["-c", "%n"])
+            .arg(mount_directory.join("{ year:2021 }").join("file_1"))
+            .run_and_log()?;
+        assert!(output.ends_with("file_1\n"));
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn listing_namespaces_with_metadata_predicates() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir").arg(mount_directory.join("report"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("touch")
+            .arg(mount_directory.join("{ report }").join("file_1.pdf"))
+            .arg(mount_directory.join("{ report }").join("file_2.txt"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let echo_into = OpenOptions::new()
+            .write(true)
+            .open(mount_directory.join("{ report }").join("file_2.txt"))?;
+        let output = cmd("echo").arg("abcdefghij")
+            .stdout(echo_into)
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("ls").arg(mount_directory.join("{ report, name:*.pdf }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_1.pdf\nreport\n");
+
+        let output = cmd("ls").arg(mount_directory.join("{ report, size>10 }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_2.txt\nreport\n");
+
+        let output = cmd("ls").arg(mount_directory.join("{ report, modified<1d }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_1.pdf\nfile_2.txt\nreport\n");
+
+        let output = cmd("ls").arg(mount_directory.join("{ report, modified>1d }"))
+            .run_and_log()?;
+        assert_eq!(output, "report\n");
+
+        cmd("ls").arg(mount_directory.join("{ report, size>ten }"))
+            .run_and_log()
+            .expect_err("To not be able to parse the size.");
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn expiring_files_under_time_based_namespaces() {
+    setup_tracing();
+
+    let caching = CachingPolicy {
+        ttls: EntryTtls {
+            root: Duration::from_secs(60),
+            file: Duration::from_secs(60),
+            tag: Duration::from_secs(60),
+            namespace: Duration::from_secs(60)
+        },
+        ..CachingPolicy::default()
+    };
+    with_caching_tfs_mount(caching, |mount_directory| {
+        let output = cmd("mkdir").arg(mount_directory.join("report"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("touch").arg(mount_directory.join("{ report }").join("file_1"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let file_path = mount_directory.join("{ report, modified<1s }").join("file_1");
+        assert!(file_path.exists());
+        sleep(Duration::from_millis(1500));
+        // Cached for the file TTL, it'd still be found.
+        assert!(!file_path.exists());
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn listing_namespaces_with_content_search() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir").arg(mount_directory.join("notes"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("touch")
+            .arg(mount_directory.join("{ notes }").join("file_1"))
+            .arg(mount_directory.join("{ notes }").join("file_2"))
+            .arg(mount_directory.join("file_3"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        for (file_path, file_text) in [
+            (mount_directory.join("{ notes }").join("file_1"), "The deadline is Friday."),
+            (mount_directory.join("{ notes }").join("file_2"), "Nothing due."),
+            (mount_directory.join("file_3"), "Another deadline.")
+        ] {
+            let echo_into = OpenOptions::new()
+                .write(true)
+                .open(file_path)?;
+            let output = cmd("echo").arg(file_text)
+                .stdout(echo_into)
+                .run_and_log()?;
+            assert_eq!(output, "");
+        }
+
+        let output = cmd("ls").arg(mount_directory.join("{ notes, ?\"deadline\" }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_1\nnotes\n");
+
+        let output = cmd("ls").arg(mount_directory.join("{ ?\"deadline\" }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_3\nnotes\n");
+
+        let output = cmd("ls").arg(mount_directory.join("{ notes, ?\"friday deadline\" }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_1\nnotes\n");
+
+        let output = cmd("rm").arg(mount_directory.join("{ notes }").join("file_1"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("ls").arg(mount_directory.join("{ notes, ?\"deadline\" }"))
+            .run_and_log()?;
+        assert_eq!(output, "notes\n");
+
+        Ok(())
+    }).unwrap();
+}
+
 #[test]
 fn creating_files() {
     setup_tracing();
@@ -182,6 +417,68 @@ fn creating_duplicate_tags() {
     }).unwrap();
 }
 
+#[test]
+fn creating_conflicting_and_invalid_names() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir").arg(mount_directory.join("tag_1"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("touch").arg(mount_directory.join("file_1"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        cmd("mkdir").arg(mount_directory.join("file_1"))
+            .run_and_log()
+            .expect_err("To refuse a tag named the same as `file_1`.");
+        cmd("mkdir").arg(mount_directory.join("tag_2, tag_3"))
+            .run_and_log()
+            .expect_err("To refuse a tag that can't be queried.");
+        cmd("touch").arg(mount_directory.join("{ tag_1 }").join("{ file_2 }"))
+            .run_and_log()
+            .expect_err("To refuse a file that looks like a namespace.");
+        cmd("mv")
+            .arg(mount_directory.join("tag_1"))
+            .arg(mount_directory.join("tag<1"))
+            .run_and_log()
+            .expect_err("To refuse renaming a tag to something that can't be queried.");
+
+        let output = cmd("ls").arg(mount_directory)
+            .run_and_log()?;
+        assert_eq!(output, "file_1\ntag_1\n");
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn reporting_failures() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let last_error_path = mount_directory.join(".tfs").join("last_error");
+        assert_eq!(fs::read_to_string(&last_error_path)?, "");
+        fs::create_dir(mount_directory.join("tag_1"))?;
+        fs::create_dir(mount_directory.join("tag_2, tag_3"))
+            .expect_err("To refuse a tag that can't be queried.");
+
+        let failures = fs::read_to_string(&last_error_path)?;
+        assert!(failures.starts_with(&format!("Process `{}`", process::id())));
+        assert!(failures.contains("mkdir failed with errno"));
+        let output = cmd("ls").arg(mount_directory.join(".tfs"))
+            .run_and_log()?;
+        assert_eq!(output, "last_error\n");
+        let output = cmd("ls").arg(mount_directory)
+            .run_and_log()?;
+        assert_eq!(output, "tag_1\n");
+        fs::create_dir(mount_directory.join(".tfs"))
+            .expect_err("To refuse a tag named `.tfs`.");
+
+        Ok(())
+    }).unwrap();
+}
+
 #[test]
 fn writing_and_reading_to_files() {
     setup_tracing();
@@ -208,6 +505,32 @@ fn writing_and_reading_to_files() {
     }).unwrap();
 }
 
+#[test]
+fn copying_ranges_between_files() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        fs::write(mount_directory.join("{}").join("file_1"), "abcdefghij\n")?;
+        let output = cmd("touch").arg(mount_directory.join("{}").join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let source_file = File::open(mount_directory.join("{}").join("file_1"))?;
+        let target_file = OpenOptions::new()
+            .write(true)
+            .open(mount_directory.join("{}").join("file_2"))?;
+        let copied_amount = copy_file_range(&source_file, Some(&mut 2), &target_file,
+            Some(&mut 0), 5)?;
+        assert_eq!(copied_amount, 5);
+
+        let output = cmd("cat").arg(mount_directory.join("{}").join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "cdefg");
+
+        Ok(())
+    }).unwrap();
+}
+
 #[test]
 fn removing_file() {
     setup_tracing();
@@ -301,50 +624,605 @@ fn removing_nonexistent_tag() {
 }
 
 #[test]
-fn doing_random_chained_interactions() {
+fn removing_tags_carried_by_files() {
     setup_tracing();
 
     with_tfs_mount(|mount_directory| {
         let output = cmd("mkdir")
             .arg(mount_directory.join("tag_1"))
             .arg(mount_directory.join("tag_2"))
-            .arg(mount_directory.join("tag_3"))
-            .run_and_log()?;
-        assert_eq!(output, "");
-        let output = cmd("rmdir").arg(mount_directory.join("tag_2"))
             .run_and_log()?;
         assert_eq!(output, "");
         let output = cmd("touch").arg(mount_directory.join("{ tag_1 }").join("file_1"))
             .run_and_log()?;
         assert_eq!(output, "");
-        let output = cmd("touch").arg(mount_directory.join("file_2"))
+
+        cmd("rmdir").arg(mount_directory.join("tag_1"))
+            .run_and_log()
+            .expect_err("To refuse deleting a tag that's on `file_1`.");
+        let output = cmd("rmdir").arg(mount_directory.join("tag_2"))
             .run_and_log()?;
         assert_eq!(output, "");
+        let output = cmd("ls").arg(mount_directory)
+            .run_and_log()?;
+        assert_eq!(output, "tag_1\n");
 
-        let output = cmd("ls").arg(mount_directory.join("{ tag_1 }"))
+        let output = cmd("mv")
+            .arg(mount_directory.join("tag_1"))
+            .arg(mount_directory.join("{ .trash }").join("tag_1"))
             .run_and_log()?;
-        assert_eq!(output, "file_1\ntag_1\n");
+        assert_eq!(output, "");
         let output = cmd("ls").arg(mount_directory)
             .run_and_log()?;
-        assert_eq!(output, "file_2\ntag_1\ntag_3\n");
+        assert_eq!(output, "file_1\n");
 
-        let output = cmd("mkdir").arg(mount_directory.join("tag_4"))
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn trashing_and_restoring() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir").arg(mount_directory.join("tag_1"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("touch")
+            .arg(mount_directory.join("{ tag_1 }").join("file_1"))
+            .arg(mount_directory.join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let echo_into = OpenOptions::new()
+            .write(true)
+            .open(mount_directory.join("{ tag_1 }").join("file_1"))?;
+        let output = cmd("echo").arg("abcdefghij")
+            .stdout(echo_into)
             .run_and_log()?;
         assert_eq!(output, "");
 
+        let output = cmd("rm")
+            .arg(mount_directory.join("{ tag_1 }").join("file_1"))
+            .arg(mount_directory.join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("rmdir").arg(mount_directory.join("tag_1"))
+            .run_and_log()?;
+        assert_eq!(output, "");
         let output = cmd("ls").arg(mount_directory)
             .run_and_log()?;
-        assert_eq!(output, "file_2\ntag_1\ntag_3\ntag_4\n");
+        assert_eq!(output, "");
+        let output = cmd("ls").arg(mount_directory.join("{ .trash }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_1\nfile_2\ntag_1\n");
 
-        let output = cmd("rmdir").arg(mount_directory.join("tag_4"))
+        let output = cmd("mv")
+            .arg(mount_directory.join("{ .trash }").join("tag_1"))
+            .arg(mount_directory.join("tag_1"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("mv")
+            .arg(mount_directory.join("{ .trash }").join("file_1"))
+            .arg(mount_directory.join("file_1"))
             .run_and_log()?;
         assert_eq!(output, "");
+        let output = cmd("cat").arg(mount_directory.join("{ tag_1 }").join("file_1"))
+            .run_and_log()?;
+        assert_eq!(output, "abcdefghij\n");
 
+        let output = cmd("rm").arg(mount_directory.join("{ .trash }").join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("ls").arg(mount_directory.join("{ .trash }"))
+            .run_and_log()?;
+        assert_eq!(output, "");
         let output = cmd("ls").arg(mount_directory)
             .run_and_log()?;
-        assert_eq!(output, "file_2\ntag_1\ntag_3\n");
+        assert_eq!(output, "tag_1\n");
 
-        let output = cmd("rmdir").arg(mount_directory.join("tag_1"))
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn browsing_tag_directories() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir")
+            .arg(mount_directory.join("tag_1"))
+            .arg(mount_directory.join("tag_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("touch")
+            .arg(mount_directory.join("tag_1").join("file_1"))
+            .arg(mount_directory.join("{ tag_1, tag_2 }").join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("ls").arg(mount_directory.join("tag_1"))
+            .run_and_log()?;
+        assert_eq!(output, "file_1\ntag_1\ntag_2\n");
+        let output = cmd("ls").arg(mount_directory.join("{ tag_1 }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_1\ntag_1\ntag_2\n");
+        let output = cmd("ls").arg(mount_directory.join("tag_1").join("tag_2"))
+            .run_and_log()?;
+        assert_eq!(output, "file_2\ntag_1\ntag_2\n");
+        let output = cmd("ls").arg(mount_directory.join("tag_2").join("tag_1"))
+            .run_and_log()?;
+        assert_eq!(output, "file_2\ntag_1\ntag_2\n");
+
+        let echo_into = OpenOptions::new()
+            .write(true)
+            .open(mount_directory.join("tag_1").join("tag_2").join("file_2"))?;
+        let output = cmd("echo").arg("abcdefghij")
+            .stdout(echo_into)
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("cat").arg(mount_directory.join("{ tag_1, tag_2 }").join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "abcdefghij\n");
+
+        let output = cmd("mv")
+            .arg(mount_directory.join("tag_1").join("file_1"))
+            .arg(mount_directory.join("tag_2").join("file_1"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("rm").arg(mount_directory.join("tag_2").join("tag_1").join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("ls").arg(mount_directory.join("tag_2"))
+            .run_and_log()?;
+        assert_eq!(output, "file_1\ntag_2\n");
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn nesting_namespaces() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir")
+            .arg(mount_directory.join("tag_1"))
+            .arg(mount_directory.join("tag_2"))
+            .arg(mount_directory.join("tag_3"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("touch")
+            .arg(mount_directory.join("{ tag_1 }").join("file_1"))
+            .arg(mount_directory.join("{ tag_1, tag_2 }").join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("ls").arg(mount_directory.join("{ tag_1 }").join("{ tag_2 }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_2\ntag_1\ntag_2\n");
+
+        let tags_1_2 = mount_directory.join("{ tag_1, tag_2 }");
+        let output = cmd("mv")
+            .arg(tags_1_2.join("file_2"))
+            .arg(tags_1_2.join("{ tag_3 }").join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("ls").arg(mount_directory.join("{ tag_1, tag_2, tag_3 }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_2\ntag_1\ntag_2\ntag_3\n");
+
+        let tags_1_2_3 = mount_directory.join("{ tag_1, tag_2, tag_3 }");
+        let output = cmd("mv")
+            .arg(tags_1_2_3.join("file_2"))
+            .arg(tags_1_2_3.join("{ ~tag_2 }").join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("ls").arg(mount_directory.join("{ tag_1, tag_3 }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_2\ntag_1\ntag_3\n");
+
+        cmd("ls").arg(mount_directory.join("{ tag_1 }").join("{ ~tag_4 }"))
+            .run_and_log()
+            .expect_err("To not have created `tag_4`.");
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn using_relative_tokens_in_namespaces() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir")
+            .arg(mount_directory.join("tag_1"))
+            .arg(mount_directory.join("tag_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("touch")
+            .arg(mount_directory.join("{ tag_1 }").join("file_1"))
+            .arg(mount_directory.join("{ tag_1, tag_2 }").join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let tag_1 = mount_directory.join("{ tag_1 }");
+        let output = cmd("mv")
+            .arg(tag_1.join("{ ., tag_2 }").join("file_2"))
+            .arg(&tag_1)
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("ls").arg(&tag_1)
+            .run_and_log()?;
+        assert_eq!(output, "file_1\nfile_2\ntag_1\n");
+
+        let output = cmd("mv")
+            .arg(tag_1.join("file_1"))
+            .arg(tag_1.join("{ ., !tag_1 }"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("ls").arg(mount_directory.join("{ ., tag_1 }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_2\ntag_1\n");
+        let output = cmd("ls").arg(mount_directory)
+            .run_and_log()?;
+        assert_eq!(output, "file_1\ntag_1\ntag_2\n");
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn renaming_over_files() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir").arg(mount_directory.join("tag_1"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("touch")
+            .arg(mount_directory.join("file_1"))
+            .arg(mount_directory.join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let echo_into = OpenOptions::new()
+            .write(true)
+            .open(mount_directory.join("file_1"))?;
+        let output = cmd("echo").arg("abcdefghij")
+            .stdout(echo_into)
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("mv").arg("--no-clobber")
+            .arg(mount_directory.join("file_1"))
+            .arg(mount_directory.join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("ls").arg(mount_directory)
+            .run_and_log()?;
+        assert_eq!(output, "file_1\nfile_2\ntag_1\n");
+
+        let output = cmd("mv")
+            .arg(mount_directory.join("file_1"))
+            .arg(mount_directory.join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("ls").arg(mount_directory)
+            .run_and_log()?;
+        assert_eq!(output, "file_2\ntag_1\n");
+        let output = cmd("cat").arg(mount_directory.join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "abcdefghij\n");
+
+        let output = cmd("mv")
+            .arg(mount_directory.join("file_2"))
+            .arg(mount_directory.join("{ tag_1 }").join("file_3"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("ls").arg(mount_directory.join("{ tag_1 }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_3\ntag_1\n");
+        let output = cmd("mv")
+            .arg(mount_directory.join("{ tag_1 }").join("file_3"))
+            .arg(mount_directory.join("file_1"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("ls").arg(mount_directory)
+            .run_and_log()?;
+        assert_eq!(output, "file_1\ntag_1\n");
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn renaming_onto_existing_tags() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir")
+            .arg(mount_directory.join("tag_1"))
+            .arg(mount_directory.join("tag_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let replacing = fs::rename(mount_directory.join("tag_1"),
+            mount_directory.join("tag_2"));
+        assert_eq!(replacing.unwrap_err().raw_os_error(), Some(EEXIST));
+        let last_error_path = mount_directory.join(".tfs").join("last_error");
+        assert!(fs::read_to_string(&last_error_path)?.contains("rename failed with errno \
+            `17`. Tag `tag_2` already exists."));
+        let not_replacing = renameat2(None, &mount_directory.join("tag_1"), None,
+            &mount_directory.join("tag_2"), RenameFlags::RENAME_NOREPLACE);
+        assert_eq!(not_replacing.unwrap_err() as i32, EEXIST);
+
+        let output = cmd("ls").arg(mount_directory)
+            .run_and_log()?;
+        assert_eq!(output, "tag_1\ntag_2\n");
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn linking_files_into_namespaces() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir")
+            .arg(mount_directory.join("tag_1"))
+            .arg(mount_directory.join("tag_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("touch").arg(mount_directory.join("{ tag_1 }").join("file_1"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let echo_into = OpenOptions::new()
+            .write(true)
+            .open(mount_directory.join("{ tag_1 }").join("file_1"))?;
+        let output = cmd("echo").arg("abcdefghij")
+            .stdout(echo_into)
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("cp").arg("--link")
+            .arg(mount_directory.join("{ tag_1 }").join("file_1"))
+            .arg(mount_directory.join("{ tag_2 }"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("ls").arg(mount_directory.join("{ tag_1, tag_2 }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_1\ntag_1\ntag_2\n");
+        let output = cmd("cat").arg(mount_directory.join("{ tag_1, tag_2 }").join("file_1"))
+            .run_and_log()?;
+        assert_eq!(output, "abcdefghij\n");
+
+        cmd("ln")
+            .arg(mount_directory.join("{ tag_1, tag_2 }").join("file_1"))
+            .arg(mount_directory.join("file_2"))
+            .run_and_log()
+            .expect_err("To not be able to rename by linking.");
+        let output = cmd("ls").arg(mount_directory)
+            .run_and_log()?;
+        assert_eq!(output, "tag_1\ntag_2\n");
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn caching_with_readdir_plus() {
+    setup_tracing();
+
+    let caching = CachingPolicy {
+        ttls: EntryTtls {
+            root: Duration::from_secs(60),
+            file: Duration::from_secs(60),
+            tag: Duration::from_secs(60),
+            namespace: Duration::from_secs(60)
+        },
+        writeback_cache: true,
+        keep_cache: true,
+        readdir_plus: true
+    };
+    with_caching_tfs_mount(caching, |mount_directory| {
+        let output = cmd("mkdir")
+            .arg(mount_directory.join("tag_1"))
+            .arg(mount_directory.join("tag_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("touch")
+            .arg(mount_directory.join("tag_1").join("file_1"))
+            .arg(mount_directory.join("{ tag_1, tag_2 }").join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("ls").arg(mount_directory.join("tag_1"))
+            .run_and_log()?;
+        assert_eq!(output, "file_1\ntag_1\ntag_2\n");
+        // Listed tags have to be entered as nested namespaces, not as themselves.
+        let output = cmd("ls").arg(mount_directory.join("tag_1").join("tag_2"))
+            .run_and_log()?;
+        assert_eq!(output, "file_2\ntag_1\ntag_2\n");
+
+        let echo_into = OpenOptions::new()
+            .write(true)
+            .open(mount_directory.join("tag_1").join("file_1"))?;
+        let output = cmd("echo").arg("abcdefghij")
+            .stdout(echo_into)
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("cat").arg(mount_directory.join("{ tag_1 }").join("file_1"))
+            .run_and_log()?;
+        assert_eq!(output, "abcdefghij\n");
+
+        // Cached entries are invalidated, however long their TTLs.
+        let output = cmd("mv")
+            .arg(mount_directory.join("tag_1").join("file_1"))
+            .arg(mount_directory.join("tag_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("ls").arg(mount_directory.join("tag_1"))
+            .run_and_log()?;
+        assert_eq!(output, "tag_1\ntag_2\n");
+        cmd("stat").arg(mount_directory.join("tag_1").join("file_1"))
+            .run_and_log()
+            .expect_err("To have moved out of `tag_1`.");
+        let output = cmd("cat").arg(mount_directory.join("tag_2").join("file_1"))
+            .run_and_log()?;
+        assert_eq!(output, "abcdefghij\n");
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn reporting_statistics_and_links() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir")
+            .arg(mount_directory.join("tag_1"))
+            .arg(mount_directory.join("tag_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("touch")
+            .arg(mount_directory.join("file_1"))
+            .arg(mount_directory.join("{ tag_1, tag_2 }").join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("ls").arg("-a").arg(mount_directory.join("tag_1"))
+            .run_and_log()?;
+        assert_eq!(output, ".\n..\ntag_1\ntag_2\n");
+        let output = cmd("stat").arg("--format=%h")
+            .arg(mount_directory)
+            .arg(mount_directory.join("file_1"))
+            .arg(mount_directory.join("tag_1"))
+            .arg(mount_directory.join("{ tag_1, tag_2 }"))
+            .run_and_log()?;
+        assert_eq!(output, "4\n1\n1\n1\n");
+        // Going any deeper, `find` rightly sees `tag_1` leading back to the same namespace.
+        let output = cmd("find").arg(".").arg("-maxdepth").arg("1")
+            .current_dir(mount_directory.join("{ tag_1, tag_2 }"))
+            .run_and_log()?;
+        assert_eq!(output, ".\n./file_2\n./tag_1\n./tag_2\n");
+
+        // Free inodes and blocks come from wherever the delegate storage is.
+        let output = cmd("stat").arg("--file-system").arg("--format=%c %d %l")
+            .arg(mount_directory)
+            .run_and_log()?;
+        let statistics: Vec<u64> = output.split_whitespace()
+            .map(|statistic| statistic.parse().unwrap())
+            .collect();
+        assert_eq!(statistics[0] - statistics[1], 4);
+        assert_eq!(statistics[2], 255);
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn mounting_read_only() {
+    setup_tracing();
+
+    with_prepared_tfs_mount(|tag_filesystem| {
+        let (owner, group) = (get_current_uid(), get_current_gid());
+        let tag_inode = tag_filesystem.get_free_tag_inode()?;
+        tag_filesystem.add_tag(TfsTag::builder()
+            .name("tag_1")
+            .inode(tag_inode)
+            .owner(owner)
+            .group(group)
+            .build()?)?;
+        let file_inode = tag_filesystem.get_free_file_inode()?;
+        tag_filesystem.add_file(TfsFile::builder()
+            .name("file_1")
+            .inode(file_inode)
+            .tags(TagInodes::from(tag_inode))
+            .owner(owner)
+            .group(group)
+            .build()?)?;
+        tag_filesystem.write_to_file(&file_inode, 0, b"content")?;
+        tag_filesystem.set_is_read_only(true);
+        Ok(())
+    }, |mount_directory| {
+        let file_path = mount_directory.join("tag_1").join("file_1");
+        let output = cmd("cat").arg(&file_path)
+            .run_and_log()?;
+        assert_eq!(output, "content");
+
+        let changes = [
+            fs::create_dir(mount_directory.join("tag_2")),
+            File::create(mount_directory.join("file_2")).map(drop),
+            fs::write(&file_path, "changed"),
+            OpenOptions::new().append(true).open(&file_path)
+                .and_then(|mut file| file.write_all(b"changed")),
+            fs::rename(&file_path, mount_directory.join("file_1")),
+            fs::hard_link(&file_path, mount_directory.join("file_1")),
+            fs::remove_file(&file_path),
+            fs::remove_dir(mount_directory.join("tag_1"))
+        ];
+        for change in changes {
+            assert_eq!(change.unwrap_err().raw_os_error(), Some(EROFS));
+        }
+        let output = cmd("cat").arg(&file_path)
+            .run_and_log()?;
+        assert_eq!(output, "content");
+
+        Ok(())
+    }).unwrap();
+}
+
+#[test]
+fn doing_random_chained_interactions() {
+    setup_tracing();
+
+    with_tfs_mount(|mount_directory| {
+        let output = cmd("mkdir")
+            .arg(mount_directory.join("tag_1"))
+            .arg(mount_directory.join("tag_2"))
+            .arg(mount_directory.join("tag_3"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("rmdir").arg(mount_directory.join("tag_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("touch").arg(mount_directory.join("{ tag_1 }").join("file_1"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+        let output = cmd("touch").arg(mount_directory.join("file_2"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("ls").arg(mount_directory.join("{ tag_1 }"))
+            .run_and_log()?;
+        assert_eq!(output, "file_1\ntag_1\n");
+        let output = cmd("ls").arg(mount_directory)
+            .run_and_log()?;
+        assert_eq!(output, "file_2\ntag_1\ntag_3\n");
+
+        let output = cmd("mkdir").arg(mount_directory.join("tag_4"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("ls").arg(mount_directory)
+            .run_and_log()?;
+        assert_eq!(output, "file_2\ntag_1\ntag_3\ntag_4\n");
+
+        let output = cmd("rmdir").arg(mount_directory.join("tag_4"))
+            .run_and_log()?;
+        assert_eq!(output, "");
+
+        let output = cmd("ls").arg(mount_directory)
+            .run_and_log()?;
+        assert_eq!(output, "file_2\ntag_1\ntag_3\n");
+
+        cmd("rmdir").arg(mount_directory.join("tag_1"))
+            .run_and_log()
+            .expect_err("To refuse deleting a tag that's on `file_1`.");
+        let output = cmd("mv")
+            .arg(mount_directory.join("tag_1"))
+            .arg(mount_directory.join("{ .trash }").join("tag_1"))
             .run_and_log()?;
         assert_eq!(output, "");
 
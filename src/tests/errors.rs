@@ -1,4 +1,8 @@
-use crate::{errors::{AnyError, ResultBtAny}, unwrap_or};
+use libc::{EEXIST, EINVAL, EIO, ENAMETOOLONG, ENOENT, ENOTEMPTY, EROFS};
+use tracing::Level;
+
+use crate::{errors::{AnyError, ResultBtAny, TfsError}, filesystem::TagFilesystem,
+    names::{check_tag_name, MAX_NAME_LENGTH}, unwrap_or};
 
 #[test]
 fn executing_unwrap_or_on_result() {
@@ -34,3 +38,26 @@ fn executing_unwrap_or_on_option() {
     });
     assert!(did_execute);
 }
+
+#[test]
+fn mapping_tfs_errors_to_errno() {
+    let mut tag_filesystem = TagFilesystem::new();
+    let e = tag_filesystem.delete_tag("tag_1").unwrap_err();
+    assert!(matches!(e.get(), TfsError::NotFound(_)));
+    assert_eq!((e.get_errno(), e.get_level()), (ENOENT, Level::INFO));
+
+    let e = tag_filesystem.insert_namespace(String::from("{ size>ten }")).unwrap_err();
+    assert!(matches!(e.get(), TfsError::InvalidName(_)));
+
+    assert_eq!(TfsError::AlreadyExists(String::from("tag_1")).get_errno(), EEXIST);
+    assert_eq!(TfsError::NotEmpty(String::from("tag_1")).get_errno(), ENOTEMPTY);
+    assert_eq!(TfsError::InvalidArgument(String::from("offset")).get_errno(), EINVAL);
+    let e = TfsError::ReadOnly(String::from("tag_1"));
+    assert_eq!((e.get_errno(), e.get_level()), (EROFS, Level::DEBUG));
+    let e = TfsError::from(AnyError::from("Storage failed."));
+    assert_eq!((e.get_errno(), e.get_level()), (EIO, Level::ERROR));
+    let e = TfsError::from(check_tag_name(&"t".repeat(MAX_NAME_LENGTH + 1))
+        .unwrap_err()
+        .get_owned());
+    assert_eq!(e.get_errno(), ENAMETOOLONG);
+}
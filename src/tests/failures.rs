@@ -0,0 +1,52 @@
+use std::time::{Duration, SystemTime};
+
+use libc::{EEXIST, ENOENT};
+
+use crate::failures::{Failure, FailureLog};
+
+fn get_failure(seconds: u64, errno: i32) -> Failure {
+    Failure {
+        when_failed: SystemTime::UNIX_EPOCH + Duration::from_secs(seconds),
+        operation: "mkdir",
+        errno,
+        message: format!("Failure `{seconds}`.")
+    }
+}
+
+#[test]
+fn keeping_recent_failures_per_process() {
+    let mut failure_log = FailureLog::default();
+    for seconds in 0..6 {
+        failure_log.record(1, get_failure(seconds, EEXIST));
+    }
+    let kept: Vec<_> = failure_log.get_by_pid(1)
+        .map(|failure| failure.when_failed)
+        .collect();
+    assert_eq!(kept, (2..6).map(|seconds| SystemTime::UNIX_EPOCH
+        + Duration::from_secs(seconds)).collect::<Vec<_>>());
+    assert_eq!(failure_log.get_by_pid(2).count(), 0);
+
+    for pid in 2..=16 {
+        failure_log.record(pid, get_failure(100 + u64::from(pid), ENOENT));
+    }
+    failure_log.record(17, get_failure(200, ENOENT));
+    assert_eq!(failure_log.get_by_pid(1).count(), 0);
+    assert_eq!(failure_log.get_by_pid(2).count(), 1);
+    assert_eq!(failure_log.get_by_pid(17).count(), 1);
+}
+
+#[test]
+fn rendering_failures() {
+    let mut failure_log = FailureLog::default();
+    assert_eq!(failure_log.render(), "");
+    failure_log.record(u32::MAX, get_failure(0, EEXIST));
+    failure_log.record(u32::MAX - 1, get_failure(60, ENOENT));
+    failure_log.record(u32::MAX, get_failure(30, EEXIST));
+    assert_eq!(failure_log.render(), format!("\
+        Process `{}` (`unknown`):\n  \
+        1970-01-01T00:01:00Z mkdir failed with errno `2`. Failure `60`.\n\
+        Process `{}` (`unknown`):\n  \
+        1970-01-01T00:00:00Z mkdir failed with errno `17`. Failure `0`.\n  \
+        1970-01-01T00:00:30Z mkdir failed with errno `17`. Failure `30`.\n",
+        u32::MAX - 1, u32::MAX));
+}
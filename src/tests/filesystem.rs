@@ -0,0 +1,164 @@
+use std::{collections::HashSet, path::PathBuf, sync::mpsc};
+
+use fuser::FUSE_ROOT_ID;
+use libc::{EEXIST, ENOENT};
+use tempfile::tempdir;
+
+use crate::{files::{IndexedFiles, TfsFile}, filesystem::{RenameMode, TagFilesystem},
+    inodes::{FileInode, TagInode, TagInodes}, invalidations::Invalidation,
+    stores::StoreLocation, tags::TfsTag};
+
+#[test]
+fn renaming_over_files() {
+    let mut tag_filesystem = TagFilesystem::new();
+    tag_filesystem.add_tag(TfsTag::builder()
+        .name("tag_1")
+        .inode(TagInode::try_from(4).unwrap())
+        .owner(1000)
+        .group(1000)
+        .build().unwrap())
+        .unwrap();
+    let tag_inodes = TagInodes::from(TagInode::try_from(4).unwrap());
+    for (file_name, inode_id, file_tags) in [("file_1", 3, TagInodes::new()),
+        ("file_2", 6, TagInodes::new()), ("file_1", 9, tag_inodes.clone())]
+    {
+        tag_filesystem.add_file(TfsFile::builder()
+            .name(file_name)
+            .inode(FileInode::try_from(inode_id).unwrap())
+            .tags(file_tags)
+            .owner(1000)
+            .group(1000)
+            .build().unwrap())
+            .unwrap();
+    }
+    let e = tag_filesystem.move_file(&TagInodes::new(), "file_1", TagInodes::new(),
+        String::from("file_2"), RenameMode::NoReplace)
+        .unwrap_err();
+    assert_eq!(e.get_errno(), EEXIST);
+    let e = tag_filesystem.move_file(&TagInodes::new(), "file_1", TagInodes::new(),
+        String::from("file_3"), RenameMode::Exchange)
+        .unwrap_err();
+    assert_eq!(e.get_errno(), ENOENT);
+
+    tag_filesystem.move_file(&TagInodes::new(), "file_1", tag_inodes.clone(),
+        String::from("file_1"), RenameMode::Exchange)
+        .unwrap();
+    assert_eq!(get_inode_id(tag_filesystem.get_files(), "file_1", &TagInodes::new()), Some(9));
+    assert_eq!(get_inode_id(tag_filesystem.get_files(), "file_1", &tag_inodes), Some(3));
+
+    tag_filesystem.move_file(&TagInodes::new(), "file_2", TagInodes::new(),
+        String::from("file_1"), RenameMode::Replace)
+        .unwrap();
+    assert_eq!(get_inode_id(tag_filesystem.get_files(), "file_1", &TagInodes::new()), Some(6));
+    assert_eq!(get_inode_id(tag_filesystem.get_files(), "file_2", &TagInodes::new()), None);
+    assert_eq!(tag_filesystem.get_free_file_inode().unwrap().get_id(), 9);
+}
+
+#[test]
+fn invalidating_cached_entries() {
+    let mut tag_filesystem = TagFilesystem::new();
+    let (invalidations, to_invalidate) = mpsc::channel();
+    tag_filesystem.set_invalidations(invalidations);
+    for (tag_name, inode_id) in [("tag_1", 4), ("tag_2", 7)] {
+        tag_filesystem.add_tag(TfsTag::builder()
+            .name(tag_name)
+            .inode(TagInode::try_from(inode_id).unwrap())
+            .owner(1000)
+            .group(1000)
+            .build().unwrap())
+            .unwrap();
+    }
+    let tag_inodes = TagInodes::from(TagInode::try_from(4).unwrap());
+    tag_filesystem.add_file(TfsFile::builder()
+        .name("file_1")
+        .inode(FileInode::try_from(3).unwrap())
+        .tags(tag_inodes.clone())
+        .owner(1000)
+        .group(1000)
+        .build().unwrap())
+        .unwrap();
+    let namespace_inode = tag_filesystem.insert_namespace(String::from("{ tag_1 }"))
+        .unwrap()
+        .get_id();
+    tag_filesystem.remember_lookup(namespace_inode);
+
+    tag_filesystem.move_file(&tag_inodes, "file_1",
+        TagInodes::from(TagInode::try_from(7).unwrap()), String::from("file_2"),
+        RenameMode::Replace)
+        .unwrap();
+    let invalidated: HashSet<_> = to_invalidate.try_iter().collect();
+    for parent_inode in [FUSE_ROOT_ID, namespace_inode] {
+        for name in ["file_1", "file_2", "tag_1", "tag_2"] {
+            assert!(invalidated.contains(&Invalidation::Entry {
+                parent_inode,
+                name: String::from(name)
+            }), "`{name}` under `{parent_inode}` to be invalidated.");
+        }
+    }
+    assert!(invalidated.contains(&Invalidation::Inode(3)));
+
+    tag_filesystem.rename_tag("tag_1", String::from("tag_3")).unwrap();
+    let invalidated: HashSet<_> = to_invalidate.try_iter().collect();
+    assert!(invalidated.contains(&Invalidation::Entry {
+        parent_inode: FUSE_ROOT_ID,
+        name: String::from("{ tag_1 }")
+    }));
+    assert!(invalidated.contains(&Invalidation::Inode(4)));
+}
+
+#[test]
+fn counting_directory_links() {
+    let mut tag_filesystem = TagFilesystem::new();
+    for (tag_name, inode_id) in [("tag_1", 4), ("tag_2", 7), ("tag_3", 10)] {
+        tag_filesystem.add_tag(TfsTag::builder()
+            .name(tag_name)
+            .inode(TagInode::try_from(inode_id).unwrap())
+            .owner(1000)
+            .group(1000)
+            .build().unwrap())
+            .unwrap();
+    }
+    let tag_inodes = TagInodes::from([4, 7].into_iter()
+        .map(|inode_id| TagInode::try_from(inode_id).unwrap()));
+    tag_filesystem.add_file(TfsFile::builder()
+        .name("file_1")
+        .inode(FileInode::try_from(3).unwrap())
+        .tags(tag_inodes)
+        .owner(1000)
+        .group(1000)
+        .build().unwrap())
+        .unwrap();
+
+    assert_eq!(tag_filesystem.get_root_link_count(), 5);
+    assert_eq!(tag_filesystem.get_fuser_attributes(3).unwrap().nlink, 1);
+    // Tag directories' subdirectories aren't counted.
+    assert_eq!(tag_filesystem.get_fuser_attributes(4).unwrap().nlink, 1);
+    assert_eq!(tag_filesystem.get_fuser_attributes(10).unwrap().nlink, 1);
+    let namespace_inode = tag_filesystem.insert_namespace(String::from("{ tag_1, tag_2 }"))
+        .unwrap();
+    assert_eq!(tag_filesystem.get_namespace_fuser(&namespace_inode).unwrap().nlink, 1);
+
+    tag_filesystem.trash_tag("tag_3").unwrap();
+    assert_eq!(tag_filesystem.get_root_link_count(), 4);
+    assert_eq!(tag_filesystem.get_fuser_attributes(10).unwrap().nlink, 1);
+}
+
+#[test]
+fn opening_stores_read_only() {
+    let data_directory = tempdir().unwrap();
+    let store_location = StoreLocation::MountPath(PathBuf::from("/mnt/tfs"));
+
+    TagFilesystem::try_open_read_only(&store_location, data_directory.path()).unwrap_err();
+    assert_eq!(data_directory.path().read_dir().unwrap().count(), 0);
+
+    TagFilesystem::try_new(&store_location, data_directory.path()).unwrap();
+    let tag_filesystem = TagFilesystem::try_open_read_only(&store_location,
+        data_directory.path())
+        .unwrap();
+    assert!(tag_filesystem.get_is_read_only());
+}
+
+fn get_inode_id(files: &IndexedFiles, file_name: &str, file_tags: &TagInodes) -> Option<u64> {
+    files.get_by_name_and_tags(file_name, file_tags)
+        .map(|file| file.inode.get_id())
+}
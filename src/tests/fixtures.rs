@@ -1,16 +1,36 @@
-use std::{error::Error, ffi::OsString, path::PathBuf, process::Command,
+use std::{error::Error, ffi::OsString, path::PathBuf, process::Command, sync::mpsc,
     thread::{self, JoinHandle}};
 
-use fuser::{mount2, MountOption};
+use fuser::{spawn_mount2, MountOption};
 use tracing::{info, instrument};
 use mount_watcher::{MountWatcher, WatchControl};
 use tempfile::tempdir;
 
-use crate::{errors::{AnyError, ResultBtAny}, filesystem::TagFilesystem};
+use crate::{caching::CachingPolicy, errors::{AnyError, ResultBtAny},
+    filesystem::TagFilesystem, invalidations::spawn_invalidator,
+    path::get_configuration_directory, stores::StoreLocation};
 
 pub fn with_tfs_mount(to_do: impl FnOnce(&PathBuf) -> ResultBtAny<()>) -> ResultBtAny<()> {
+    with_caching_tfs_mount(CachingPolicy::default(), to_do)
+}
+
+pub fn with_caching_tfs_mount(caching: CachingPolicy,
+    to_do: impl FnOnce(&PathBuf) -> ResultBtAny<()>) -> ResultBtAny<()>
+{
+    with_prepared_tfs_mount(move |tag_filesystem| {
+        tag_filesystem.set_caching(caching);
+        Ok(())
+    }, to_do)
+}
+
+/// Mounts after `prepare` has had the filesystem, e.g., to add files that a
+/// read-only mount can't.
+pub fn with_prepared_tfs_mount(
+    prepare: impl FnOnce(&mut TagFilesystem) -> ResultBtAny<()> + Send + 'static,
+    to_do: impl FnOnce(&PathBuf) -> ResultBtAny<()>) -> ResultBtAny<()>
+{
     let expectation = "Test setup code should work."; 
-    let setup_payload = fuse_setup()
+    let setup_payload = fuse_setup(prepare)
         .expect(expectation);
     let test_result = to_do(&setup_payload.0); 
     fuse_cleanup(setup_payload)
@@ -22,8 +42,9 @@ pub fn with_tfs_mount(to_do: impl FnOnce(&PathBuf) -> ResultBtAny<()>) -> Result
 
 type FusePayload = (PathBuf, JoinHandle<ResultBtAny<()>>);
 
-#[instrument]
-fn fuse_setup() -> Result<FusePayload, Box<(dyn Error + 'static)>> {
+#[instrument(skip(prepare))]
+fn fuse_setup(prepare: impl FnOnce(&mut TagFilesystem) -> ResultBtAny<()> + Send + 'static)
+-> Result<FusePayload, Box<(dyn Error + 'static)>> {
     let temporary_directory = tempdir()?;
     let temporary_directory = temporary_directory
         .path()
@@ -49,11 +70,23 @@ fn fuse_setup() -> Result<FusePayload, Box<(dyn Error + 'static)>> {
     let temporary_directory_ = temporary_directory.clone();
     let mount_handle: JoinHandle<ResultBtAny<()>> = thread::spawn(move || {
         info!("Mounting at `{temporary_directory_:?}`.");
-        Ok(mount2(
-            TagFilesystem::try_new(&temporary_directory_)?,
+        let mut tag_filesystem = TagFilesystem::try_new(
+            &StoreLocation::MountPath(temporary_directory_.clone()),
+            &get_configuration_directory())?;
+        prepare(&mut tag_filesystem)?;
+        let (invalidations, to_invalidate) = mpsc::channel();
+        tag_filesystem.set_invalidations(invalidations);
+        let mount_handle = spawn_mount2(
+            tag_filesystem,
             &temporary_directory_,
             &[MountOption::AutoUnmount, MountOption::AllowRoot]
-        )?)
+        )?;
+        spawn_invalidator(mount_handle.notifier(), to_invalidate);
+        // Not `join`, which would unmount rather than wait for the unmount.
+        match mount_handle.guard.join() {
+            Ok(session_result) => Ok(session_result?),
+            Err(e) => Err(format!("{e:?}"))?
+        }
     });
 
     info!("Waiting for notification that FUSE GFS is mounted.");
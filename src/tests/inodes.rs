@@ -1,4 +1,4 @@
-use crate::inodes::{FileInode, NamespaceInode, TagInode};
+use crate::inodes::{FileInode, InodeAllocator, NamespaceInode, TagInode};
 
 #[test]
 fn creating_file_inodes() {
@@ -53,3 +53,52 @@ fn creating_namespace_inodes_() {
     NamespaceInode::try_from(7)
         .expect_err(expectation);
 }
+
+#[test]
+fn allocating_inodes() {
+    let mut file_inodes = InodeAllocator::<FileInode>::new();
+    let allocate = |file_inodes: &mut InodeAllocator<FileInode>| {
+        let file_inode = file_inodes.get_free().unwrap();
+        file_inodes.mark_used(&file_inode);
+        file_inode.get_id()
+    };
+    assert_eq!(allocate(&mut file_inodes), 3);
+    assert_eq!(allocate(&mut file_inodes), 6);
+    assert_eq!(allocate(&mut file_inodes), 9);
+
+    file_inodes.release(&6.try_into().unwrap());
+    file_inodes.release(&3.try_into().unwrap());
+    assert!(!file_inodes.get_is_inuse(&3.try_into().unwrap()));
+    assert_eq!(file_inodes.get_generation(3), 0);
+    assert_eq!(allocate(&mut file_inodes), 3);
+    assert_eq!(file_inodes.get_generation(3), 1);
+    assert_eq!(allocate(&mut file_inodes), 6);
+    assert_eq!(allocate(&mut file_inodes), 12);
+
+    file_inodes.mark_used(&18.try_into().unwrap());
+    assert_eq!(allocate(&mut file_inodes), 15);
+    assert_eq!(allocate(&mut file_inodes), 21);
+
+    let tag_inodes = InodeAllocator::<TagInode>::new();
+    assert_eq!(tag_inodes.get_free().unwrap().get_id(), 4);
+    let namespace_inodes = InodeAllocator::<NamespaceInode>::new();
+    assert_eq!(namespace_inodes.get_free().unwrap().get_id(), 2);
+}
+
+#[test]
+fn restoring_persisted_inode_allocators() {
+    let mut file_inodes = InodeAllocator::<FileInode>::try_from_persisted(
+        9, [3].into_iter(), [(3, 2)].into_iter()).unwrap();
+    file_inodes.mark_used(&6.try_into().unwrap());
+    assert_eq!(file_inodes.get_free().unwrap().get_id(), 3);
+    file_inodes.mark_used(&3.try_into().unwrap());
+    assert_eq!(file_inodes.get_generation(3), 3);
+    assert_eq!(file_inodes.get_free().unwrap().get_id(), 9);
+
+    let legacy_inodes = InodeAllocator::<FileInode>::try_from_persisted(
+        0, [].into_iter(), [].into_iter()).unwrap();
+    assert_eq!(legacy_inodes.get_free().unwrap().get_id(), 3);
+
+    InodeAllocator::<FileInode>::try_from_persisted(4, [].into_iter(), [].into_iter())
+        .expect_err("To not accept a tag inode as the next file inode.");
+}
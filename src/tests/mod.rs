@@ -1,12 +1,24 @@
+mod caching;
 mod cli;
+mod configuration;
+mod contents;
 mod display;
+mod doctor;
 mod e2e;
 mod errors;
+mod failures;
+mod filesystem;
 mod fixtures;
 mod inodes;
 mod miscellaneous;
+mod mounts;
+mod names;
+mod namespaces;
 mod path;
 mod persistence;
+mod queries;
 mod snapshots;
 mod storage;
+mod stores;
 mod tracing;
+mod trash;
@@ -0,0 +1,76 @@
+use std::{fs, io::Write, process::Command, time::Duration};
+
+use tempfile::tempdir;
+
+use crate::{mounts::{get_is_mounted, get_is_stale, unmount, MountRecord},
+    snapshots::{PersistentSnapshots, TfsSnapshots}, stores::StoreLocation,
+    tests::tracing::setup_tracing};
+
+#[test]
+fn recording_mounts() {
+    setup_tracing();
+    let data_directory = tempdir().unwrap();
+    let data_directory = data_directory.path();
+    let mount_path = tempdir().unwrap();
+    let mount_path = mount_path.path();
+
+    assert_eq!(MountRecord::load(mount_path).unwrap(), None);
+    let store_location = StoreLocation::Named(String::from("work"));
+    let mount_record = MountRecord::new(mount_path, &store_location, data_directory, false);
+    mount_record.save().unwrap();
+    assert_eq!(fs::read_to_string(MountRecord::get_pidfile_path(mount_path)).unwrap(),
+        format!("{}\n", std::process::id()));
+    let loaded_record = MountRecord::load(mount_path).unwrap().unwrap();
+    assert_eq!(loaded_record, mount_record);
+    assert_eq!(loaded_record.store.as_deref(), Some("work"));
+    assert!(loaded_record.get_is_alive());
+    assert!(loaded_record.get_is_mount_process());
+    assert!(!get_is_mounted(mount_path).unwrap());
+    assert!(!get_is_stale(mount_path));
+
+    MountRecord::remove(mount_path).unwrap();
+    assert_eq!(MountRecord::load(mount_path).unwrap(), None);
+    MountRecord::remove(mount_path).unwrap();
+    fs::remove_dir_all(MountRecord::get_record_directory(mount_path)).unwrap();
+}
+
+#[test]
+fn unmounting_leaves_reused_pids_alone() {
+    setup_tracing();
+    let data_directory = tempdir().unwrap();
+    let mount_path = tempdir().unwrap();
+    let mount_path = mount_path.path();
+
+    let mut unrelated_process = Command::new("sleep").arg("30").spawn().unwrap();
+    let mut mount_record = MountRecord::new(mount_path,
+        &StoreLocation::MountPath(mount_path.to_path_buf()), data_directory.path(), false);
+    mount_record.pid = unrelated_process.id().try_into().unwrap();
+    mount_record.save().unwrap();
+    assert!(mount_record.get_is_alive());
+    assert!(!mount_record.get_is_mount_process());
+
+    unmount(mount_path, Duration::from_millis(100)).unwrap();
+    assert!(unrelated_process.try_wait().unwrap().is_none());
+    assert_eq!(MountRecord::load(mount_path).unwrap(), None);
+
+    unrelated_process.kill().unwrap();
+    unrelated_process.wait().unwrap();
+    fs::remove_dir_all(MountRecord::get_record_directory(mount_path)).unwrap();
+}
+
+#[test]
+fn telling_when_last_persisted() {
+    setup_tracing();
+    let snapshot_directory = tempdir().unwrap();
+    let snapshot_directory = snapshot_directory.path().join("snapshots");
+
+    assert_eq!(PersistentSnapshots::get_when_promoted(&snapshot_directory).unwrap(), None);
+    assert!(!snapshot_directory.exists());
+    let snapshots = PersistentSnapshots::try_new(snapshot_directory.clone()).unwrap();
+    assert_eq!(PersistentSnapshots::get_when_promoted(&snapshot_directory).unwrap(), None);
+    snapshots.create_staging().unwrap()
+        .write_all(b"snapshot")
+        .unwrap();
+    snapshots.promote_staging().unwrap();
+    assert!(PersistentSnapshots::get_when_promoted(&snapshot_directory).unwrap().is_some());
+}
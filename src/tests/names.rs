@@ -0,0 +1,78 @@
+use libc::{EEXIST, EINVAL, ENAMETOOLONG};
+
+use crate::{files::TfsFile, filesystem::TagFilesystem, inodes::{FileInode, TagInode},
+    names::{check_file_name, check_tag_name, MAX_NAME_LENGTH}, tags::TfsTag};
+
+#[test]
+fn validating_names() {
+    for file_name in ["file_1", "tag_1, tag_2", "notes{1}.txt", "size>10M", ".hidden"] {
+        assert!(check_file_name(file_name).is_ok(), "`{file_name}` to be valid.");
+    }
+    for file_name in ["", ".", "..", "{ tag_1 }", "a/b", "a\0b"] {
+        assert_eq!(check_file_name(file_name).unwrap_err().get_errno(), EINVAL,
+            "`{file_name}` to be invalid.");
+    }
+    assert_eq!(check_file_name(&"f".repeat(MAX_NAME_LENGTH + 1)).unwrap_err().get_errno(),
+        ENAMETOOLONG);
+
+    for tag_name in ["tag_1", "year:2024", "read-later", "a?b"] {
+        assert!(check_tag_name(tag_name).is_ok(), "`{tag_name}` to be valid.");
+    }
+    for tag_name in ["tag_1, tag_2", "{tag_1}", "year>2020", "rating<3", "?deadline",
+        "~tag_1", "!tag_1", " tag_1", "tag_1 ", ".trash", ".."]
+    {
+        assert_eq!(check_tag_name(tag_name).unwrap_err().get_errno(), EINVAL,
+            "`{tag_name}` to be invalid.");
+    }
+    assert_eq!(check_tag_name(&"t".repeat(MAX_NAME_LENGTH + 1)).unwrap_err().get_errno(),
+        ENAMETOOLONG);
+
+    assert!(TfsTag::builder()
+        .name("tag_1, tag_2")
+        .inode(TagInode::try_from(4).unwrap())
+        .owner(1000)
+        .group(1000)
+        .build()
+        .is_err());
+}
+
+#[test]
+fn refusing_conflicting_names() {
+    let mut tag_filesystem = TagFilesystem::new();
+    tag_filesystem.add_tag(TfsTag::builder()
+        .name("tag_1")
+        .inode(TagInode::try_from(4).unwrap())
+        .owner(1000)
+        .group(1000)
+        .build().unwrap())
+        .unwrap();
+    tag_filesystem.add_file(TfsFile::builder()
+        .name("file_1")
+        .inode(FileInode::try_from(3).unwrap())
+        .owner(1000)
+        .group(1000)
+        .build().unwrap())
+        .unwrap();
+
+    let e = tag_filesystem.add_file(TfsFile::builder()
+        .name("tag_1")
+        .inode(FileInode::try_from(6).unwrap())
+        .owner(1000)
+        .group(1000)
+        .build().unwrap())
+        .unwrap_err();
+    assert_eq!(e.get_errno(), EEXIST);
+    let e = tag_filesystem.add_tag(TfsTag::builder()
+        .name("file_1")
+        .inode(TagInode::try_from(7).unwrap())
+        .owner(1000)
+        .group(1000)
+        .build().unwrap())
+        .unwrap_err();
+    assert_eq!(e.get_errno(), EEXIST);
+
+    let e = tag_filesystem.rename_tag("tag_1", String::from("tag_1, tag_2"))
+        .unwrap_err();
+    assert_eq!(e.get_errno(), EINVAL);
+    assert!(tag_filesystem.get_tags().get_by_name("tag_1").is_some());
+}
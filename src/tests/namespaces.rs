@@ -0,0 +1,77 @@
+use crate::{filesystem::TagFilesystem, inodes::TagInode, tags::TfsTag};
+
+#[test]
+fn deduplicating_and_forgetting_namespaces() {
+    let mut tag_filesystem = TagFilesystem::new();
+    for (tag_name, inode_id) in [("tag_1", 4), ("tag_2", 7)] {
+        tag_filesystem.add_tag(TfsTag::builder()
+            .name(tag_name)
+            .inode(TagInode::try_from(inode_id).unwrap())
+            .owner(1000)
+            .group(1000)
+            .build().unwrap())
+            .unwrap();
+    }
+
+    let namespace_inode = tag_filesystem.insert_namespace(String::from("{ tag_1, tag_2 }"))
+        .unwrap();
+    tag_filesystem.remember_lookup(namespace_inode.get_id());
+    let same_namespace_inode = tag_filesystem.insert_namespace(String::from("{ tag_1, tag_2 }"))
+        .unwrap();
+    tag_filesystem.remember_lookup(same_namespace_inode.get_id());
+    assert_eq!(namespace_inode, same_namespace_inode);
+    assert_eq!(tag_filesystem.get_lookup_count(namespace_inode.get_id()), 2);
+
+    let other_namespace_inode = tag_filesystem.insert_namespace(String::from("{ tag_1 }"))
+        .unwrap();
+    assert_ne!(namespace_inode, other_namespace_inode);
+    assert_eq!(tag_filesystem.get_namespaces().get_all().count(), 2);
+
+    tag_filesystem.forget_lookups(namespace_inode.get_id(), 1);
+    assert!(tag_filesystem.get_namespaces().get_by_inode(&namespace_inode).is_ok());
+    tag_filesystem.forget_lookups(namespace_inode.get_id(), 1);
+    assert!(tag_filesystem.get_namespaces().get_by_inode(&namespace_inode).is_err());
+    assert_eq!(tag_filesystem.get_lookup_count(namespace_inode.get_id()), 0);
+
+    let reused_namespace_inode = tag_filesystem.insert_namespace(String::from("{ tag_2 }"))
+        .unwrap();
+    assert_eq!(reused_namespace_inode, namespace_inode);
+    assert_eq!(tag_filesystem.get_generation(reused_namespace_inode.get_id()), 1);
+}
+
+#[test]
+fn canonicalizing_namespace_spellings() {
+    let mut tag_filesystem = TagFilesystem::new();
+    for (tag_name, inode_id) in [("a", 4), ("b", 7)] {
+        tag_filesystem.add_tag(TfsTag::builder()
+            .name(tag_name)
+            .inode(TagInode::try_from(inode_id).unwrap())
+            .owner(1000)
+            .group(1000)
+            .build().unwrap())
+            .unwrap();
+    }
+
+    let namespace_inodes: Vec<_> = ["{b,a}", "{ a, b }", "{a , b}", "{ b, a, a }"].into_iter()
+        .map(|namespace_string| tag_filesystem.insert_namespace(namespace_string.to_string())
+            .unwrap())
+        .collect();
+    assert!(namespace_inodes.iter().all(|inode| *inode == namespace_inodes[0]));
+    assert_eq!(tag_filesystem.get_namespaces().get_by_inode(&namespace_inodes[0]).unwrap().name,
+        "{ a, b }");
+
+    let predicate_inodes: Vec<_> = ["{ a, size>1K, name:*.txt }", "{name:*.txt,size>1024,a}",
+        "{ ?\"b a\", a }", "{ a, ?\"a b\" }"].into_iter()
+        .map(|namespace_string| tag_filesystem.insert_namespace(namespace_string.to_string())
+            .unwrap())
+        .collect();
+    assert_eq!(predicate_inodes[0], predicate_inodes[1]);
+    assert_eq!(predicate_inodes[2], predicate_inodes[3]);
+    assert_ne!(predicate_inodes[0], predicate_inodes[2]);
+    assert_eq!(tag_filesystem.get_namespaces().get_by_inode(&predicate_inodes[0]).unwrap().name,
+        "{ a, name:*.txt, size>1K }");
+
+    let trash_inode = tag_filesystem.insert_namespace(String::from("{.trash}")).unwrap();
+    assert_eq!(tag_filesystem.get_namespaces().get_by_inode(&trash_inode).unwrap().name,
+        "{ .trash }");
+}
@@ -1,10 +1,20 @@
-use std::io::Cursor;
+use std::{io::Cursor, time::{Duration, SystemTime}};
 
-use crate::{files::TfsFile, persistence::{deserialize_tag_filesystem,
-    serialize_tag_filesystem}, tags::TfsTag};
+use crate::{contents::TfsContent, files::TfsFile, inodes::{FileInode, InodeAllocator,
+    TagInode}, persistence::{deserialize_tag_filesystem, serialize_tag_filesystem},
+    tags::TfsTag, trash::{TfsTrashed, TrashedItem}};
 
 #[test]
 fn running_tag_filesystem_serdeialization() {
+    let mut file_inodes = InodeAllocator::<FileInode>::new();
+    for inode_id in [3, 6, 9, 12] {
+        file_inodes.mark_used(&inode_id.try_into().unwrap());
+    }
+    file_inodes.release(&12.try_into().unwrap());
+    file_inodes.mark_used(&12.try_into().unwrap());
+    file_inodes.release(&12.try_into().unwrap());
+    let tag_inodes = InodeAllocator::<TagInode>::new();
+
     let mut persistence_location = vec![];
     serialize_tag_filesystem(&mut persistence_location, vec![
             &TfsFile::builder()
@@ -12,13 +22,13 @@ fn running_tag_filesystem_serdeialization() {
                 .inode(3.try_into().unwrap())
                 .owner(1000)
                 .group(1000)
-                .build(),
+                .build().unwrap(),
             &TfsFile::builder()
                 .name(String::from("test_file_b"))
                 .inode(6.try_into().unwrap())
                 .owner(1000)
                 .group(1000)
-                .build()
+                .build().unwrap()
         ], 
         vec![
             &TfsTag::builder()
@@ -26,23 +36,51 @@ fn running_tag_filesystem_serdeialization() {
                 .inode(4.try_into().unwrap())
                 .owner(1000)
                 .group(1000)
-                .build(),
+                .build().unwrap(),
             &TfsTag::builder()
                 .name(String::from("test_tag_b"))
                 .inode(7.try_into().unwrap())
                 .owner(1000)
                 .group(1000)
-                .build(),
+                .build().unwrap(),
             &TfsTag::builder()
                 .name(String::from("test_tag_c"))
                 .inode(10.try_into().unwrap())
                 .owner(1000)
                 .group(1000)
-                .build()
-        ]);
-    let (recovered_files, recovered_tags) =
+                .build().unwrap()
+        ],
+        vec![
+            &TfsContent::from_text(6.try_into().unwrap(), "Deadline is on Friday.")
+        ],
+        vec![
+            &TfsTrashed {
+                name: String::from("test_file_c~2"),
+                when_trashed: SystemTime::UNIX_EPOCH + Duration::from_secs(1000),
+                item: TrashedItem::File(TfsFile::builder()
+                    .name(String::from("test_file_c"))
+                    .inode(9.try_into().unwrap())
+                    .owner(1000)
+                    .group(1000)
+                    .build().unwrap())
+            },
+            &TfsTrashed {
+                name: String::from("test_tag_d"),
+                when_trashed: SystemTime::UNIX_EPOCH + Duration::from_secs(2000),
+                item: TrashedItem::Tag(TfsTag::builder()
+                    .name(String::from("test_tag_d"))
+                    .inode(13.try_into().unwrap())
+                    .owner(1000)
+                    .group(1000)
+                    .build().unwrap(), vec![3.try_into().unwrap()])
+            }
+        ],
+        &file_inodes,
+        &tag_inodes);
+    let (recovered_files, recovered_tags, recovered_contents, recovered_trash,
+        recovered_file_inodes, recovered_tag_inodes) =
         deserialize_tag_filesystem(Cursor::new(persistence_location)).unwrap();
-    let (rf, rt) = (recovered_files, recovered_tags);
+    let (rf, rt, rc, rtr) = (recovered_files, recovered_tags, recovered_contents, recovered_trash);
 
     assert_eq!(rf.len(), 2);
     assert_eq!(rf[0].name, "test_file_a");
@@ -57,4 +95,23 @@ fn running_tag_filesystem_serdeialization() {
     assert_eq!(rt[1].inode.get_id(), 7);
     assert_eq!(rt[2].name, "test_tag_c");
     assert_eq!(rt[2].inode.get_id(), 10);
+
+    assert_eq!(rc.len(), 1);
+    assert_eq!(rc[0].inode.get_id(), 6);
+    assert_eq!(rc[0].terms.iter().collect::<Vec<_>>(), ["deadline", "friday", "is", "on"]);
+
+    assert_eq!(rtr.len(), 2);
+    assert_eq!(rtr[0].name, "test_file_c~2");
+    assert_eq!(rtr[0].when_trashed, SystemTime::UNIX_EPOCH + Duration::from_secs(1000));
+    assert!(matches!(&rtr[0].item, TrashedItem::File(file)
+        if file.name == "test_file_c" && file.inode.get_id() == 9));
+    assert_eq!(rtr[1].name, "test_tag_d");
+    assert!(matches!(&rtr[1].item, TrashedItem::Tag(tag, files)
+        if tag.inode.get_id() == 13 && files.len() == 1 && files[0].get_id() == 3));
+
+    assert_eq!(recovered_file_inodes.get_next_id(), 15);
+    assert_eq!(recovered_file_inodes.get_released().collect::<Vec<_>>(), [&12]);
+    assert_eq!(recovered_file_inodes.get_generation(12), 1);
+    assert_eq!(recovered_file_inodes.get_generation(3), 0);
+    assert_eq!(recovered_tag_inodes.get_next_id(), 4);
 }
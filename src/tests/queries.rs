@@ -0,0 +1,153 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{contents::{IndexedContents, TfsContent}, files::TfsFile,
+    queries::{get_is_glob_match, parse_comparison, parse_file_predicate, FilePredicate,
+    TagComparison, TagPredicate}, storage::StubStorage,
+    tags::{IndexedTags, TagValue, TfsTag}};
+
+#[test]
+fn parsing_comparisons() {
+    assert_eq!(parse_comparison("year>=2020"), Some(TagPredicate {
+        key: String::from("year"),
+        comparison: TagComparison::GreaterOrEqual,
+        value: TagValue::from("2020")
+    }));
+    assert_eq!(parse_comparison("rating < 3"), Some(TagPredicate {
+        key: String::from("rating"),
+        comparison: TagComparison::Less,
+        value: TagValue::from("3")
+    }));
+    assert_eq!(parse_comparison("rating:5"), None);
+    assert_eq!(parse_comparison(">5"), None);
+    assert_eq!(parse_comparison("year>="), None);
+
+    assert_eq!(parse_comparison("year>=2020").unwrap().to_string(), "year>=2020");
+}
+
+#[test]
+fn ordering_tag_values() {
+    assert!(TagValue::from("9") < TagValue::from("10"));
+    assert!(TagValue::from("-1") < TagValue::from("0.5"));
+    assert!(TagValue::from("10") < TagValue::from("abc"));
+    assert!(TagValue::from("abc") < TagValue::from("abd"));
+    assert_ne!(TagValue::from("2020"), TagValue::from("2020.0"));
+}
+
+#[test]
+fn getting_tags_by_predicate() {
+    let mut indexed_tags = IndexedTags::new();
+    for (tag_index, tag_name) in ["year:2019", "year:2020", "year:2020.0", "year:2021",
+        "year:unknown", "rating:5", "year"].into_iter().enumerate()
+    {
+        indexed_tags.add(TfsTag::builder()
+            .name(tag_name)
+            .inode((4 + 3 * tag_index as u64).try_into().unwrap())
+            .owner(1000)
+            .group(1000)
+            .build().unwrap())
+            .unwrap();
+    }
+    let get_names = |query_token: &str| {
+        let tag_predicate = parse_comparison(query_token)
+            .unwrap_or(TagPredicate::new_present(query_token));
+        let mut tag_names: Vec<_> = indexed_tags.get_by_predicate(&tag_predicate).0
+            .iter()
+            .map(|inode| indexed_tags.get_by_inode(inode).unwrap().name.clone())
+            .collect();
+        tag_names.sort();
+        tag_names
+    };
+
+    assert_eq!(get_names("year>=2020"), ["year:2020", "year:2020.0", "year:2021"]);
+    assert_eq!(get_names("year>2020"), ["year:2021"]);
+    assert_eq!(get_names("year<=2020"), ["year:2019", "year:2020", "year:2020.0"]);
+    assert_eq!(get_names("year<2020"), ["year:2019"]);
+    assert_eq!(get_names("year>a"), ["year:unknown"]);
+    assert_eq!(get_names("year"), ["year:2019", "year:2020", "year:2020.0", "year:2021",
+        "year:unknown"]);
+    assert!(get_names("month").is_empty());
+
+    assert!(indexed_tags.get_is_key("rating"));
+    indexed_tags.remove_by_name("rating:5");
+    assert!(!indexed_tags.get_is_key("rating"));
+}
+
+#[test]
+fn parsing_file_predicates() {
+    assert_eq!(parse_file_predicate("modified<7d").unwrap(), Some(FilePredicate::Modified(
+        TagComparison::Less, Duration::from_secs(7 * 24 * 60 * 60))));
+    assert_eq!(parse_file_predicate("size >= 1.5K").unwrap(), Some(FilePredicate::Size(
+        TagComparison::GreaterOrEqual, 1536)));
+    assert_eq!(parse_file_predicate("size>10MB").unwrap(), Some(FilePredicate::Size(
+        TagComparison::Greater, 10 * 1024 * 1024)));
+    assert_eq!(parse_file_predicate("name:*.pdf").unwrap(),
+        Some(FilePredicate::Name(String::from("*.pdf"))));
+    assert_eq!(parse_file_predicate("owner:1000").unwrap(), Some(FilePredicate::Owner(1000)));
+
+    assert_eq!(parse_file_predicate("year>=2020").unwrap(), None);
+    assert_eq!(parse_file_predicate("size").unwrap(), None);
+    assert!(parse_file_predicate("size:10").is_err());
+    assert!(parse_file_predicate("size>ten").is_err());
+    assert!(parse_file_predicate("modified<7").is_err());
+    assert!(parse_file_predicate("name:").is_err());
+
+    assert_eq!(parse_file_predicate("?\"Deadline soon\"").unwrap(), Some(FilePredicate::Content(
+        vec![String::from("deadline"), String::from("soon")])));
+    assert_eq!(parse_file_predicate("?deadline").unwrap(), Some(FilePredicate::Content(
+        vec![String::from("deadline")])));
+    assert!(parse_file_predicate("?\"\"").is_err());
+    assert_eq!(parse_file_predicate("?\"deadline soon\"").unwrap().unwrap().to_string(),
+        "?\"deadline soon\"");
+
+    for query_token in ["modified<3d", "modified>=90m", "size>10M", "size<=1536", "name:*.pdf"] {
+        assert_eq!(parse_file_predicate(query_token).unwrap().unwrap().to_string(),
+            query_token);
+    }
+    assert_eq!(parse_file_predicate("modified<7d").unwrap().unwrap().to_string(),
+        "modified<1w");
+}
+
+#[test]
+fn matching_globs() {
+    assert!(get_is_glob_match("*.pdf", "report.pdf"));
+    assert!(get_is_glob_match("*.pdf", ".pdf"));
+    assert!(!get_is_glob_match("*.pdf", "report.pdf.txt"));
+    assert!(get_is_glob_match("report_??.*", "report_01.pdf"));
+    assert!(!get_is_glob_match("report_??.*", "report_1.pdf"));
+    assert!(get_is_glob_match("*a*b*", "xxaxxbxx"));
+    assert!(!get_is_glob_match("*a*b*", "xxbxxaxx"));
+    assert!(get_is_glob_match("*", ""));
+    assert!(!get_is_glob_match("?", ""));
+}
+
+#[test]
+fn checking_files_against_file_predicates() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(30 * 24 * 60 * 60);
+    let tfs_file = TfsFile::builder()
+        .name("report.pdf")
+        .inode(3.try_into().unwrap())
+        .owner(1000)
+        .group(1000)
+        .when_modified(now - Duration::from_secs(2 * 24 * 60 * 60))
+        .build().unwrap();
+    let mut indexed_contents = IndexedContents::new();
+    indexed_contents.add(TfsContent::from_text(tfs_file.inode, "The deadline is near."))
+        .unwrap();
+    let is_satisfied = |query_token: &str| parse_file_predicate(query_token)
+        .unwrap()
+        .unwrap()
+        .is_satisfied_by(&tfs_file, &StubStorage, &indexed_contents, now)
+        .unwrap();
+
+    assert!(is_satisfied("modified<7d"));
+    assert!(!is_satisfied("modified<1d"));
+    assert!(is_satisfied("size<=0"));
+    assert!(!is_satisfied("size>0"));
+    assert!(is_satisfied("name:*.pdf"));
+    assert!(!is_satisfied("name:*.txt"));
+    assert!(is_satisfied("owner:1000"));
+    assert!(!is_satisfied("owner:0"));
+    assert!(is_satisfied("?\"deadline\""));
+    assert!(is_satisfied("?\"Near DEADLINE\""));
+    assert!(!is_satisfied("?\"far deadline\""));
+}
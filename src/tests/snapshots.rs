@@ -10,7 +10,7 @@ fn running_normal_snapshot_cycle() {
 
     let temporary_directory = tempdir().unwrap();
     let persistent_snapshots = PersistentSnapshots::try_new(
-        &temporary_directory.path().to_path_buf())
+        temporary_directory.path().to_path_buf())
         .unwrap();
 
     persistent_snapshots.open_safe().unwrap_err();
@@ -0,0 +1,67 @@
+use std::{fs, io::{Read, Write}, path::PathBuf};
+
+use tempfile::tempdir;
+
+use crate::{snapshots::{PersistentSnapshots, TfsSnapshots},
+    stores::{StoreLocation, StoreLock, StoreRegistry}, tests::tracing::setup_tracing};
+
+#[test]
+fn creating_and_locking_stores() {
+    setup_tracing();
+    let data_directory = tempdir().unwrap();
+    let data_directory = data_directory.path();
+
+    let mut store_registry = StoreRegistry::load(data_directory).unwrap();
+    store_registry.create(data_directory, "work").unwrap();
+    store_registry.create(data_directory, "work").expect_err("To reject taken names.");
+    store_registry.create(data_directory, "a/b").expect_err("To reject invalid names.");
+    let store_registry = StoreRegistry::load(data_directory).unwrap();
+    let store_location = store_registry.get_location("work").unwrap();
+    assert!(store_location.get_delegate_directory(data_directory).is_dir());
+    store_registry.get_location("home").expect_err("To only mount existing stores.");
+
+    let lock_path = store_location.get_lock_path(data_directory);
+    let store_lock = StoreLock::try_acquire(&lock_path).unwrap();
+    StoreLock::try_acquire(&lock_path).expect_err("To let only one mount have a store.");
+    drop(store_lock);
+    StoreLock::try_acquire(&lock_path).unwrap();
+}
+
+#[test]
+fn migrating_mount_path_data() {
+    setup_tracing();
+    let data_directory = tempdir().unwrap();
+    let data_directory = data_directory.path();
+    let mount_path = PathBuf::from("/home/user/tags");
+
+    let old_location = StoreLocation::MountPath(mount_path.clone());
+    let old_snapshots = PersistentSnapshots::try_new(
+        old_location.get_snapshot_directory(data_directory))
+        .unwrap();
+    old_snapshots.create_staging().unwrap()
+        .write_all(b"snapshot")
+        .unwrap();
+    old_snapshots.promote_staging().unwrap();
+    let old_delegate_directory = old_location.get_delegate_directory(data_directory);
+    fs::create_dir_all(&old_delegate_directory).unwrap();
+    fs::write(old_delegate_directory.join("3"), "content").unwrap();
+
+    let mut store_registry = StoreRegistry::load(data_directory).unwrap();
+    store_registry.migrate(data_directory, "work", &mount_path).unwrap();
+    store_registry.migrate(data_directory, "home", &mount_path)
+        .expect_err("To have nothing left to migrate.");
+
+    let new_location = StoreLocation::Named(String::from("work"));
+    let mut snapshot = String::new();
+    PersistentSnapshots::try_new(new_location.get_snapshot_directory(data_directory))
+        .unwrap()
+        .open_safe()
+        .unwrap()
+        .read_to_string(&mut snapshot)
+        .unwrap();
+    assert_eq!(snapshot, "snapshot");
+    assert_eq!(fs::read_to_string(new_location.get_delegate_directory(data_directory)
+        .join("3")).unwrap(), "content");
+    assert_eq!(StoreRegistry::load(data_directory).unwrap().get("work").unwrap()
+        .migrated_from, Some(mount_path));
+}
@@ -9,7 +9,7 @@ static FOR_TRACING_SETUP: OnceLock<()> = OnceLock::new();
 
 pub fn setup_tracing() {
     FOR_TRACING_SETUP.get_or_init(|| {
-        let tracing_setup = configure_tracing();
+        let tracing_setup = configure_tracing("");
         tracing_setup.0.with_writer(TestWriter)
             .init();
         info!("Tracing directive is `{}`.", tracing_setup.1);
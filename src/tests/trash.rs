@@ -0,0 +1,105 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{files::TfsFile, filesystem::TagFilesystem, inodes::{FileInode, TagInode,
+    TagInodes}, tags::TfsTag, trash::{IndexedTrash, TrashedItem}};
+
+#[test]
+fn naming_trashed_items() {
+    let mut indexed_trash = IndexedTrash::new();
+    let now = SystemTime::now();
+    for inode_id in [3, 6, 9] {
+        indexed_trash.add(TrashedItem::File(TfsFile::builder()
+            .name(String::from("file_1"))
+            .inode(inode_id.try_into().unwrap())
+            .owner(1000)
+            .group(1000)
+            .build().unwrap()), now);
+    }
+    indexed_trash.add(TrashedItem::Tag(TfsTag::builder()
+        .name(String::from("tag_1"))
+        .inode(4.try_into().unwrap())
+        .owner(1000)
+        .group(1000)
+        .build().unwrap(), vec![3.try_into().unwrap()]), now);
+
+    let mut trash_names: Vec<_> = indexed_trash.get_all()
+        .map(|trashed| trashed.name.as_str())
+        .collect();
+    trash_names.sort();
+    assert_eq!(trash_names, ["file_1", "file_1~2", "file_1~3", "tag_1"]);
+    assert_eq!(indexed_trash.get_file_inodes().count(), 3);
+    assert!(indexed_trash.get_file_by_inode(&6.try_into().unwrap()).is_some());
+    assert!(indexed_trash.get_tag_by_inode(&4.try_into().unwrap()).is_some());
+
+    indexed_trash.remove_by_name("file_1~2").unwrap();
+    assert!(indexed_trash.get_by_name("file_1~2").is_none());
+    assert_eq!(indexed_trash.add(TrashedItem::File(TfsFile::builder()
+        .name(String::from("file_1"))
+        .inode(12.try_into().unwrap())
+        .owner(1000)
+        .group(1000)
+        .build().unwrap()), now).name, "file_1~2");
+}
+
+#[test]
+fn expiring_trashed_items() {
+    let mut indexed_trash = IndexedTrash::new();
+    let now = SystemTime::now();
+    let day = Duration::from_secs(24 * 60 * 60);
+    for (file_name, inode_id, trashed_ago) in [("file_1", 3, 40 * day), ("file_2", 6, 2 * day)] {
+        indexed_trash.add(TrashedItem::File(TfsFile::builder()
+            .name(String::from(file_name))
+            .inode(inode_id.try_into().unwrap())
+            .owner(1000)
+            .group(1000)
+            .build().unwrap()), now - trashed_ago);
+    }
+
+    let expired = indexed_trash.remove_expired(IndexedTrash::DEFAULT_RETENTION, now);
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].name, "file_1");
+    assert!(indexed_trash.get_by_name("file_2").is_some());
+    assert!(indexed_trash.remove_expired(IndexedTrash::DEFAULT_RETENTION, now).is_empty());
+}
+
+#[test]
+fn restoring_without_purged_tags() {
+    let mut tag_filesystem = TagFilesystem::new();
+    for (tag_name, inode_id) in [("tag_1", 4), ("tag_2", 7)] {
+        tag_filesystem.add_tag(TfsTag::builder()
+            .name(tag_name)
+            .inode(TagInode::try_from(inode_id).unwrap())
+            .owner(1000)
+            .group(1000)
+            .build().unwrap())
+            .unwrap();
+    }
+    let file_tags = TagInodes::from([4, 7].into_iter()
+        .map(|inode_id| TagInode::try_from(inode_id).unwrap()));
+    tag_filesystem.add_file(TfsFile::builder()
+        .name("file_1")
+        .inode(FileInode::try_from(3).unwrap())
+        .tags(file_tags.clone())
+        .owner(1000)
+        .group(1000)
+        .build().unwrap())
+        .unwrap();
+    tag_filesystem.trash_file_by_name_and_tags("file_1", &file_tags).unwrap();
+    tag_filesystem.trash_tag("tag_1").unwrap();
+    tag_filesystem.purge_trashed("tag_1").unwrap();
+
+    let reused_inode = tag_filesystem.get_free_tag_inode().unwrap();
+    assert_eq!(reused_inode.get_id(), 4);
+    tag_filesystem.add_tag(TfsTag::builder()
+        .name("tag_3")
+        .inode(reused_inode)
+        .owner(1000)
+        .group(1000)
+        .build().unwrap())
+        .unwrap();
+    tag_filesystem.restore_trashed("file_1", String::from("file_1"), None).unwrap();
+    let restored_file = tag_filesystem.get_files().get_by_inode(&FileInode::try_from(3)
+        .unwrap())
+        .unwrap();
+    assert_eq!(restored_file.tags, TagInodes::from(TagInode::try_from(7).unwrap()));
+}
@@ -5,13 +5,13 @@ use syslog_tracing::Syslog;
 use tracing::info;
 use tracing_subscriber::{fmt::{format::{DefaultFields, Format}, SubscriberBuilder}, EnvFilter};
 
-use crate::{errors::ResultBtAny, path::get_configuration_directory};
+use crate::{configuration::LoggingConfiguration, errors::ResultBtAny};
 
 const LOG_CONFIG_ENVVAR: &str = "ADDITIONAL_LOG_DIRECTIVES";
 
 const SETUP_TRACING_EXPECTATION: &str = "That it's ok to crash if can't setup tracing.";
 
-pub fn configure_tracing()
+pub fn configure_tracing(additional_directives: &str)
 -> (SubscriberBuilder<DefaultFields, Format, EnvFilter, fn() -> Stderr>, String) {
     let to_binary = current_exe()
         .expect(SETUP_TRACING_EXPECTATION);
@@ -21,6 +21,9 @@ pub fn configure_tracing()
         .to_string_lossy();
     let mut directive = format!("warn,{}=info,{}=info", env!("CARGO_PKG_NAME"),
         binary_name);
+    if !additional_directives.is_empty() {
+        directive += &format!(",{additional_directives}");
+    }
     let overrides = env::var(LOG_CONFIG_ENVVAR);
     if let Ok(overrides) = overrides && !overrides.is_empty() {
         directive += &format!(",{}", overrides);
@@ -39,15 +42,16 @@ pub fn configure_tracing()
     )
 }
 
-pub fn setup_normal_tracing<P: AsRef<Path>>(middle_scoping: P) {
-    let tracing_setup = configure_tracing();
+pub fn setup_normal_tracing<P: AsRef<Path>>(log_directory: &Path, middle_scoping: P,
+    logging: &LoggingConfiguration)
+{
+    let tracing_setup = configure_tracing(&logging.directives);
     let rotating_writer = Mutex::new(FileRotate::new(
-        get_configuration_directory()
-            .join("logs")
+        log_directory
             .join(middle_scoping)
             .join("tfs.log"),
-        AppendCount::new(1),
-        ContentLimit::Bytes(10_000_000),
+        AppendCount::new(logging.kept_files),
+        ContentLimit::Bytes(logging.max_size),
         Compression::None,
         None
     ));
@@ -57,8 +61,8 @@ pub fn setup_normal_tracing<P: AsRef<Path>>(middle_scoping: P) {
     info!("Tracing directive is `{}`.", tracing_setup.1);
 }
 
-pub fn setup_syslog_tracing() -> ResultBtAny<()> {
-    let tracing_setup = configure_tracing();
+pub fn setup_syslog_tracing(logging: &LoggingConfiguration) -> ResultBtAny<()> {
+    let tracing_setup = configure_tracing(&logging.directives);
     let (syslog_options, syslog_facility) = Default::default();
     let logger_name = env!("CARGO_PKG_NAME");
     let syslog_writer = Syslog::new(
@@ -0,0 +1,181 @@
+use std::{collections::HashMap, fmt::Display, time::{Duration, SystemTime}};
+
+use crate::{entries::TfsEntry, errors::ResultBtAny, files::TfsFile,
+    inodes::{FileInode, TagInode}, tags::TfsTag, wrappers::{write_iter, VecWrapper}};
+
+/// Name of the virtual namespace, i.e., `{ .trash }`, that lists trashed items.
+pub const TRASH_NAME: &str = ".trash";
+
+#[derive(Debug)]
+pub enum TrashedItem {
+    /// The file keeps its inode, so its delegate content stays where it was.
+    File(TfsFile),
+    /// The tag, along with the files that carried it when it was deleted.
+    Tag(TfsTag, Vec<FileInode>)
+}
+
+#[derive(Debug)]
+pub struct TfsTrashed {
+    /// Unique within the trash, being the original name, or it suffixed with
+    /// `~2`, `~3`, etc. when that's already taken.
+    pub name: String,
+    pub when_trashed: SystemTime,
+    pub item: TrashedItem
+}
+
+impl TfsTrashed {
+    pub fn get_entry(&self) -> &dyn TfsEntry {
+        match &self.item {
+            TrashedItem::File(tfs_file) => tfs_file,
+            TrashedItem::Tag(tfs_tag, _) => tfs_tag
+        }
+    }
+}
+
+impl Display for TfsTrashed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.item {
+            TrashedItem::File(tfs_file) => write!(f, "{}(file={tfs_file})", self.name),
+            TrashedItem::Tag(tfs_tag, file_inodes) => write!(f, "{}(tag={tfs_tag}, files={})",
+                self.name, VecWrapper(file_inodes.iter().collect()))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct IndexedTrash {
+    trashed: HashMap<String, TfsTrashed>
+}
+
+impl IndexedTrash {
+    /// Unless `trash_retention` is set under `[persistence]`.
+    pub const DEFAULT_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+    const NAME_SUFFIX_SEPARATOR: char = '~';
+
+    pub fn new() -> Self {
+        Self {
+            trashed: HashMap::new()
+        }
+    }
+
+    pub fn get_by_name(&self, trash_name: &str) -> Option<&TfsTrashed> {
+        self.trashed.get(trash_name)
+    }
+
+    pub fn get_all(&self) -> impl Iterator<Item = &TfsTrashed> {
+        self.trashed.values()
+    }
+
+    pub fn get_file_by_inode(&self, file_inode: &FileInode) -> Option<&TfsFile> {
+        self.trashed.values()
+            .find_map(|trashed| match &trashed.item {
+                TrashedItem::File(tfs_file) if tfs_file.inode == *file_inode => Some(tfs_file),
+                _ => None
+            })
+    }
+
+    pub fn get_tag_by_inode(&self, tag_inode: &TagInode) -> Option<&TfsTag> {
+        self.trashed.values()
+            .find_map(|trashed| match &trashed.item {
+                TrashedItem::Tag(tfs_tag, _) if tfs_tag.inode == *tag_inode => Some(tfs_tag),
+                _ => None
+            })
+    }
+
+    /// Inodes of trashed files, which mustn't be handed out again while their
+    /// delegate content is still around.
+    pub fn get_file_inodes(&self) -> impl Iterator<Item = &FileInode> {
+        self.trashed.values()
+            .filter_map(|trashed| match &trashed.item {
+                TrashedItem::File(tfs_file) => Some(&tfs_file.inode),
+                TrashedItem::Tag(..) => None
+            })
+    }
+
+    /// Inodes of trashed tags, which files in the trash might still refer to.
+    pub fn get_tag_inodes(&self) -> impl Iterator<Item = &TagInode> {
+        self.trashed.values()
+            .filter_map(|trashed| match &trashed.item {
+                TrashedItem::File(_) => None,
+                TrashedItem::Tag(tfs_tag, _) => Some(&tfs_tag.inode)
+            })
+    }
+
+    fn get_free_name(&self, original_name: &str) -> String {
+        let mut trash_name = original_name.to_string();
+        let mut suffix = 2;
+        while self.trashed.contains_key(&trash_name) {
+            trash_name = format!("{original_name}{}{suffix}", Self::NAME_SUFFIX_SEPARATOR);
+            suffix += 1;
+        }
+        trash_name
+    }
+
+    pub fn add(&mut self, item: TrashedItem, when_trashed: SystemTime) -> &TfsTrashed {
+        let trash_name = match &item {
+            TrashedItem::File(tfs_file) => self.get_free_name(&tfs_file.name),
+            TrashedItem::Tag(tfs_tag, _) => self.get_free_name(&tfs_tag.name)
+        };
+        self.trashed.entry(trash_name.clone())
+            .or_insert(TfsTrashed {
+                name: trash_name,
+                when_trashed,
+                item
+            })
+    }
+
+    /// Adds back something that was already in the trash, e.g., when loading it.
+    pub fn add_existing(&mut self, to_add: TfsTrashed) -> ResultBtAny<&TfsTrashed> {
+        if self.trashed.contains_key(&to_add.name) {
+            Err(format!("Trash already has an item named `{}`.", to_add.name))?;
+        }
+        Ok(self.trashed.entry(to_add.name.clone())
+            .or_insert(to_add))
+    }
+
+    pub fn remove_by_name(&mut self, trash_name: &str) -> Option<TfsTrashed> {
+        self.trashed.remove(trash_name)
+    }
+
+    /// Drops a tag whose inode is being released from trashed files, so that they
+    /// aren't restored with whatever tag reuses it.
+    pub fn forget_tag(&mut self, tag_inode: &TagInode) {
+        for trashed in self.trashed.values_mut() {
+            if let TrashedItem::File(tfs_file) = &mut trashed.item {
+                tfs_file.tags.0.remove(tag_inode);
+            }
+        }
+    }
+
+    /// Likewise drops a file from trashed tags' files.
+    pub fn forget_file(&mut self, file_inode: &FileInode) {
+        for trashed in self.trashed.values_mut() {
+            if let TrashedItem::Tag(_, file_inodes) = &mut trashed.item {
+                file_inodes.retain(|inode| inode != file_inode);
+            }
+        }
+    }
+
+    pub fn remove_expired(&mut self, retention: Duration, now: SystemTime) -> Vec<TfsTrashed> {
+        let expired_names: Vec<_> = self.trashed.values()
+            .filter(|trashed| now.duration_since(trashed.when_trashed)
+                .is_ok_and(|trashed_for| trashed_for > retention))
+            .map(|trashed| trashed.name.clone())
+            .collect();
+        expired_names.iter()
+            .filter_map(|trash_name| self.trashed.remove(trash_name))
+            .collect()
+    }
+}
+
+impl Default for IndexedTrash {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for IndexedTrash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_iter(f, ('[', ']'), self.trashed.values())
+    }
+}
@@ -1,5 +0,0 @@
-use std::time::Duration;
-
-pub const ANY_TTL: Duration = Duration::from_secs(1);
-
-pub const NO_TTL: Duration = Duration::from_secs(0);